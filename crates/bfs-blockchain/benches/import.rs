@@ -0,0 +1,47 @@
+//! Compares importing an exported chain with owned and borrowed decoding.
+//!
+//! Run with `cargo bench -p bfs-blockchain`.
+
+use bfs_blockchain::{Block, State};
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+
+/// The number of blocks in the exported chain.
+const BLOCKS: u64 = 1_000;
+/// The number of extrinsics in every block.
+const EXTRINSICS: u64 = 100;
+
+/// Export a chain, one encoded block after another. Decoding doesn't check the state roots, so
+/// every block is built on the same state.
+fn exported_chain() -> Vec<Vec<u8>> {
+	let state = State::new(0, 1);
+	let mut tip = Block::genesis(&state);
+	let mut archive = vec![tip.encode()];
+	for height in 1..BLOCKS {
+		tip = tip.child(&state, (0..EXTRINSICS).map(|i| height * EXTRINSICS + i).collect());
+		archive.push(tip.encode());
+	}
+	archive
+}
+
+fn import(c: &mut Criterion) {
+	let archive = exported_chain();
+	let mut group = c.benchmark_group("import");
+	group.bench_function("owned", |b| {
+		b.iter(|| {
+			for bytes in &archive {
+				black_box(Block::decode(black_box(bytes)).unwrap());
+			}
+		})
+	});
+	group.bench_function("borrowed", |b| {
+		b.iter(|| {
+			for bytes in &archive {
+				black_box(Block::decode_borrowed(black_box(bytes)).unwrap());
+			}
+		})
+	});
+	group.finish();
+}
+
+criterion_group!(benches, import);
+criterion_main!(benches);
@@ -0,0 +1,156 @@
+//! Difficulty retargeting for proof of work.
+//!
+//! How quickly blocks are mined against a fixed target depends on how much hash power the miners
+//! have. As miners join, blocks come faster, and as they leave, blocks come slower. To keep a
+//! steady cadence, the chain adjusts its target every so often, based on how long the last period
+//! of blocks took according to their timestamps. Bitcoin does exactly this, once every 2016 blocks,
+//! aiming for ten minutes per block.
+//!
+//! Every header stores the target it was mined against, so anyone can check its proof of work from
+//! the header alone. Checking that the target itself is right also needs the earlier headers of the
+//! period that decided it.
+
+use crate::{hasher::Hasher, Block, Header};
+
+/// The most the target may change by in a single retarget, as a factor in either direction. This
+/// stops one period with wildly wrong timestamps from making the next one trivial or impossible.
+const MAX_ADJUSTMENT: u128 = 4;
+
+/// A retargeting algorithm that adjusts the target once every fixed number of blocks.
+///
+/// The target changes at every height that is a multiple of the interval. The period that decides
+/// the new target is the `interval` blocks before it. Its duration is measured from the timestamp
+/// of its first block to that of its last, which spans one fewer block times than it has blocks.
+/// An interval below two never retargets, since a single block can't be timed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Retargeting {
+	/// The number of blocks between retargets.
+	pub interval: u64,
+	/// The time between blocks that the chain aims for, in milliseconds.
+	pub block_time: u64,
+}
+
+impl Retargeting {
+	/// A target that never changes, so every header must keep its parent's target.
+	pub const FIXED: Retargeting = Retargeting { interval: 0, block_time: 0 };
+
+	/// The target that the child of the last of the given headers must be mined against.
+	///
+	/// The headers must form a chain ending at the child's parent. When the child starts a new
+	/// period, they must include the whole of the previous period, or this returns `None`.
+	pub fn next_target<H: Hasher>(&self, ancestors: &[Header<H>]) -> Option<u64> {
+		let parent = ancestors.last()?;
+		let height = parent.height + 1;
+		if self.interval < 2 || height % self.interval != 0 {
+			return Some(parent.target);
+		}
+
+		let start = ancestors
+			.len()
+			.checked_sub(self.interval as usize)
+			.map(|index| &ancestors[index])
+			.filter(|start| start.height == height - self.interval)?;
+		let actual = parent.timestamp.saturating_sub(start.timestamp) as u128;
+		let expected = ((self.interval - 1) as u128 * self.block_time as u128).max(1);
+
+		// Blocks that came too quickly make the target lower, and so harder to meet.
+		let old = parent.target as u128;
+		let new = (old * actual / expected).clamp(old / MAX_ADJUSTMENT, old * MAX_ADJUSTMENT);
+		Some(new.clamp(1, u64::MAX as u128) as u64)
+	}
+
+	/// Whether the child has the target that its ancestors require, and meets it.
+	pub fn verify<H: Hasher>(&self, ancestors: &[Header<H>], child: &Header<H>) -> bool {
+		self.next_target(ancestors) == Some(child.target) && child.meets_target()
+	}
+
+	/// Whether every header after the first has the right target and meets it.
+	///
+	/// The chain must start early enough to include the period before each retarget, which is
+	/// always the case when it starts at genesis.
+	pub fn verify_chain<H: Hasher>(&self, chain: &[Header<H>]) -> bool {
+		(1..chain.len()).all(|index| self.verify(&chain[..index], &chain[index]))
+	}
+
+	/// Give the block the target that its ancestors require, and mine it until it meets it. Returns
+	/// `None` if the ancestors don't include the period needed to work out the target.
+	///
+	/// Mining changes the consensus digest, so a block must be sealed before it is signed.
+	pub fn seal<H: Hasher>(
+		&self,
+		ancestors: &[Header<H>],
+		mut block: Block<H>,
+	) -> Option<Block<H>> {
+		block.header.target = self.next_target(ancestors)?;
+		while !block.header.meets_target() {
+			block.header.consensus_digest = block.header.consensus_digest.wrapping_add(1);
+		}
+		Some(block)
+	}
+}
+
+#[cfg(test)]
+use crate::{p6_rich_state::State, time::SimulatedClock};
+
+/// Mine a chain from genesis with each block coming the given time after its parent.
+#[cfg(test)]
+pub(crate) fn mine_chain(rule: &Retargeting, block_times: &[u64]) -> Vec<Header> {
+	let state = State { sum: 0, product: 1 };
+	let clock = SimulatedClock::new(0);
+	let mut tip: Block = Block::genesis(&state);
+	let mut headers = vec![tip.header.clone()];
+	for block_time in block_times {
+		clock.advance(*block_time);
+		tip = rule.seal(&headers, tip.child_at(&clock, &state, vec![])).unwrap();
+		headers.push(tip.header.clone());
+	}
+	headers
+}
+
+#[test]
+fn difficulty_fast_blocks_lower_the_target() {
+	let rule = Retargeting { interval: 4, block_time: 1_000 };
+	let chain = mine_chain(&rule, &[500; 8]);
+
+	// Only the first block of each period has a new target.
+	let targets: Vec<u64> = chain.iter().map(|header| header.target).collect();
+	assert_eq!(targets[..4], [u64::MAX; 4]);
+	assert_eq!(targets[4..8], [u64::MAX / 2; 4]);
+	assert_eq!(targets[8], u64::MAX / 4);
+	assert!(rule.verify_chain(&chain));
+}
+
+#[test]
+fn difficulty_slow_blocks_raise_the_target_within_limits() {
+	let rule = Retargeting { interval: 4, block_time: 1_000 };
+	let chain = mine_chain(&rule, &[0, 0, 0, 0, 100_000, 100_000, 100_000, 100_000]);
+
+	// Instant blocks can only lower the target by the maximum adjustment.
+	assert_eq!(chain[4].target, u64::MAX / 4);
+	// Very slow ones can only raise it back by the same factor.
+	assert_eq!(chain[8].target, u64::MAX / 4 * 4);
+	assert!(rule.verify_chain(&chain));
+}
+
+#[test]
+fn difficulty_rejects_wrong_or_unmet_targets() {
+	let rule = Retargeting { interval: 4, block_time: 1_000 };
+	let chain = mine_chain(&rule, &[500; 4]);
+	let (ancestors, child) = chain.split_at(4);
+	assert!(rule.verify(ancestors, &child[0]));
+
+	// A block that kept the old target when it should have retargeted.
+	let mut stale = child[0].clone();
+	stale.target = u64::MAX;
+	assert!(!rule.verify(ancestors, &stale));
+
+	// A block that claims a target it wasn't mined against.
+	let mut unmined = child[0].clone();
+	unmined.target = 0;
+	assert!(!unmined.meets_target());
+	assert!(!rule.verify(ancestors, &unmined));
+	assert!(!ancestors[3].verify_child(&unmined));
+
+	// The previous period is needed to check a retarget.
+	assert_eq!(rule.next_target(&ancestors[2..]), None);
+}
@@ -0,0 +1,268 @@
+//! Human readable forms of the chapter's core types, for command line output, logs, and test
+//! failure messages. Hashes are shown as hex with a `0x` prefix, and headers as a one line summary
+//! of where they sit in the chain.
+//!
+//! Whole chains and fork trees can be drawn too, with [`ChainPretty`] and [`TreePretty`]. Their
+//! `Debug` output is the same drawing, so they make fork choice assertion failures much easier to
+//! read than a dump of every header field.
+
+use crate::{hash, p4_batched_extrinsics, p6_rich_state, ForkChoice, ForkTree, Hash};
+use std::{collections::HashSet, fmt, num::ParseIntError, str::FromStr};
+
+/// A hash that displays and parses as 16 hex digits with a `0x` prefix.
+///
+/// [`Hash`] is a plain `u64`, which displays in decimal. Wrap it in this type to show it the way
+/// hashes are usually shown.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, std::hash::Hash)]
+pub struct HexHash(pub Hash);
+
+impl fmt::Display for HexHash {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		write!(f, "0x{:016x}", self.0)
+	}
+}
+
+/// The reasons that parsing a [`HexHash`] can fail.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ParseHashError {
+	/// The string does not start with `0x`.
+	MissingPrefix,
+	/// The digits after the prefix are not a valid 64 bit hex number.
+	InvalidHex(ParseIntError),
+}
+
+impl FromStr for HexHash {
+	type Err = ParseHashError;
+
+	/// Parse a hash with a `0x` prefix. Leading zeros may be left out.
+	fn from_str(s: &str) -> Result<Self, ParseHashError> {
+		let digits = s.strip_prefix("0x").ok_or(ParseHashError::MissingPrefix)?;
+		u64::from_str_radix(digits, 16).map(HexHash).map_err(ParseHashError::InvalidHex)
+	}
+}
+
+/// Shows the header's height, its own hash, and its parent's hash, like `#2 0x… (parent 0x…)`.
+impl fmt::Display for p4_batched_extrinsics::Header {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		write!(f, "#{} {} (parent {})", self.height, HexHash(hash(self)), HexHash(self.parent))
+	}
+}
+
+/// Shows the header's height, its own hash, and its parent's hash, like `#2 0x… (parent 0x…)`.
+impl fmt::Display for p6_rich_state::Header {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		write!(f, "#{} {} (parent {})", self.height, HexHash(hash(self)), HexHash(self.parent))
+	}
+}
+
+/// Draws a chain of headers, one per line from oldest to newest.
+pub struct ChainPretty<'a>(pub &'a [p4_batched_extrinsics::Header]);
+
+impl fmt::Display for ChainPretty<'_> {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		for header in self.0 {
+			writeln!(f, "{header}")?;
+		}
+		Ok(())
+	}
+}
+
+impl fmt::Debug for ChainPretty<'_> {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		fmt::Display::fmt(self, f)
+	}
+}
+
+/// Draws a fork tree as an ASCII diagram, with each header's children indented below it.
+/// Siblings are shown in order of their hashes, and the headers on the best path, if one is chosen,
+/// are marked with a `*`.
+///
+/// ```text
+/// #0 0x…
+/// ├─ #1 0x… *
+/// │  └─ #2 0x… *
+/// └─ #1 0x…
+/// ```
+pub struct TreePretty<'a> {
+	tree: &'a ForkTree,
+	best_path: HashSet<Hash>,
+}
+
+impl<'a> TreePretty<'a> {
+	/// Draw the tree without marking any path.
+	pub fn new(tree: &'a ForkTree) -> Self {
+		TreePretty { tree, best_path: HashSet::new() }
+	}
+
+	/// Mark the path from the root to the header with the given hash. Nothing is marked if the
+	/// header is not in the tree.
+	pub fn with_best(mut self, tip: Hash) -> Self {
+		let path = self.tree.chain_to(tip).unwrap_or_default();
+		self.best_path = path.iter().map(hash).collect();
+		self
+	}
+
+	/// Mark the path to the leaf chosen by the given fork choice rule.
+	pub fn with_best_by<R: ForkChoice>(self, rule: &R) -> Self {
+		let tip = hash(self.tree.best_leaf_by_chains(rule));
+		self.with_best(tip)
+	}
+}
+
+impl fmt::Display for TreePretty<'_> {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		// Each entry is a header still to draw, the prefix for its line, and the prefix for the
+		// lines of its children. Deep chains would overflow the stack if this were recursive.
+		let mut to_draw = vec![(self.tree.root(), String::new(), String::new())];
+		while let Some((header_hash, line_prefix, child_prefix)) = to_draw.pop() {
+			let header = self.tree.get(header_hash).expect("only headers in the tree are drawn");
+			let marker = if self.best_path.contains(&header_hash) { " *" } else { "" };
+			writeln!(f, "{line_prefix}#{} {}{marker}", header.height, HexHash(header_hash))?;
+
+			let mut children = self.tree.children(header_hash).to_vec();
+			children.sort_unstable();
+			let last = children.len().saturating_sub(1);
+			// Pushed in reverse, so that they are popped and drawn in order.
+			for (i, child) in children.into_iter().enumerate().rev() {
+				let (connector, continuation) =
+					if i == last { ("└─ ", "   ") } else { ("├─ ", "│  ") };
+				to_draw.push((
+					child,
+					format!("{child_prefix}{connector}"),
+					format!("{child_prefix}{continuation}"),
+				));
+			}
+		}
+		Ok(())
+	}
+}
+
+impl fmt::Debug for TreePretty<'_> {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		fmt::Display::fmt(self, f)
+	}
+}
+
+impl ForkTree {
+	/// Render the tree in Graphviz DOT format, with the chain chosen by the given fork choice rule
+	/// highlighted. Each header is labelled with its height and the start of its hash.
+	///
+	/// Save the output to a file and render it with `dot -Tsvg tree.dot -o tree.svg`.
+	pub fn to_dot<R: ForkChoice>(&self, rule: &R) -> String {
+		let best_path: HashSet<Hash> = self
+			.chain_to(hash(self.best_leaf_by_chains(rule)))
+			.expect("the best leaf is in the tree")
+			.iter()
+			.map(hash)
+			.collect();
+
+		let mut dot = String::from("digraph fork_tree {\n\trankdir=LR;\n\tnode [shape=box];\n");
+		let mut to_draw = vec![self.root()];
+		while let Some(header_hash) = to_draw.pop() {
+			let header = self.get(header_hash).expect("only headers in the tree are drawn");
+			let id = HexHash(header_hash);
+			let short_hash = &id.to_string()[..10];
+			let style =
+				if best_path.contains(&header_hash) { ", style=bold, color=red" } else { "" };
+			dot += &format!("\t\"{id}\" [label=\"#{}\\n{short_hash}\"{style}];\n", header.height);
+			if header_hash != self.root() {
+				let style = if best_path.contains(&header_hash) { " [color=red]" } else { "" };
+				dot += &format!("\t\"{}\" -> \"{id}\"{style};\n", HexHash(header.parent));
+			}
+
+			let mut children = self.children(header_hash).to_vec();
+			children.sort_unstable_by(|a, b| b.cmp(a));
+			to_draw.extend(children);
+		}
+		dot += "}\n";
+		dot
+	}
+}
+
+#[test]
+fn display_hashes_round_trip() {
+	for value in [0, 1, 0xdead_beef, u64::MAX] {
+		let shown = HexHash(value).to_string();
+		assert_eq!(shown.len(), 18);
+		assert_eq!(shown.parse(), Ok(HexHash(value)));
+	}
+	assert_eq!(HexHash(255).to_string(), "0x00000000000000ff");
+	assert_eq!("0xff".parse(), Ok(HexHash(255)));
+}
+
+#[test]
+fn display_rejects_malformed_hashes() {
+	assert_eq!("ff".parse::<HexHash>(), Err(ParseHashError::MissingPrefix));
+	assert!(matches!("0xfg".parse::<HexHash>(), Err(ParseHashError::InvalidHex(_))));
+	assert!(matches!("0x".parse::<HexHash>(), Err(ParseHashError::InvalidHex(_))));
+	assert!(matches!("0x1ffffffffffffffff".parse::<HexHash>(), Err(ParseHashError::InvalidHex(_))));
+}
+
+#[test]
+fn display_header_summary() {
+	let g = p4_batched_extrinsics::Header::genesis();
+	let b1 = g.child(hash(&[1]), 1);
+	assert_eq!(b1.to_string(), format!("#1 {} (parent {})", HexHash(hash(&b1)), HexHash(hash(&g))));
+	assert_eq!(g.to_string(), format!("#0 {} (parent 0x0000000000000000)", HexHash(hash(&g))));
+}
+
+#[test]
+fn display_chain_pretty() {
+	let chain = crate::test_utils::linear_chain(2);
+	let expected: String = chain.iter().map(|header| format!("{header}\n")).collect();
+	assert_eq!(ChainPretty(&chain).to_string(), expected);
+	assert_eq!(format!("{:?}", ChainPretty(&chain)), expected);
+}
+
+#[test]
+fn display_tree_pretty_marks_best_path() {
+	let chain = crate::test_utils::linear_chain(2);
+	let mut tree = ForkTree::new(chain[0].clone());
+	for header in &chain[1..] {
+		tree.insert(header.clone());
+	}
+	let [g, b1, b2] = [&chain[0], &chain[1], &chain[2]].map(|header| HexHash(hash(header)));
+	assert_eq!(
+		TreePretty::new(&tree).with_best(hash(&chain[1])).to_string(),
+		format!("#0 {g} *\n└─ #1 {b1} *\n   └─ #2 {b2}\n")
+	);
+
+	let fork = crate::test_utils::Fork::new(1, 2, 1);
+	let mut tree = ForkTree::new(p4_batched_extrinsics::Header::genesis());
+	for header in fork.left_chain().iter().chain(&fork.right).skip(1) {
+		tree.insert(header.clone());
+	}
+	let drawing = TreePretty::new(&tree).with_best_by(&crate::p5_fork_choice::LongestChainRule);
+	let lines: Vec<String> = drawing.to_string().lines().map(String::from).collect();
+	assert_eq!(lines.len(), 5);
+	assert_eq!(lines.iter().filter(|line| line.ends_with(" *")).count(), 4);
+	for header in fork.left_chain() {
+		let shown = format!("#{} {} *", header.height, HexHash(hash(&header)));
+		assert!(lines.iter().any(|line| line.ends_with(&shown)));
+	}
+	assert_eq!(lines.iter().filter(|line| line.contains("├─ ")).count(), 1);
+}
+
+#[test]
+fn display_fork_tree_as_dot() {
+	let fork = crate::test_utils::Fork::new(0, 2, 1);
+	let mut tree = ForkTree::new(p4_batched_extrinsics::Header::genesis());
+	for header in fork.left.iter().chain(&fork.right) {
+		tree.insert(header.clone());
+	}
+	let dot = tree.to_dot(&crate::p5_fork_choice::LongestChainRule);
+
+	assert!(dot.starts_with("digraph fork_tree {"));
+	assert!(dot.ends_with("}\n"));
+	let [g, a1, a2, b1] = [fork.fork_point(), &fork.left[0], &fork.left[1], &fork.right[0]]
+		.map(|header| HexHash(hash(header)));
+	let short = |id: HexHash| id.to_string()[..10].to_string();
+	assert!(
+		dot.contains(&format!("\t\"{g}\" [label=\"#0\\n{}\", style=bold, color=red];", short(g)))
+	);
+	assert!(dot.contains(&format!("\t\"{b1}\" [label=\"#1\\n{}\"];", short(b1))));
+	assert!(dot.contains(&format!("\t\"{g}\" -> \"{a1}\" [color=red];")));
+	assert!(dot.contains(&format!("\t\"{a1}\" -> \"{a2}\" [color=red];")));
+	assert!(dot.contains(&format!("\t\"{g}\" -> \"{b1}\";")));
+	assert_eq!(dot.matches(" -> ").count(), 3);
+}
@@ -0,0 +1,417 @@
+//! A simple binary encoding for the exported headers and blocks, so chains can be exported to an
+//! archive and imported again later. The state is encoded the same way, which is how stateless
+//! blocks ship it as their witness.
+//!
+//! Every encoded header, block and state starts with a version byte. The header is expected to gain
+//! new fields as the lessons progress. When that happens, the new layout gets a new version and the
+//! decoder keeps understanding all the old ones, so archives exported by older code remain
+//! importable.
+//!
+//! All integers are encoded as 8 little-endian bytes, and so are the digests, since only headers
+//! using the default [`FastHasher`](crate::hasher::FastHasher) can be encoded. An optional field
+//! is a single byte, 0 if it is absent or 1 if it is present, followed by the value if there is
+//! one.
+//!
+//! # Versions
+//!
+//! - Version 0 was written before headers recorded when they were authored. Its headers are the
+//!   `parent`, `height`, `extrinsics_root`, `state_root` and `consensus_digest`, and they decode
+//!   with the same defaults as a genesis header: a timestamp of zero, the highest possible target,
+//!   and no author or signature.
+//! - Version 1 follows those fields with the `timestamp`, the `target`, the optional 32 byte
+//!   `author`, and the optional 64 byte `signature`.
+//!
+//! The state has had the same layout, its sum followed by its product, in every version.
+//!
+//! Blocks can also be decoded into a borrowed [`BlockRef`] that reads the extrinsics straight out
+//! of the encoded bytes. This avoids allocating a body for every block when importing a large
+//! archive only to, say, check the headers. The `import` benchmark compares the two, and runs with
+//! `cargo bench -p bfs-blockchain`.
+
+use crate::{p6_rich_state::State, signing::Signature, Block, Header};
+
+/// The version written by the encoder.
+pub const CURRENT_VERSION: u8 = 1;
+
+/// The reasons that decoding can fail.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DecodeError {
+	/// The input ended before the complete item was decoded.
+	UnexpectedEnd,
+	/// The version byte is not one this decoder understands.
+	UnsupportedVersion(u8),
+	/// The byte in front of an optional field was neither 0 nor 1.
+	InvalidOption(u8),
+	/// The item was decoded, but there were bytes left over.
+	TrailingBytes,
+}
+
+/// A cursor over the bytes being decoded.
+struct Input<'a> {
+	bytes: &'a [u8],
+}
+
+impl<'a> Input<'a> {
+	fn read_u8(&mut self) -> Result<u8, DecodeError> {
+		let (first, rest) = self.bytes.split_first().ok_or(DecodeError::UnexpectedEnd)?;
+		self.bytes = rest;
+		Ok(*first)
+	}
+
+	fn read_u64(&mut self) -> Result<u64, DecodeError> {
+		Ok(u64::from_le_bytes(self.read_array()?))
+	}
+
+	fn read_array<const N: usize>(&mut self) -> Result<[u8; N], DecodeError> {
+		Ok(self.read_bytes(N)?.try_into().expect("read exactly N bytes"))
+	}
+
+	fn read_option<T>(
+		&mut self,
+		read: impl FnOnce(&mut Self) -> Result<T, DecodeError>,
+	) -> Result<Option<T>, DecodeError> {
+		match self.read_u8()? {
+			0 => Ok(None),
+			1 => read(self).map(Some),
+			invalid => Err(DecodeError::InvalidOption(invalid)),
+		}
+	}
+
+	fn read_bytes(&mut self, len: usize) -> Result<&'a [u8], DecodeError> {
+		if self.bytes.len() < len {
+			return Err(DecodeError::UnexpectedEnd);
+		}
+		let (taken, rest) = self.bytes.split_at(len);
+		self.bytes = rest;
+		Ok(taken)
+	}
+
+	fn finish(&self) -> Result<(), DecodeError> {
+		if self.bytes.is_empty() {
+			Ok(())
+		} else {
+			Err(DecodeError::TrailingBytes)
+		}
+	}
+}
+
+/// Append an optional field.
+fn write_option(out: &mut Vec<u8>, field: Option<&[u8]>) {
+	match field {
+		None => out.push(0),
+		Some(bytes) => {
+			out.push(1);
+			out.extend_from_slice(bytes);
+		},
+	}
+}
+
+impl Header {
+	/// Encode this header using the current version.
+	pub fn encode(&self) -> Vec<u8> {
+		let mut out = vec![CURRENT_VERSION];
+		self.encode_fields(&mut out);
+		out
+	}
+
+	/// Decode a header of any supported version.
+	pub fn decode(bytes: &[u8]) -> Result<Self, DecodeError> {
+		let mut input = Input { bytes };
+		let version = input.read_u8()?;
+		let header = Self::decode_fields(version, &mut input)?;
+		input.finish()?;
+		Ok(header)
+	}
+
+	fn encode_fields(&self, out: &mut Vec<u8>) {
+		for field in [
+			self.parent,
+			self.height,
+			self.extrinsics_root,
+			self.state_root,
+			self.consensus_digest,
+			self.timestamp,
+			self.target,
+		] {
+			out.extend_from_slice(&field.to_le_bytes());
+		}
+		write_option(out, self.author.as_ref().map(|author| &author[..]));
+		write_option(out, self.signature.as_ref().map(|signature| &signature.0[..]));
+	}
+
+	/// Decode the header fields laid out according to the given version.
+	fn decode_fields(version: u8, input: &mut Input) -> Result<Self, DecodeError> {
+		match version {
+			0 => Ok(Header {
+				parent: input.read_u64()?,
+				height: input.read_u64()?,
+				extrinsics_root: input.read_u64()?,
+				state_root: input.read_u64()?,
+				consensus_digest: input.read_u64()?,
+				timestamp: 0,
+				target: u64::MAX,
+				author: None,
+				signature: None,
+			}),
+			1 => Ok(Header {
+				parent: input.read_u64()?,
+				height: input.read_u64()?,
+				extrinsics_root: input.read_u64()?,
+				state_root: input.read_u64()?,
+				consensus_digest: input.read_u64()?,
+				timestamp: input.read_u64()?,
+				target: input.read_u64()?,
+				author: input.read_option(Input::read_array)?,
+				signature: input.read_option(|input| input.read_array().map(Signature))?,
+			}),
+			unknown => Err(DecodeError::UnsupportedVersion(unknown)),
+		}
+	}
+}
+
+impl Block {
+	/// Encode this block using the current version. The version byte is followed by the header
+	/// fields, the number of extrinsics, and finally the extrinsics themselves.
+	pub fn encode(&self) -> Vec<u8> {
+		let mut out = vec![CURRENT_VERSION];
+		self.header.encode_fields(&mut out);
+		out.extend_from_slice(&(self.body.len() as u64).to_le_bytes());
+		for extrinsic in &self.body {
+			out.extend_from_slice(&extrinsic.to_le_bytes());
+		}
+		out
+	}
+
+	/// Decode a block of any supported version.
+	pub fn decode(bytes: &[u8]) -> Result<Self, DecodeError> {
+		let mut input = Input { bytes };
+		let version = input.read_u8()?;
+		let header = Header::decode_fields(version, &mut input)?;
+		let len = input.read_u64()?;
+		// Don't trust the length prefix for the allocation. It may come from a corrupt archive.
+		let mut body = Vec::new();
+		for _ in 0..len {
+			body.push(input.read_u64()?);
+		}
+		input.finish()?;
+		Ok(Block { header, body })
+	}
+
+	/// Decode a block of any supported version without copying its extrinsics. The returned block
+	/// borrows from `bytes`.
+	pub fn decode_borrowed(bytes: &[u8]) -> Result<BlockRef<'_>, DecodeError> {
+		let mut input = Input { bytes };
+		let version = input.read_u8()?;
+		let header = Header::decode_fields(version, &mut input)?;
+		let len = input.read_u64()?;
+		let body_len = usize::try_from(len)
+			.ok()
+			.and_then(|len| len.checked_mul(8))
+			.ok_or(DecodeError::UnexpectedEnd)?;
+		let body = input.read_bytes(body_len)?;
+		input.finish()?;
+		Ok(BlockRef { header, body: ExtrinsicsRef { bytes: body } })
+	}
+}
+
+impl State {
+	/// Encode this state using the current version. The version byte is followed by the sum and
+	/// then the product.
+	pub fn encode(&self) -> Vec<u8> {
+		let mut out = vec![CURRENT_VERSION];
+		out.extend_from_slice(&self.sum.to_le_bytes());
+		out.extend_from_slice(&self.product.to_le_bytes());
+		out
+	}
+
+	/// Decode a state of any supported version.
+	pub fn decode(bytes: &[u8]) -> Result<Self, DecodeError> {
+		let mut input = Input { bytes };
+		let state = match input.read_u8()? {
+			0 | 1 => State { sum: input.read_u64()?, product: input.read_u64()? },
+			unknown => return Err(DecodeError::UnsupportedVersion(unknown)),
+		};
+		input.finish()?;
+		Ok(state)
+	}
+}
+
+/// A decoded block whose extrinsics still live in the encoded bytes.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BlockRef<'a> {
+	/// The block's header. Headers are small, so they are always decoded.
+	pub header: Header,
+	/// The block's extrinsics, decoded one at a time as they are read.
+	pub body: ExtrinsicsRef<'a>,
+}
+
+impl BlockRef<'_> {
+	/// Copy the extrinsics out of the encoded bytes to make an ordinary block.
+	pub fn to_block(&self) -> Block {
+		Block { header: self.header.clone(), body: self.body.iter().collect() }
+	}
+}
+
+/// The extrinsics of a [`BlockRef`]. The bytes are known to hold a whole number of extrinsics.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ExtrinsicsRef<'a> {
+	bytes: &'a [u8],
+}
+
+impl<'a> ExtrinsicsRef<'a> {
+	/// The number of extrinsics.
+	pub fn len(&self) -> usize {
+		self.bytes.len() / 8
+	}
+
+	/// Whether there are no extrinsics at all.
+	pub fn is_empty(&self) -> bool {
+		self.bytes.is_empty()
+	}
+
+	/// The extrinsic at the given index, if there is one.
+	pub fn get(&self, index: usize) -> Option<u64> {
+		let start = index.checked_mul(8)?;
+		let word = self.bytes.get(start..start.checked_add(8)?)?;
+		Some(u64::from_le_bytes(word.try_into().expect("slice of exactly 8 bytes")))
+	}
+
+	/// Decode the extrinsics in order.
+	pub fn iter(&self) -> impl Iterator<Item = u64> + 'a {
+		self.bytes
+			.chunks_exact(8)
+			.map(|word| u64::from_le_bytes(word.try_into().expect("chunks of exactly 8 bytes")))
+	}
+}
+
+#[cfg(test)]
+fn test_block() -> Block {
+	let state = State::new(6, 9);
+	let clock = crate::time::FixedClock(1_000);
+	Block::genesis(&state).child_at(&clock, &state, vec![1, 2, 3])
+}
+
+/// The size of an unsigned header in the current version, after its version byte.
+#[cfg(test)]
+const UNSIGNED_HEADER_LEN: usize = 7 * 8 + 2;
+
+#[test]
+fn encoding_header_roundtrip() {
+	let header = test_block().header;
+	let encoded = header.encode();
+
+	assert_eq!(encoded[0], CURRENT_VERSION);
+	assert_eq!(encoded.len(), 1 + UNSIGNED_HEADER_LEN);
+	assert_eq!(encoded[41..49], 1_000u64.to_le_bytes());
+	assert_eq!(Header::decode(&encoded), Ok(header));
+
+	let key = crate::signing::SigningKey::from_bytes(&[1; 32]);
+	let signed = test_block().child_signed(&key, &State::new(12, 54), vec![]).header;
+	let encoded = signed.encode();
+	assert_eq!(encoded.len(), 1 + UNSIGNED_HEADER_LEN + 32 + 64);
+	assert_eq!(Header::decode(&encoded), Ok(signed));
+}
+
+#[test]
+fn encoding_block_roundtrip() {
+	let block = test_block();
+
+	assert_eq!(Block::decode(&block.encode()), Ok(block));
+}
+
+#[test]
+fn encoding_decodes_archives_from_before_timestamps() {
+	// A block exported by the version 0 encoder, before headers had a timestamp.
+	let block = test_block();
+	let h = &block.header;
+	let mut encoded = vec![0];
+	for field in
+		[h.parent, h.height, h.extrinsics_root, h.state_root, h.consensus_digest, 3, 1, 2, 3]
+	{
+		encoded.extend_from_slice(&field.to_le_bytes());
+	}
+
+	let decoded = Block::decode(&encoded).unwrap();
+	assert_eq!(decoded.header.timestamp, 0);
+	assert_eq!(decoded.header.target, u64::MAX);
+	assert_eq!(decoded.header.author, None);
+	assert_eq!(decoded, Block { header: Header { timestamp: 0, ..h.clone() }, ..block.clone() });
+	assert_eq!(Header::decode(&encoded[..41]), Ok(decoded.header.clone()));
+	assert_eq!(Block::decode_borrowed(&encoded).unwrap().to_block(), decoded);
+
+	// Encoding it again upgrades it to the current version.
+	assert_eq!(decoded.encode()[0], CURRENT_VERSION);
+	assert_eq!(Block::decode(&decoded.encode()), Ok(decoded));
+}
+
+#[test]
+fn encoding_unknown_version_is_rejected() {
+	let mut encoded = test_block().header.encode();
+	encoded[0] = 200;
+
+	assert_eq!(Header::decode(&encoded), Err(DecodeError::UnsupportedVersion(200)));
+}
+
+#[test]
+fn encoding_truncated_and_padded_input_is_rejected() {
+	let encoded = test_block().encode();
+
+	assert_eq!(Block::decode(&encoded[..encoded.len() - 1]), Err(DecodeError::UnexpectedEnd));
+	assert_eq!(
+		Block::decode(&[encoded.clone(), vec![0]].concat()),
+		Err(DecodeError::TrailingBytes)
+	);
+	assert_eq!(Header::decode(&[]), Err(DecodeError::UnexpectedEnd));
+
+	let mut bad_option = test_block().header.encode();
+	bad_option[1 + 7 * 8] = 2;
+	assert_eq!(Header::decode(&bad_option), Err(DecodeError::InvalidOption(2)));
+}
+
+#[test]
+fn encoding_borrowed_block_matches_owned() {
+	let block = test_block();
+	let encoded = block.encode();
+	let borrowed = Block::decode_borrowed(&encoded).unwrap();
+
+	assert_eq!(borrowed.header, block.header);
+	assert_eq!(borrowed.body.len(), 3);
+	assert_eq!(borrowed.body.get(1), Some(2));
+	assert_eq!(borrowed.body.get(3), None);
+	assert!(borrowed.body.iter().eq(block.body.iter().copied()));
+	assert_eq!(borrowed.to_block(), block);
+}
+
+#[test]
+fn encoding_borrowed_block_rejects_bad_input() {
+	let encoded = test_block().encode();
+
+	assert_eq!(
+		Block::decode_borrowed(&encoded[..encoded.len() - 1]),
+		Err(DecodeError::UnexpectedEnd)
+	);
+	assert_eq!(
+		Block::decode_borrowed(&[encoded.clone(), vec![0]].concat()),
+		Err(DecodeError::TrailingBytes)
+	);
+
+	// A length prefix far larger than the input must not cause a huge allocation or a panic.
+	let mut huge = test_block().encode();
+	let len_at = 1 + UNSIGNED_HEADER_LEN;
+	huge[len_at..len_at + 8].copy_from_slice(&u64::MAX.to_le_bytes());
+	assert_eq!(Block::decode_borrowed(&huge), Err(DecodeError::UnexpectedEnd));
+}
+
+#[test]
+fn encoding_state_roundtrip() {
+	let state = State::new(6, 9);
+	let encoded = state.encode();
+
+	assert_eq!(encoded.len(), 1 + 2 * 8);
+	assert_eq!(encoded[1..9], 6u64.to_le_bytes());
+	assert_eq!(State::decode(&encoded), Ok(state.clone()));
+	assert_eq!(State::decode(&encoded[..16]), Err(DecodeError::UnexpectedEnd));
+
+	// The layout has never changed, so states from before timestamps decode just the same.
+	assert_eq!(State::decode(&[&[0][..], &encoded[1..]].concat()), Ok(state));
+}
@@ -0,0 +1,367 @@
+//! A tree of every header a client has observed, including those on side branches.
+//!
+//! The fork choice rules from part 5 compare chains that the caller has already assembled. A real
+//! client instead hears about headers one at a time, from many peers, in no particular order. The
+//! [`ForkTree`] keeps track of all of them, links each header to its parent, and finds the leaf
+//! that a fork choice rule considers best.
+//!
+//! Headers whose parent has not arrived yet are parked rather than rejected. They join the tree as
+//! soon as their parent does.
+
+use crate::{hash, p4_batched_extrinsics::Header, ForkChoice, Hash, IncrementalForkChoice};
+use std::collections::HashMap;
+
+/// What happened to a header given to [`ForkTree::insert`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum InsertOutcome {
+	/// The header joined the tree, along with the given number of parked descendants that were
+	/// waiting for it.
+	Inserted { unparked: usize },
+	/// The header's parent is unknown, so it was parked until the parent arrives.
+	Parked,
+	/// The header was already in the tree or already parked, so nothing changed.
+	AlreadyKnown,
+}
+
+/// Every header we have observed, arranged as a tree below a single root.
+///
+/// With the `serde` feature, a tree serializes as a [`ForkTreeSnapshot`] and is rebuilt from one.
+#[derive(Clone, Debug)]
+#[cfg_attr(
+	feature = "serde",
+	derive(serde::Serialize, serde::Deserialize),
+	serde(into = "ForkTreeSnapshot", from = "ForkTreeSnapshot")
+)]
+pub struct ForkTree {
+	root: Hash,
+	headers: HashMap<Hash, Header>,
+	children: HashMap<Hash, Vec<Hash>>,
+	/// Headers waiting for their parent, keyed by the parent's hash.
+	orphans: HashMap<Hash, Vec<Header>>,
+}
+
+impl ForkTree {
+	/// Create a tree containing only the given root header. This is usually the genesis header,
+	/// but may be any header that all the others are descended from.
+	pub fn new(root: Header) -> Self {
+		let root_hash = hash(&root);
+		ForkTree {
+			root: root_hash,
+			headers: HashMap::from([(root_hash, root)]),
+			children: HashMap::new(),
+			orphans: HashMap::new(),
+		}
+	}
+
+	/// Add a header to the tree. If its parent is not in the tree yet, the header is parked until
+	/// it is.
+	pub fn insert(&mut self, header: Header) -> InsertOutcome {
+		let header_hash = hash(&header);
+		let parked = self.orphans.get(&header.parent);
+		if self.headers.contains_key(&header_hash) ||
+			parked.is_some_and(|siblings| siblings.contains(&header))
+		{
+			return InsertOutcome::AlreadyKnown;
+		}
+		if !self.headers.contains_key(&header.parent) {
+			self.orphans.entry(header.parent).or_default().push(header);
+			return InsertOutcome::Parked;
+		}
+
+		let mut ready = vec![header];
+		let mut unparked = 0;
+		while let Some(header) = ready.pop() {
+			let header_hash = hash(&header);
+			self.connect(header_hash, header);
+			if let Some(waiting) = self.orphans.remove(&header_hash) {
+				unparked += waiting.len();
+				ready.extend(waiting);
+			}
+		}
+		InsertOutcome::Inserted { unparked }
+	}
+
+	/// Link a header whose parent is known into the tree. Nothing above the parent is touched, so
+	/// this takes the same time however deep the header is.
+	fn connect(&mut self, header_hash: Hash, header: Header) {
+		self.children.entry(header.parent).or_default().push(header_hash);
+		self.headers.insert(header_hash, header);
+	}
+
+	/// The hash of the root header.
+	pub fn root(&self) -> Hash {
+		self.root
+	}
+
+	/// The header with the given hash, if it is in the tree. Parked headers are not in the tree.
+	pub fn get(&self, header_hash: Hash) -> Option<&Header> {
+		self.headers.get(&header_hash)
+	}
+
+	/// Whether the header with the given hash is in the tree.
+	pub fn contains(&self, header_hash: Hash) -> bool {
+		self.headers.contains_key(&header_hash)
+	}
+
+	/// The hashes of the known children of the given header.
+	pub fn children(&self, header_hash: Hash) -> &[Hash] {
+		self.children.get(&header_hash).map_or(&[], Vec::as_slice)
+	}
+
+	/// The hashes of every header in the tree that has no children, in ascending order.
+	pub fn leaves(&self) -> Vec<Hash> {
+		let mut leaves: Vec<Hash> =
+			self.headers.keys().filter(|h| self.children(**h).is_empty()).copied().collect();
+		leaves.sort_unstable();
+		leaves
+	}
+
+	/// The hashes of the given header and all its descendants, with every parent before its
+	/// children. Headers that are not in the tree have no descendants.
+	fn descendants(&self, header_hash: Hash) -> Vec<Hash> {
+		if !self.contains(header_hash) {
+			return Vec::new();
+		}
+		let mut descendants = vec![header_hash];
+		let mut next = 0;
+		while let Some(&current) = descendants.get(next) {
+			descendants.extend_from_slice(self.children(current));
+			next += 1;
+		}
+		descendants
+	}
+
+	/// The number of headers in the subtree below the given header, including the header itself.
+	/// Headers that are not in the tree have no subtree at all.
+	///
+	/// This counts the whole subtree. Use [`ForkTree::subtree_sizes`] to get the size of every
+	/// subtree at once.
+	pub fn subtree_size(&self, header_hash: Hash) -> u64 {
+		self.descendants(header_hash).len() as u64
+	}
+
+	/// The number of headers in the subtree below every header in the tree, counted in a single
+	/// pass from the leaves up.
+	pub fn subtree_sizes(&self) -> HashMap<Hash, u64> {
+		let mut sizes = HashMap::with_capacity(self.len());
+		for header_hash in self.descendants(self.root).into_iter().rev() {
+			let size = self.children(header_hash).iter().map(|child| sizes[child]).sum::<u64>() + 1;
+			sizes.insert(header_hash, size);
+		}
+		sizes
+	}
+
+	/// The chain from the root down to the given header, or `None` if the header is not in the
+	/// tree.
+	pub fn chain_to(&self, header_hash: Hash) -> Option<Vec<Header>> {
+		let mut chain = vec![self.get(header_hash)?.clone()];
+		let mut current = header_hash;
+		while current != self.root {
+			current = chain.last().expect("chain is never empty").parent;
+			chain.push(self.headers[&current].clone());
+		}
+		chain.reverse();
+		Some(chain)
+	}
+
+	/// The leaf at the tip of the best chain according to the given fork choice rule. Every chain
+	/// is measured from the root.
+	///
+	/// Every header is scored once, from its parent's score, and then the leaves are compared by
+	/// their scores, so no chain is ever assembled. Equally good leaves go to the lowest hash,
+	/// unless the rule prefers the later of equal scores.
+	pub fn best_leaf<R: IncrementalForkChoice>(&self, rule: &R) -> &Header {
+		let mut scores = HashMap::with_capacity(self.len());
+		for header_hash in self.descendants(self.root) {
+			let header = &self.headers[&header_hash];
+			let parent_score = scores.get(&header.parent).copied().unwrap_or_default();
+			scores.insert(header_hash, rule.extend_score(parent_score, header));
+		}
+
+		let leaves = self.leaves();
+		let mut best = leaves[0];
+		for leaf in leaves {
+			if rule.first_score_is_better(scores[&leaf], scores[&best]) {
+				best = leaf;
+			}
+		}
+		&self.headers[&best]
+	}
+
+	/// The same as [`ForkTree::best_leaf`], for rules that can only compare whole chains. This
+	/// assembles the chain to every leaf, so prefer `best_leaf` for rules that have a score.
+	pub fn best_leaf_by_chains<R: ForkChoice>(&self, rule: &R) -> &Header {
+		let chains: Vec<Vec<Header>> = self
+			.leaves()
+			.into_iter()
+			.map(|leaf| self.chain_to(leaf).expect("leaves are in the tree"))
+			.collect();
+		let candidates: Vec<&[Header]> = chains.iter().map(Vec::as_slice).collect();
+		let best = rule.best_chain(&candidates);
+		self.get(hash(best.last().expect("chains always contain the root")))
+			.expect("the chain came from this tree")
+	}
+
+	/// The number of headers in the tree, not counting parked headers.
+	pub fn len(&self) -> usize {
+		self.headers.len()
+	}
+
+	/// Whether the tree is empty. It never is, because the root is always there.
+	pub fn is_empty(&self) -> bool {
+		self.headers.is_empty()
+	}
+
+	/// The number of headers parked while waiting for their parent.
+	pub fn parked(&self) -> usize {
+		self.orphans.values().map(Vec::len).sum()
+	}
+}
+
+/// The tree that [`GhostRule`](crate::GhostRule) walks was first introduced under this name. It is
+/// now the fork tree, which also parks orphans, so `insert` reports an [`InsertOutcome`] rather
+/// than a bool.
+pub type BlockTree = ForkTree;
+
+/// The headers of a [`ForkTree`], without the indexes that can be rebuilt from them. Headers are
+/// sorted by height and then by hash, so the same tree always gives the same snapshot, which makes
+/// snapshots suitable for test fixtures.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ForkTreeSnapshot {
+	/// The root of the tree
+	pub root: Header,
+	/// Every other header in the tree. Parents always come before their children.
+	pub headers: Vec<Header>,
+	/// The headers parked while waiting for their parent
+	pub parked: Vec<Header>,
+}
+
+impl From<ForkTree> for ForkTreeSnapshot {
+	fn from(tree: ForkTree) -> Self {
+		let by_height_then_hash = |headers: &mut Vec<Header>| {
+			headers.sort_by_cached_key(|header| (header.height, hash(header)));
+		};
+		let mut headers = tree.headers;
+		let root = headers.remove(&tree.root).expect("the root is in the tree");
+		let mut headers: Vec<Header> = headers.into_values().collect();
+		by_height_then_hash(&mut headers);
+		let mut parked: Vec<Header> = tree.orphans.into_values().flatten().collect();
+		by_height_then_hash(&mut parked);
+		ForkTreeSnapshot { root, headers, parked }
+	}
+}
+
+/// Rebuilds the tree by inserting every header. Headers that don't descend from the root end up
+/// parked, the same as if they had arrived over the network.
+impl From<ForkTreeSnapshot> for ForkTree {
+	fn from(snapshot: ForkTreeSnapshot) -> Self {
+		let mut tree = ForkTree::new(snapshot.root);
+		for header in snapshot.headers.into_iter().chain(snapshot.parked) {
+			tree.insert(header);
+		}
+		tree
+	}
+}
+
+#[cfg(test)]
+use crate::{
+	p5_fork_choice::LongestChainRule,
+	test_utils::{linear_chain, Fork},
+};
+
+#[test]
+fn fork_tree_links_children_to_parents() {
+	let chain = linear_chain(2);
+	let [g, b1, b2] = [&chain[0], &chain[1], &chain[2]];
+	let mut tree = ForkTree::new(g.clone());
+
+	assert_eq!(tree.insert(b1.clone()), InsertOutcome::Inserted { unparked: 0 });
+	assert_eq!(tree.insert(b1.clone()), InsertOutcome::AlreadyKnown);
+	assert_eq!(tree.insert(b2.clone()), InsertOutcome::Inserted { unparked: 0 });
+
+	assert_eq!(tree.len(), 3);
+	assert_eq!(tree.children(hash(g)), &[hash(b1)]);
+	assert_eq!(tree.subtree_size(hash(g)), 3);
+	assert_eq!(tree.subtree_size(hash(b2)), 1);
+	assert_eq!(tree.subtree_sizes(), HashMap::from([(hash(g), 3), (hash(b1), 2), (hash(b2), 1)]));
+	assert_eq!(tree.get(hash(b2)), Some(b2));
+	assert_eq!(tree.chain_to(hash(b2)), Some(chain.clone()));
+}
+
+#[test]
+fn fork_tree_parks_orphans_until_parent_arrives() {
+	let chain = linear_chain(3);
+	let [g, b1, b2, b3] = [&chain[0], &chain[1], &chain[2], &chain[3]];
+	let mut tree = ForkTree::new(g.clone());
+
+	assert_eq!(tree.insert(b3.clone()), InsertOutcome::Parked);
+	assert_eq!(tree.insert(b2.clone()), InsertOutcome::Parked);
+	assert_eq!(tree.insert(b2.clone()), InsertOutcome::AlreadyKnown);
+	assert_eq!(tree.parked(), 2);
+	assert!(!tree.contains(hash(b3)));
+
+	assert_eq!(tree.insert(b1.clone()), InsertOutcome::Inserted { unparked: 2 });
+	assert_eq!(tree.parked(), 0);
+	assert_eq!(tree.subtree_size(hash(g)), 4);
+	assert_eq!(tree.leaves(), vec![hash(b3)]);
+}
+
+#[test]
+fn fork_tree_best_leaf() {
+	let fork = Fork::new(0, 2, 1);
+	let mut tree = ForkTree::new(Header::genesis());
+	for header in fork.left.iter().chain(&fork.right) {
+		tree.insert(header.clone());
+	}
+
+	let left_tip = fork.left.last().unwrap();
+	let right_tip = fork.right.last().unwrap();
+	let mut expected_leaves = vec![hash(left_tip), hash(right_tip)];
+	expected_leaves.sort();
+	assert_eq!(tree.leaves(), expected_leaves);
+	assert_eq!(tree.best_leaf(&LongestChainRule), left_tip);
+	assert_eq!(tree.best_leaf_by_chains(&LongestChainRule), left_tip);
+	assert_best_chain!(
+		LongestChainRule,
+		[tree.chain_to(hash(right_tip)).unwrap(), tree.chain_to(hash(left_tip)).unwrap()],
+		fork.left_chain()
+	);
+}
+
+#[test]
+fn fork_tree_snapshot_roundtrip() {
+	let fork = Fork::new(1, 2, 1);
+	let mut tree = ForkTree::new(Header::genesis());
+	for header in fork.right.iter().chain(&fork.left).chain(&fork.common[1..]) {
+		tree.insert(header.clone());
+	}
+	let orphan = crate::test_utils::extend(&fork.left[0], 3, 9).pop().unwrap();
+	tree.insert(orphan.clone());
+
+	let snapshot = ForkTreeSnapshot::from(tree.clone());
+	assert_eq!(snapshot.root, Header::genesis());
+	assert_eq!(snapshot.headers.len(), 4);
+	assert!(snapshot.headers.windows(2).all(|pair| pair[0].height <= pair[1].height));
+	assert_eq!(snapshot.parked, vec![orphan]);
+
+	let rebuilt = ForkTree::from(snapshot.clone());
+	assert_eq!(rebuilt.leaves(), tree.leaves());
+	assert_eq!(rebuilt.parked(), 1);
+	assert_eq!(ForkTreeSnapshot::from(rebuilt), snapshot);
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn fork_tree_json_roundtrip() {
+	let fork = Fork::new(0, 2, 1);
+	let mut tree = ForkTree::new(Header::genesis());
+	for header in fork.left.iter().chain(&fork.right) {
+		tree.insert(header.clone());
+	}
+
+	let json = serde_json::to_string(&tree).unwrap();
+	assert_eq!(json, serde_json::to_string(&ForkTreeSnapshot::from(tree.clone())).unwrap());
+	let rebuilt: ForkTree = serde_json::from_str(&json).unwrap();
+	assert_eq!(ForkTreeSnapshot::from(rebuilt), ForkTreeSnapshot::from(tree));
+}
@@ -0,0 +1,131 @@
+//! Fraud proofs that let light clients reject blocks with invalid state transitions.
+//!
+//! A light client only downloads headers. It can check that they link together, but since it has
+//! neither the state nor the extrinsics, it can't check that a header's state root is really the
+//! result of executing the block. It has to trust that the chain's authors executed it honestly.
+//!
+//! A fraud proof removes that trust. A full node that executes a block and gets a different state
+//! root hands the light client everything it needs to repeat the execution itself: the parent
+//! header, a witness for the pre-state, the extrinsics, and the header claiming the wrong post
+//! state root. The light client checks every piece against the commitments in the headers, so a
+//! dishonest full node can't fake a proof against a valid block.
+//!
+//! In a real chain the state is far too big to send, so the witness is a state trie proof covering
+//! only the parts of the state that the block touches. Our state is just two numbers, so the whole
+//! of it is a perfectly compact witness.
+//!
+//! A block whose body doesn't match its extrinsics root can't be proven invalid this way, because
+//! nobody can show what its real extrinsics were. That is the separate problem of data
+//! availability.
+
+use crate::{
+	hasher::{FastHasher, Hasher},
+	p6_rich_state::State,
+	stateless::re_execute,
+	Block, Header,
+};
+
+/// Evidence that a header's state root is not the result of executing its block.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FraudProof<H: Hasher = FastHasher> {
+	/// The parent of the fraudulent header, which commits to the pre-state.
+	pub parent: Header<H>,
+	/// The state that the block was executed on.
+	pub pre_state: State,
+	/// The block's extrinsics, which the fraudulent header commits to.
+	pub extrinsics: Vec<u64>,
+	/// The fraudulent header, with its claimed post state root.
+	pub header: Header<H>,
+}
+
+impl<H: Hasher> FraudProof<H> {
+	/// A proof that the block is invalid, built by a full node that knows the parent's post state.
+	/// Returns `None` if there is nothing to prove, because executing the block does give the state
+	/// root it claims, or because the arguments don't fit together.
+	pub fn construct(parent: &Header<H>, pre_state: &State, block: &Block<H>) -> Option<Self> {
+		let proof = FraudProof {
+			parent: parent.clone(),
+			pre_state: pre_state.clone(),
+			extrinsics: block.body.clone(),
+			header: block.header.clone(),
+		};
+		proof.verify().then_some(proof)
+	}
+
+	/// Whether the proof shows that its header is invalid. This only needs the data in the proof,
+	/// so a light client can run it, and should then reject the header and all its descendants.
+	///
+	/// Every piece is checked against the headers before the block is re-executed, so a proof
+	/// built from made up data never verifies.
+	pub fn verify(&self) -> bool {
+		re_execute(&self.parent, &self.pre_state, &self.extrinsics, &self.header)
+			.is_some_and(|state_root| state_root != self.header.state_root)
+	}
+}
+
+#[cfg(test)]
+fn invalid_child(parent: &Block, pre_state: &State) -> Block {
+	let mut block = parent.child(pre_state, vec![1, 2, 3]);
+	block.header.state_root = crate::hash(&State { sum: 0, product: 0 });
+	block
+}
+
+#[test]
+fn fraud_proof_for_invalid_state_root() {
+	let state = State { sum: 6, product: 9 };
+	let g = Block::genesis(&state);
+	let b1 = invalid_child(&g, &state);
+
+	let proof = FraudProof::construct(&g.header, &state, &b1).unwrap();
+	assert!(proof.verify());
+	assert_eq!(proof.header, b1.header);
+}
+
+#[test]
+fn fraud_proof_not_possible_for_valid_block() {
+	let state = State { sum: 6, product: 9 };
+	let g: Block = Block::genesis(&state);
+	let b1 = g.child(&state, vec![1, 2, 3]);
+	assert_eq!(FraudProof::construct(&g.header, &state, &b1), None);
+
+	let b2 = b1.child(&state.execute(&[1, 2, 3]), vec![4]);
+	assert_eq!(FraudProof::construct(&b1.header, &state.execute(&[1, 2, 3]), &b2), None);
+}
+
+#[test]
+fn fraud_proof_with_made_up_data_doesnt_verify() {
+	let state = State { sum: 6, product: 9 };
+	let g = Block::genesis(&state);
+	let b1 = invalid_child(&g, &state);
+	let proof = FraudProof::construct(&g.header, &state, &b1).unwrap();
+
+	// A pre-state the parent doesn't commit to.
+	let wrong_pre_state = FraudProof { pre_state: State { sum: 0, product: 0 }, ..proof.clone() };
+	assert!(!wrong_pre_state.verify());
+
+	// Extrinsics the header doesn't commit to.
+	let wrong_extrinsics = FraudProof { extrinsics: vec![1, 2], ..proof.clone() };
+	assert!(!wrong_extrinsics.verify());
+
+	// A parent the header isn't a child of.
+	let wrong_parent = FraudProof { parent: b1.header.clone(), ..proof };
+	assert!(!wrong_parent.verify());
+}
+
+#[test]
+fn fraud_proof_for_overflowing_block() {
+	let state = State { sum: u64::MAX, product: u64::MAX };
+	let g: Block = Block::genesis(&state);
+	let extrinsics = vec![u64::MAX, 2];
+	let post_state = state.execute(&extrinsics);
+	assert_eq!(post_state, State { sum: 0, product: 2 });
+
+	// Executing the overflowing body neither panics nor lets its author claim any state they like.
+	let honest = g.child(&state, extrinsics.clone());
+	assert_eq!(honest.header.state_root, crate::hash(&post_state));
+	assert_eq!(FraudProof::construct(&g.header, &state, &honest), None);
+
+	let mut dishonest = honest;
+	dishonest.header.state_root = crate::hash(&state);
+	assert!(FraudProof::construct(&g.header, &state, &dishonest).unwrap().verify());
+}
@@ -0,0 +1,51 @@
+//! This module explores the blockchain. A distributed hash-linked tree-like data structure that is
+//! used to track alternative histories of a shared resource. It also explores a simple work-based
+//! consensus algorithm to help users decide which history is the canonical one.
+
+#[cfg_attr(test, macro_use)]
+extern crate bfs_state_machine;
+
+use bfs_state_machine::{hash, hasher, hints, time};
+
+// We make the complete Block and Header types publicly visible so that we can continue developing
+// against them in future chapters. The prior iterations are not available outside this chapter.
+// The state is exported too, since verifying blocks and building their witnesses needs it.
+pub use p6_rich_state::{Block, Header, State};
+
+// The fork choice abstractions are useful well beyond this chapter, so they are exported as well.
+// Notice that they operate on the headers from part 4, as does the fork tree.
+pub use fork_tree::{BlockTree, ForkTree};
+pub use p5_fork_choice::{
+	is_well_linked, ChainScore, Equivocation, EquivocationPenalizingRule, FinalizedBase,
+	ForkChoice, GhostRule, HeaderWeight, HeaviestChainRule, IncrementalForkChoice, InterleavedRule,
+	Lexicographic, LongestChainRule, MostBlocksWithEvenHash, MostPrimaryAuthoredRule, ScoreCache,
+	Streamed, StreamingForkChoice, TieBreak, Validated, WeightedChainRule, WithTieBreak, Work,
+};
+
+/// We use Rust's built-in hashing where the output type is u64. Throughout the chapter each lesson
+/// uses this same alias so the code is slightly more readable.
+pub type Hash = u64;
+
+#[cfg(test)]
+#[macro_use]
+mod test_utils;
+
+pub mod difficulty;
+pub mod display;
+pub mod encoding;
+pub mod fork_tree;
+pub mod fraud;
+pub mod merkle;
+mod p1_header_chain;
+mod p2_extrinsic_state;
+mod p3_consensus;
+pub mod p4_batched_extrinsics;
+mod p5_fork_choice;
+mod p6_rich_state;
+mod p7_fair_ordering;
+pub mod reorg;
+#[cfg(feature = "scale-codec")]
+pub mod scale;
+pub mod signing;
+pub mod stateless;
+pub mod verification;
@@ -0,0 +1,181 @@
+//! A binary Merkle tree over a block's extrinsics.
+//!
+//! Hashing the whole body into the extrinsics root commits to every extrinsic, but the only way to
+//! check that one particular extrinsic is in the block is to download all of them. A Merkle root
+//! commits to exactly the same data, and also lets anyone holding just the header check a single
+//! extrinsic against a short proof. The proof holds one sibling hash per level of the tree, so it
+//! grows with the logarithm of the number of extrinsics.
+//!
+//! The leaves are the hashes of the extrinsics. Each level above hashes neighbouring pairs
+//! together. When a level has an odd number of nodes, the last one has no partner and moves up
+//! unchanged. Some trees instead pair the last node with a copy of itself, but that lets two
+//! different lists of extrinsics share a root.
+//!
+//! Everything here is generic over the hash function. The lessons use
+//! [`FastHasher`](crate::hasher::FastHasher), whose digests are plain `u64`s.
+
+use crate::{hasher::Hasher, Hash};
+
+/// The root of the Merkle tree over the given extrinsics.
+///
+/// A body with no extrinsics has a root of all zeros, the same as the genesis header's.
+pub fn merkle_root<H: Hasher>(extrinsics: &[u64]) -> H::Output {
+	let mut level: Vec<H::Output> = extrinsics.iter().map(H::digest).collect();
+	if level.is_empty() {
+		return H::Output::default();
+	}
+	while level.len() > 1 {
+		level = next_level::<H>(&level);
+	}
+	level[0]
+}
+
+/// The level of the tree above the given one.
+fn next_level<H: Hasher>(level: &[H::Output]) -> Vec<H::Output> {
+	level
+		.chunks(2)
+		.map(|pair| match pair {
+			[left, right] => H::digest(&(left, right)),
+			[odd_one_out] => *odd_one_out,
+			_ => unreachable!("chunks are one or two long"),
+		})
+		.collect()
+}
+
+/// Evidence that an extrinsic is at a given position in a list of extrinsics with a known root.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MerkleProof<Digest = Hash> {
+	/// The position of the extrinsic in the list.
+	pub index: usize,
+	/// The number of extrinsics in the list. This decides which levels the extrinsic's ancestor
+	/// has no partner on.
+	pub leaf_count: usize,
+	/// The hash of the ancestor's partner on each level that it has one, from the leaves up.
+	pub siblings: Vec<Digest>,
+}
+
+/// A proof that the extrinsic at the given index is part of the list, or `None` if the index is
+/// out of bounds.
+pub fn prove_inclusion<H: Hasher>(
+	extrinsics: &[u64],
+	index: usize,
+) -> Option<MerkleProof<H::Output>> {
+	if index >= extrinsics.len() {
+		return None;
+	}
+
+	let mut level: Vec<H::Output> = extrinsics.iter().map(H::digest).collect();
+	let mut position = index;
+	let mut siblings = Vec::new();
+	while level.len() > 1 {
+		if let Some(sibling) = level.get(position ^ 1) {
+			siblings.push(*sibling);
+		}
+		level = next_level::<H>(&level);
+		position /= 2;
+	}
+	Some(MerkleProof { index, leaf_count: extrinsics.len(), siblings })
+}
+
+/// Whether the proof shows that the extrinsic is part of a list with the given Merkle root.
+pub fn verify_inclusion<H: Hasher>(
+	root: H::Output,
+	proof: &MerkleProof<H::Output>,
+	extrinsic: u64,
+) -> bool {
+	if proof.index >= proof.leaf_count {
+		return false;
+	}
+
+	let mut node = H::digest(&extrinsic);
+	let mut position = proof.index;
+	let mut width = proof.leaf_count;
+	let mut siblings = proof.siblings.iter();
+	while width > 1 {
+		// The last node on a level with an odd width moves up without a partner.
+		if position ^ 1 < width {
+			let Some(sibling) = siblings.next() else {
+				return false;
+			};
+			node = if position % 2 == 0 {
+				H::digest(&(&node, sibling))
+			} else {
+				H::digest(&(sibling, &node))
+			};
+		}
+		position /= 2;
+		width = width.div_ceil(2);
+	}
+	siblings.next().is_none() && node == root
+}
+
+#[cfg(test)]
+use crate::{hash, hasher::FastHasher};
+
+#[test]
+fn merkle_root_of_small_lists() {
+	let [a, b, c] = [1u64, 2, 3].map(|extrinsic| hash(&extrinsic));
+	assert_eq!(merkle_root::<FastHasher>(&[]), 0);
+	assert_eq!(merkle_root::<FastHasher>(&[1]), a);
+	assert_eq!(merkle_root::<FastHasher>(&[1, 2]), hash(&(&a, &b)));
+	assert_eq!(merkle_root::<FastHasher>(&[1, 2, 3]), hash(&(&hash(&(&a, &b)), &c)));
+}
+
+#[test]
+fn merkle_root_depends_on_order_and_length() {
+	assert_ne!(merkle_root::<FastHasher>(&[1, 2]), merkle_root::<FastHasher>(&[2, 1]));
+	assert_ne!(merkle_root::<FastHasher>(&[1, 2, 3]), merkle_root::<FastHasher>(&[1, 2, 3, 3]));
+	assert_ne!(merkle_root::<FastHasher>(&[0]), merkle_root::<FastHasher>(&[]));
+}
+
+#[test]
+fn merkle_proves_every_extrinsic() {
+	for len in 1..=9u64 {
+		let extrinsics: Vec<u64> = (0..len).map(|i| i * 10).collect();
+		let root = merkle_root::<FastHasher>(&extrinsics);
+		for (index, extrinsic) in extrinsics.iter().enumerate() {
+			let proof = prove_inclusion::<FastHasher>(&extrinsics, index).unwrap();
+			assert!(proof.siblings.len() <= 4);
+			assert!(verify_inclusion::<FastHasher>(root, &proof, *extrinsic));
+		}
+		assert_eq!(prove_inclusion::<FastHasher>(&extrinsics, len as usize), None);
+	}
+}
+
+#[test]
+fn merkle_rejects_bad_proofs() {
+	let extrinsics = [4, 8, 15, 16, 23];
+	let root = merkle_root::<FastHasher>(&extrinsics);
+	let proof = prove_inclusion::<FastHasher>(&extrinsics, 2).unwrap();
+	let verify = |root, proof: &MerkleProof, extrinsic| {
+		verify_inclusion::<FastHasher>(root, proof, extrinsic)
+	};
+	assert!(verify(root, &proof, 15));
+
+	assert!(!verify(root, &proof, 42));
+	assert!(!verify(root.wrapping_add(1), &proof, 15));
+	assert!(!verify(root, &MerkleProof { index: 3, ..proof.clone() }, 15));
+	assert!(!verify(root, &MerkleProof { leaf_count: 2, ..proof.clone() }, 15));
+	assert!(!verify(root, &MerkleProof { index: 5, ..proof.clone() }, 15));
+
+	let mut extra_sibling = proof.clone();
+	extra_sibling.siblings.push(0);
+	assert!(!verify(root, &extra_sibling, 15));
+	let mut missing_sibling = proof;
+	missing_sibling.siblings.pop();
+	assert!(!verify(root, &missing_sibling, 15));
+}
+
+#[test]
+fn merkle_works_with_wide_digests() {
+	use crate::hasher::Blake2b256;
+
+	let extrinsics = [4, 8, 15, 16, 23];
+	let root = merkle_root::<Blake2b256>(&extrinsics);
+	assert_eq!(merkle_root::<Blake2b256>(&[]), [0; 32]);
+	for (index, extrinsic) in extrinsics.iter().enumerate() {
+		let proof = prove_inclusion::<Blake2b256>(&extrinsics, index).unwrap();
+		assert!(verify_inclusion::<Blake2b256>(root, &proof, *extrinsic));
+		assert!(!verify_inclusion::<Blake2b256>(root, &proof, extrinsic + 1));
+	}
+}
@@ -2,7 +2,7 @@
 //! structure. We learned from the lecture that it is actually the headers that are hash linked, so
 //! let's start with that.
 
-use crate::hash;
+use crate::{hash, hints::Hints};
 
 // We will use Rust's built-in hashing where the output type is u64. I'll make an alias
 // so the code is slightly more readable.
@@ -10,6 +10,8 @@ type Hash = u64;
 
 /// The most basic blockchain header possible. We learned its basic structure from lecture.
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "scale-codec", derive(parity_scale_codec::Encode, parity_scale_codec::Decode))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Header {
 	parent: Hash,
 	height: u64,
@@ -95,6 +97,17 @@ fn build_an_invalid_chain() -> Vec<Header> {
 	chain
 }
 
+const VERIFY_SUB_CHAIN_HINTS: Hints = &[
+	"Each header must point at the header right before it, starting with the one you were called on.",
+	"Check both the parent hash and the height of every header in the slice.",
+	"Keep track of the previous header as you loop so you can compare each child against it.",
+];
+
+const INVALID_CHAIN_HINTS: Hints = &[
+	"Start from a real genesis header, then break one of the rules `verify_sub_chain` checks.",
+	"A child header whose height or parent hash has been tampered with is invalid.",
+];
+
 // To run these tests: `cargo test bc_1
 #[test]
 fn bc_1_genesis_block_height() {
@@ -135,7 +148,7 @@ fn bc_1_verify_three_blocks() {
 	let b1 = g.child();
 	let b2 = b1.child();
 
-	assert!(g.verify_sub_chain(&[b1, b2]));
+	hint_assert!(g.verify_sub_chain(&[b1, b2]), VERIFY_SUB_CHAIN_HINTS);
 }
 
 #[test]
@@ -165,7 +178,7 @@ fn bc_1_verify_chain_length_five() {
 	// This test chooses to use the student's own verify function.
 	// This should be relatively safe given that we have already tested that function.
 	let chain = build_valid_chain_length_5();
-	assert!(chain[0].verify_sub_chain(&chain[1..]))
+	hint_assert!(chain[0].verify_sub_chain(&chain[1..]), VERIFY_SUB_CHAIN_HINTS);
 }
 
 #[test]
@@ -173,5 +186,5 @@ fn bc_1_invalid_chain_is_really_invalid() {
 	// This test chooses to use the student's own verify function.
 	// This should be relatively safe given that we have already tested that function.
 	let invalid_chain = build_an_invalid_chain();
-	assert!(!invalid_chain[0].verify_sub_chain(&invalid_chain[1..]))
+	hint_assert!(!invalid_chain[0].verify_sub_chain(&invalid_chain[1..]), INVALID_CHAIN_HINTS);
 }
@@ -18,6 +18,8 @@ type Hash = u64;
 /// using roots yet, but rather directly embedding some minimal extrinsic and state info
 /// into the header.
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "scale-codec", derive(parity_scale_codec::Encode, parity_scale_codec::Decode))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Header {
 	parent: Hash,
 	height: u64,
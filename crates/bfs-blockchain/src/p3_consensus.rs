@@ -25,6 +25,8 @@ const FORK_HEIGHT: u64 = 2;
 /// hash below a certain threshold. Although we could call the field `nonce` we will leave
 /// the more general `digest` term. For PoA we would have a cryptographic signature in this field.
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "scale-codec", derive(parity_scale_codec::Encode, parity_scale_codec::Decode))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Header {
 	parent: Hash,
 	height: u64,
@@ -329,8 +331,8 @@ fn bc_3_even_chain_valid() {
 	let g = Header::genesis(); // 0
 	let b1 = g.child(2); // 2
 	let b2 = b1.child(1); // 3
-					  // It' all about the states, not the extrinsics. So once the state is even
-					  // we need to keep it that way. So add evens
+					   // It' all about the states, not the extrinsics. So once the state is even
+					   // we need to keep it that way. So add evens
 	let b3 = b2.child(1); // 4
 	let b4 = b3.child(2); // 6
 
@@ -364,8 +366,8 @@ fn bc_3_odd_chain_valid() {
 	let g = Header::genesis(); // 0
 	let b1 = g.child(2); // 2
 	let b2 = b1.child(1); // 3
-					  // It' all about the states, not the extrinsics. So once the state is odd
-					  // we need to keep it that way. So add evens
+					   // It' all about the states, not the extrinsics. So once the state is odd
+					   // we need to keep it that way. So add evens
 	let b3 = b2.child(2); // 5
 	let b4 = b3.child(2); // 7
 
@@ -1,7 +1,7 @@
 //! Until now, each block has contained just a single extrinsic. Really we would prefer to batch
 //! them. Now, we stop relying solely on headers, and instead, create complete blocks.
 
-use crate::hash;
+use crate::{hash, hasher::FastHasher, hints::Hints, merkle::merkle_root};
 use std::thread::current;
 type Hash = u64;
 
@@ -9,14 +9,16 @@ type Hash = u64;
 /// stored in the block body. We are still storing the state in the header for now. This will change
 /// in an upcoming lesson as well.
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "scale-codec", derive(parity_scale_codec::Encode, parity_scale_codec::Decode))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Header {
-	parent: Hash,
-	height: u64,
+	pub(crate) parent: Hash,
+	pub(crate) height: u64,
 	// We now switch from storing an extrinsic directly, to storing an extrinsic root.
 	// This is basically a concise cryptographic commitment to the complete list of extrinsics.
 	// For example, a hash or a Merkle root.
-	extrinsics_root: Hash,
-	state: u64,
+	pub(crate) extrinsics_root: Hash,
+	pub(crate) state: u64,
 	pub consensus_digest: u64,
 }
 
@@ -74,6 +76,8 @@ impl Header {
 
 /// A complete Block is a header and the extrinsics.
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "scale-codec", derive(parity_scale_codec::Encode, parity_scale_codec::Decode))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Block {
 	pub(crate) header: Header,
 	pub(crate) body: Vec<u64>,
@@ -95,7 +99,7 @@ impl Block {
 	/// Create and return a valid child block.
 	/// The extrinsics are batched now, so we need to execute each of them.
 	pub fn child(&self, extrinsics: Vec<u64>) -> Self {
-		let extrinsic_root = hash(&extrinsics);
+		let extrinsic_root = merkle_root::<FastHasher>(&extrinsics);
 		let extrinsic_sum: u64 = extrinsics.iter().sum();
 		let header = self.header.child(extrinsic_root, self.header.state + extrinsic_sum);
 
@@ -112,7 +116,7 @@ impl Block {
 				return false;
 			}
 
-			if hash(&block.body) != block.header.extrinsics_root {
+			if merkle_root::<FastHasher>(&block.body) != block.header.extrinsics_root {
 				return false;
 			}
 
@@ -132,12 +136,23 @@ impl Block {
 /// Notice that you do not need the entire parent block to do this. You only need the header.
 fn build_invalid_child_block_with_valid_header(parent: &Header) -> Block {
 	let extrinsics = vec![2, 3, 4];
-	let extrinsic_root = hash(&extrinsics);
+	let extrinsic_root = merkle_root::<FastHasher>(&extrinsics);
 	let extrinsic_sum: u64 = extrinsics.iter().sum();
 	let header = parent.child(extrinsic_root, parent.state + extrinsic_sum);
 	Block { header, body: vec![3, 4, 5] }
 }
 
+const VERIFY_BLOCKS_HINTS: Hints = &[
+	"A valid block needs both a valid header and a body that matches that header.",
+	"Check each header with `verify_child` and each body against the header's extrinsics root.",
+	"The extrinsics root is the Merkle root of the body, `merkle_root::<FastHasher>(&block.body)`.",
+];
+
+const INVALID_BLOCK_VALID_HEADER_HINTS: Hints = &[
+	"Header checks no longer execute extrinsics, so the body can disagree with the header.",
+	"Build the header from one set of extrinsics, then put a different set in the body.",
+];
+
 #[test]
 fn bc_4_genesis_header() {
 	let g = Header::genesis();
@@ -200,7 +215,7 @@ fn bc_4_verify_three_blocks() {
 	let b1 = g.child(vec![1]);
 	let b2 = b1.child(vec![2]);
 	let chain = vec![g.clone(), b1, b2];
-	assert!(g.verify_sub_chain(&chain[1..]));
+	hint_assert!(g.verify_sub_chain(&chain[1..]), VERIFY_BLOCKS_HINTS);
 }
 
 #[test]
@@ -217,7 +232,7 @@ fn bc_4_invalid_block_state_does_not_check() {
 	let mut b1 = b0.child(vec![1, 2, 3]);
 	b1.body = vec![];
 
-	assert!(!b0.verify_sub_chain(&[b1]));
+	hint_assert!(!b0.verify_sub_chain(&[b1]), VERIFY_BLOCKS_HINTS);
 }
 
 #[test]
@@ -238,8 +253,8 @@ fn bc_4_student_invalid_block_really_is_invalid() {
 	let h1 = &b1.header;
 
 	// Make sure that the header is valid according to header rules.
-	assert!(gh.verify_child(h1));
+	hint_assert!(gh.verify_child(h1), INVALID_BLOCK_VALID_HEADER_HINTS);
 
 	// Make sure that the block is not valid when executed.
-	assert!(!gb.verify_sub_chain(&[b1]));
+	hint_assert!(!gb.verify_sub_chain(&[b1]), INVALID_BLOCK_VALID_HEADER_HINTS);
 }
@@ -0,0 +1,1869 @@
+//! Forks in the blockchain represent alternative histories of the system.
+//! When forks arise in the blockchain, users need a way to decide which chain
+//! they will consider best, for now. This is known as a "fork choice rule".
+//! There are several meaningful notions of "best", so we introduce a trait
+//! that allows multiple implementations.
+//!
+//! Since we have nothing to add to the Block or Header data structures in this lesson,
+//! we will import them from the previous lesson.
+
+use super::p4_batched_extrinsics::{Block, Header};
+use crate::{fork_tree::ForkTree, hash, hasher::HashOutput, hints::Hints};
+use std::{
+	cmp::{Ordering, Reverse},
+	collections::{HashMap, HashSet},
+};
+
+const THRESHOLD: u64 = u64::max_value() / 100;
+
+/// One more than the largest possible hash. Every hash is below this target.
+const MAX_TARGET: u128 = 1 << 64;
+
+/// An amount of proof of work, measured in the expected number of hashes needed to do it.
+///
+/// Work adds up across many blocks, so it is kept in a `u128` that comfortably holds the work of
+/// any realistic chain. Even so, it saturates rather than overflowing.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Work(pub u128);
+
+impl Work {
+	/// The work needed to find a hash no greater than the given one. Hashes are spread evenly, so
+	/// on average that takes `MAX_TARGET / (hash + 1)` attempts.
+	pub fn of_hash(hash: u64) -> Self {
+		Work(MAX_TARGET / (hash as u128 + 1))
+	}
+
+	/// The work needed to find a digest no greater than the given one, from a hash function of any
+	/// width. Only the leading 64 bits count, which is plenty of precision for any realistic
+	/// amount of work.
+	pub fn of_digest(digest: &impl HashOutput) -> Self {
+		Work::of_hash(digest.leading_u64())
+	}
+}
+
+impl core::ops::Add for Work {
+	type Output = Work;
+
+	fn add(self, rhs: Work) -> Work {
+		Work(self.0.saturating_add(rhs.0))
+	}
+}
+
+impl core::iter::Sum for Work {
+	fn sum<I: Iterator<Item = Work>>(iter: I) -> Work {
+		iter.fold(Work::default(), |total, work| total + work)
+	}
+}
+
+/// Judge which blockchain is "best" when there are multiple candidates. There are several
+/// meaningful notions of "best" which is why this is a trait instead of just a
+/// method.
+///
+/// Rules are values rather than bare types, so that a rule can carry its own parameters, such as
+/// the difficulty threshold of the heaviest chain rule.
+pub trait ForkChoice {
+	/// Compare two chains, and return the "best" one.
+	///
+	/// The chains are not assumed to start from the same genesis block, or even a
+	/// genesis block at all. This makes it possible to compare entirely disjoint
+	/// histories. It also makes it possible to compare _only_ the divergent part
+	/// of sibling chains back to the last common ancestor.
+	///
+	/// The chains are assumed to be valid, so it is up to the caller to check
+	/// validity first if they are unsure.
+	fn first_chain_is_better(&self, chain_1: &[Header], chain_2: &[Header]) -> bool;
+
+	/// Compare many chains and return the best one.
+	///
+	/// It is always possible to compare several chains if you are able to compare
+	/// two chains. Therefore this method has a provided implementation. However,
+	/// it may be much more performant to write a fork-choice-specific implementation.
+	fn best_chain<'a>(&self, candidate_chains: &[&'a [Header]]) -> &'a [Header] {
+		let mut best_chain = candidate_chains[0];
+		for i in 1..candidate_chains.len() {
+			if self.first_chain_is_better(candidate_chains[i], best_chain) {
+				best_chain = candidate_chains[i];
+			}
+		}
+		best_chain
+	}
+}
+
+/// The same idea as `ForkChoice`, but the chains are streamed one header at a time rather than
+/// handed over as slices. The methods have different names so that a rule can implement both
+/// traits without calls becoming ambiguous.
+///
+/// Once chains are thousands of blocks long, and are read out of some kind of storage, collecting
+/// them into a slice just to compare them is wasteful. Every rule in this lesson can be decided in
+/// a single pass over each chain, so every rule implements both traits. Rules that only implement
+/// this one can still be used with the slice based API by wrapping them in [`Streamed`].
+pub trait StreamingForkChoice {
+	/// Compare two chains, and return whether the first is "better". The same assumptions apply as
+	/// for `ForkChoice::first_chain_is_better`.
+	fn first_stream_is_better<'a>(
+		&self,
+		chain_1: impl Iterator<Item = &'a Header>,
+		chain_2: impl Iterator<Item = &'a Header>,
+	) -> bool;
+
+	/// Compare many chains and return the best one, or `None` if there are no candidates.
+	///
+	/// The best chain found so far has to be compared again against each later candidate, so the
+	/// chains must be cheap to clone. Slice iterators and most iterators over storage are.
+	fn best_stream<'a, C>(&self, candidate_chains: impl IntoIterator<Item = C>) -> Option<C>
+	where
+		C: Iterator<Item = &'a Header> + Clone,
+	{
+		let mut candidates = candidate_chains.into_iter();
+		let mut best = candidates.next()?;
+		for candidate in candidates {
+			if self.first_stream_is_better(candidate.clone(), best.clone()) {
+				best = candidate;
+			}
+		}
+		Some(best)
+	}
+}
+
+/// Bridges a rule that only implements [`StreamingForkChoice`] to the slice based API, so that it
+/// can be used anywhere a [`ForkChoice`] is expected, such as [`ForkTree::best_leaf_by_chains`].
+///
+/// A rule written for streams never needs a second implementation for slices. The slices are simply
+/// streamed one header at a time.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Streamed<R>(pub R);
+
+impl<R: StreamingForkChoice> ForkChoice for Streamed<R> {
+	fn first_chain_is_better(&self, chain_1: &[Header], chain_2: &[Header]) -> bool {
+		self.0.first_stream_is_better(chain_1.iter(), chain_2.iter())
+	}
+}
+
+/// The weight that a single header adds to its chain. Implement this to experiment with new fork
+/// choice rules without writing the comparison yourself, and wrap it in [`WeightedChainRule`].
+pub trait HeaderWeight {
+	/// The weight of the given header.
+	fn weight(&self, header: &Header) -> u128;
+}
+
+impl<W: HeaderWeight + ?Sized> HeaderWeight for &W {
+	fn weight(&self, header: &Header) -> u128 {
+		(**self).weight(header)
+	}
+}
+
+/// The best chain is the one with the highest total weight, according to the given
+/// [`HeaderWeight`]. Ties go to the second chain.
+///
+/// The heaviest chain, most even hash, and most primary-authored rules below are all this rule with
+/// a particular header weight.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct WeightedChainRule<W>(pub W);
+
+impl<W: HeaderWeight> WeightedChainRule<W> {
+	/// The total weight of a chain. This saturates rather than overflowing.
+	pub fn total_weight<'a>(&self, chain: impl IntoIterator<Item = &'a Header>) -> u128 {
+		chain
+			.into_iter()
+			.fold(0, |total, header| total.saturating_add(self.0.weight(header)))
+	}
+}
+
+impl<W: HeaderWeight> ForkChoice for WeightedChainRule<W> {
+	fn first_chain_is_better(&self, chain_1: &[Header], chain_2: &[Header]) -> bool {
+		self.total_weight(chain_1) > self.total_weight(chain_2)
+	}
+}
+
+impl<W: HeaderWeight> StreamingForkChoice for WeightedChainRule<W> {
+	fn first_stream_is_better<'a>(
+		&self,
+		chain_1: impl Iterator<Item = &'a Header>,
+		chain_2: impl Iterator<Item = &'a Header>,
+	) -> bool {
+		self.total_weight(chain_1) > self.total_weight(chain_2)
+	}
+}
+
+/// The "best" chain is simply the longest chain.
+///
+/// Like every rule in this lesson, ties go to the second chain, so `best_chain` keeps the first of
+/// several equally long chains. Wrap the rule in [`WithTieBreak`] to choose a tie breaking policy
+/// explicitly.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct LongestChainRule;
+
+impl ForkChoice for LongestChainRule {
+	fn first_chain_is_better(&self, chain_1: &[Header], chain_2: &[Header]) -> bool {
+		chain_1.len() > chain_2.len()
+	}
+}
+
+impl StreamingForkChoice for LongestChainRule {
+	fn first_stream_is_better<'a>(
+		&self,
+		chain_1: impl Iterator<Item = &'a Header>,
+		chain_2: impl Iterator<Item = &'a Header>,
+	) -> bool {
+		chain_1.count() > chain_2.count()
+	}
+}
+
+/// The best chain is the one with the most accumulated work.
+///
+/// In Proof of Work chains, each block contains a certain amount of "work".
+/// Roughly speaking, the lower a block's hash is, the more work it contains,
+/// because finding a block with a low hash requires, on average, trying more
+/// nonces. Precisely, finding a hash no greater than `h` takes `MAX_TARGET / (h + 1)`
+/// attempts on average, so that is the [`Work`] each block is credited with. Headers whose hash
+/// does not meet the threshold were not properly mined, and contain no work at all.
+///
+/// Real chains credit each block with the work implied by its target rather than by its actual
+/// hash, so that lucky blocks are not worth more than others. The idea is the same.
+///
+/// The default rule uses the module's `THRESHOLD`. Use [`HeaviestChainRule::new`] to compare
+/// chains mined to a different difficulty.
+///
+/// Ties go to the second chain, so `best_chain` keeps the first of several equally heavy chains.
+/// Wrap the rule in [`WithTieBreak`] to choose a tie breaking policy explicitly.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct HeaviestChainRule {
+	threshold: u64,
+}
+
+/// Mutates a block (and its embedded header) to contain more PoW difficulty.
+/// This will be useful for exploring the heaviest chain rule. The expected
+/// usage is that you create a block using the normal `Block.child()` method
+/// and then pass the block to this helper for additional mining.
+///
+/// Once mined, the block contains at least `Work::of_hash(threshold)` work.
+fn mine_extra_hard(block: &mut Block, threshold: u64) {
+	let mut i = 1;
+	loop {
+		block.header.consensus_digest = block.header.consensus_digest + i;
+		if hash(&block.header) < threshold {
+			return;
+		}
+		i += 1;
+	}
+}
+impl HeaviestChainRule {
+	/// A heaviest chain rule for blocks mined against the given threshold.
+	pub fn new(threshold: u64) -> Self {
+		HeaviestChainRule { threshold }
+	}
+
+	/// The work contained in a single header. Headers that do not meet the threshold contain none.
+	fn work(&self, header: &Header) -> Work {
+		let header_hash = hash(header);
+		if header_hash < self.threshold {
+			Work::of_hash(header_hash)
+		} else {
+			Work::default()
+		}
+	}
+
+	/// The probability that an attacker with the given fraction of the hash rate ever catches up
+	/// from the given number of blocks behind, and so reverses a block with that many blocks built
+	/// on top of it.
+	///
+	/// This is the calculation from section 11 of the Bitcoin whitepaper. While the honest miners
+	/// build `depth` blocks, the attacker makes progress that follows a Poisson distribution. From
+	/// wherever that leaves them, their chance of ever catching up is `(q / p)^blocks_behind`,
+	/// exactly like a gambler trying to recover from a loss.
+	///
+	/// An attacker with at least half the hash rate always catches up eventually. The hash rate
+	/// must be between 0 and 1, and the result is NaN when it is not, including when it is NaN
+	/// itself.
+	///
+	/// Depths beyond the deepest one that [`HeaviestChainRule::confirmation_depth`] searches are
+	/// treated as that depth, so the work stays bounded. The probability only falls with depth, so
+	/// for deeper blocks the result is an upper bound.
+	pub fn reversal_probability(attacker_hashrate: f64, depth: u64) -> f64 {
+		if !(0.0..=1.0).contains(&attacker_hashrate) {
+			return f64::NAN;
+		}
+		let q = attacker_hashrate;
+		let p = 1.0 - q;
+		if q >= p {
+			return 1.0;
+		}
+
+		let depth = depth.min(MAX_CONFIRMATION_DEPTH);
+
+		let expected_progress = depth as f64 * q / p;
+		// The Poisson probabilities are tracked as logarithms so they don't underflow for deep
+		// blocks, where the expected progress is large.
+		let mut log_poisson = -expected_progress;
+		let mut caught_up = 1.0;
+		for k in 0..=depth {
+			if k > 0 {
+				log_poisson += expected_progress.ln() - (k as f64).ln();
+			}
+			caught_up -= log_poisson.exp() * (1.0 - (q / p).powf((depth - k) as f64));
+		}
+		caught_up.clamp(0.0, 1.0)
+	}
+
+	/// The number of blocks that must be built on top of a block before the chance of an attacker
+	/// with the given fraction of the hash rate reversing it falls below the given probability.
+	///
+	/// Returns `None` when no depth is ever enough: when the attacker has at least half the hash
+	/// rate, or when the probability is zero. Also returns `None` when the hash rate is not between
+	/// 0 and 1, or the probability is above 1, including when either is NaN. Depths are counted in
+	/// blocks, so this assumes each block contains about the same amount of work.
+	///
+	/// The reversal probability only falls as blocks are added, so the depth is found with a binary
+	/// search rather than by trying every depth in turn.
+	pub fn confirmation_depth(
+		attacker_hashrate: f64,
+		max_reversal_probability: f64,
+	) -> Option<u64> {
+		let valid_hashrate = (0.0..0.5).contains(&attacker_hashrate);
+		let valid_probability = max_reversal_probability > 0.0 && max_reversal_probability <= 1.0;
+		if !(valid_hashrate && valid_probability) {
+			return None;
+		}
+		let is_deep_enough = |depth: u64| {
+			Self::reversal_probability(attacker_hashrate, depth) < max_reversal_probability
+		};
+		if !is_deep_enough(MAX_CONFIRMATION_DEPTH) {
+			return None;
+		}
+
+		// The shallowest deep enough depth is always in `low..=high`.
+		let (mut low, mut high) = (0, MAX_CONFIRMATION_DEPTH);
+		while low < high {
+			let middle = low + (high - low) / 2;
+			if is_deep_enough(middle) {
+				high = middle;
+			} else {
+				low = middle + 1;
+			}
+		}
+		Some(low)
+	}
+
+	/// Whether the block at the given index in the chain has enough blocks on top of it that an
+	/// attacker with the given fraction of the hash rate is unlikely to reverse it. See
+	/// [`HeaviestChainRule::confirmation_depth`].
+	pub fn is_probably_final(
+		chain: &[Header],
+		block_index: usize,
+		attacker_hashrate: f64,
+		max_reversal_probability: f64,
+	) -> bool {
+		let Some(depth) = (chain.len() as u64).checked_sub(block_index as u64 + 1) else {
+			return false;
+		};
+		Self::confirmation_depth(attacker_hashrate, max_reversal_probability)
+			.is_some_and(|required| depth >= required)
+	}
+}
+
+/// The deepest confirmation depth that [`HeaviestChainRule::confirmation_depth`] searches. An
+/// attacker with just under half the hash rate needs absurdly deep blocks, and there is no point
+/// looking further than anyone would ever wait.
+const MAX_CONFIRMATION_DEPTH: u64 = 100_000;
+
+impl Default for HeaviestChainRule {
+	fn default() -> Self {
+		HeaviestChainRule::new(THRESHOLD)
+	}
+}
+
+impl HeaderWeight for HeaviestChainRule {
+	fn weight(&self, header: &Header) -> u128 {
+		self.work(header).0
+	}
+}
+
+impl ForkChoice for HeaviestChainRule {
+	fn first_chain_is_better(&self, chain_1: &[Header], chain_2: &[Header]) -> bool {
+		WeightedChainRule(self).first_chain_is_better(chain_1, chain_2)
+	}
+}
+
+impl StreamingForkChoice for HeaviestChainRule {
+	fn first_stream_is_better<'a>(
+		&self,
+		chain_1: impl Iterator<Item = &'a Header>,
+		chain_2: impl Iterator<Item = &'a Header>,
+	) -> bool {
+		WeightedChainRule(self).first_stream_is_better(chain_1, chain_2)
+	}
+}
+
+/// The best chain is the one with the most blocks that have even hashes.
+///
+/// This exact rule is a bit contrived, but it does model a family of fork choice rules
+/// that are useful in the real world. Consider the following real world examples
+/// that have very similar implementations.
+///
+/// 1. Secondary authors. In each round there is one author who is supposed to author. If that
+///    author fails to create a block, there is a secondary author who may do so. The best chain is
+///    the one with the most primary-authored blocks. See [`MostPrimaryAuthoredRule`] for exactly
+///    that rule, with the consensus digest naming each block's author.
+///
+/// 2. Interleaved Pow/PoA. In each round there is one author who is allowed to author. Anyone else
+///    is allowed to mine a PoW-style block. The best chain is the one with the most PoA blocks, and
+///    ties are broken by the most accumulated work. See [`InterleavedRule`] for exactly that rule,
+///    with even hashes standing in for PoA blocks.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct MostBlocksWithEvenHash;
+
+/// Headers with even hashes weigh one, and all others weigh nothing.
+impl HeaderWeight for MostBlocksWithEvenHash {
+	fn weight(&self, header: &Header) -> u128 {
+		u128::from(hash(header).is_multiple_of(2))
+	}
+}
+
+impl ForkChoice for MostBlocksWithEvenHash {
+	fn first_chain_is_better(&self, chain_1: &[Header], chain_2: &[Header]) -> bool {
+		WeightedChainRule(self).first_chain_is_better(chain_1, chain_2)
+	}
+}
+
+impl StreamingForkChoice for MostBlocksWithEvenHash {
+	fn first_stream_is_better<'a>(
+		&self,
+		chain_1: impl Iterator<Item = &'a Header>,
+		chain_2: impl Iterator<Item = &'a Header>,
+	) -> bool {
+		WeightedChainRule(self).first_stream_is_better(chain_1, chain_2)
+	}
+}
+
+/// The best chain is the one with the most primary-authored blocks.
+///
+/// Each header's consensus digest names the authority who authored it. At each height, one
+/// authority is the primary author, taking turns in order just like round robin Proof of Authority.
+/// If the primary author does not produce a block, any other authority may author a secondary block
+/// instead so that the chain keeps moving. Secondary blocks are valid, but they do not count
+/// towards the chain's score, so a chain where the primaries showed up beats a longer chain that
+/// needed the fallback.
+///
+/// The genesis header is not authored by anyone, so it never counts.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct MostPrimaryAuthoredRule {
+	authorities: Vec<u64>,
+}
+
+impl MostPrimaryAuthoredRule {
+	/// A rule where the given authorities take turns being the primary author.
+	pub fn new(authorities: Vec<u64>) -> Self {
+		MostPrimaryAuthoredRule { authorities }
+	}
+
+	/// The authority who is supposed to author at the given height, or `None` if there are no
+	/// authorities at all.
+	pub fn primary_author(&self, height: u64) -> Option<u64> {
+		if self.authorities.is_empty() {
+			return None;
+		}
+		Some(self.authorities[(height % self.authorities.len() as u64) as usize])
+	}
+
+	/// Whether the header was authored by the primary author for its height.
+	pub fn is_primary(&self, header: &Header) -> bool {
+		header.height > 0 && self.primary_author(header.height) == Some(header.consensus_digest)
+	}
+}
+
+/// Primary-authored headers weigh one, and secondary ones weigh nothing.
+impl HeaderWeight for MostPrimaryAuthoredRule {
+	fn weight(&self, header: &Header) -> u128 {
+		u128::from(self.is_primary(header))
+	}
+}
+
+impl ForkChoice for MostPrimaryAuthoredRule {
+	fn first_chain_is_better(&self, chain_1: &[Header], chain_2: &[Header]) -> bool {
+		WeightedChainRule(self).first_chain_is_better(chain_1, chain_2)
+	}
+}
+
+impl StreamingForkChoice for MostPrimaryAuthoredRule {
+	fn first_stream_is_better<'a>(
+		&self,
+		chain_1: impl Iterator<Item = &'a Header>,
+		chain_2: impl Iterator<Item = &'a Header>,
+	) -> bool {
+		WeightedChainRule(self).first_stream_is_better(chain_1, chain_2)
+	}
+}
+
+/// Evidence that an author produced two different blocks at the same height. Like
+/// [`MostPrimaryAuthoredRule`], the author is read from each header's consensus digest.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Equivocation {
+	/// The author who equivocated
+	pub author: u64,
+	/// The height at which they authored more than one block
+	pub height: u64,
+}
+
+impl Equivocation {
+	/// The equivocation shown by the two headers, or `None` if they are the same header or were not
+	/// authored by the same author at the same height.
+	pub fn between(header_1: &Header, header_2: &Header) -> Option<Self> {
+		let conflicting = header_1.consensus_digest == header_2.consensus_digest &&
+			header_1.height == header_2.height &&
+			header_1 != header_2;
+		conflicting
+			.then_some(Equivocation { author: header_1.consensus_digest, height: header_1.height })
+	}
+
+	/// Whether the header is one of the blocks this equivocation is about.
+	pub fn covers(&self, header: &Header) -> bool {
+		header.consensus_digest == self.author && header.height == self.height
+	}
+}
+
+/// The best chain is the one with the most weight, where blocks from known equivocations are
+/// penalized.
+///
+/// An author who produces two blocks at the same height is trying to split the network. Whichever
+/// fork wins, we would rather it did not contain either of their blocks. So each ordinary header
+/// adds one to a chain's weight, like the longest chain rule, but each header covered by a known
+/// equivocation subtracts the penalty instead. With a penalty of zero the offending headers are
+/// simply ignored. Ties go to the second chain.
+///
+/// Equivocations are usually discovered as headers arrive, so they can be reported at any time with
+/// [`EquivocationPenalizingRule::report`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct EquivocationPenalizingRule {
+	equivocations: HashSet<Equivocation>,
+	penalty: u64,
+}
+
+impl EquivocationPenalizingRule {
+	/// A rule that subtracts the penalty for every header covered by one of the equivocations.
+	pub fn new(equivocations: impl IntoIterator<Item = Equivocation>, penalty: u64) -> Self {
+		EquivocationPenalizingRule { equivocations: equivocations.into_iter().collect(), penalty }
+	}
+
+	/// Record an equivocation. Returns whether it was new.
+	pub fn report(&mut self, equivocation: Equivocation) -> bool {
+		self.equivocations.insert(equivocation)
+	}
+
+	/// Whether the header is covered by a known equivocation.
+	pub fn is_offending(&self, header: &Header) -> bool {
+		self.equivocations
+			.contains(&Equivocation { author: header.consensus_digest, height: header.height })
+	}
+
+	/// The weight that a single header adds to its chain.
+	fn weight(&self, header: &Header) -> i128 {
+		if self.is_offending(header) {
+			-i128::from(self.penalty)
+		} else {
+			1
+		}
+	}
+
+	/// The total weight of a chain.
+	fn total_weight<'a>(&self, chain: impl IntoIterator<Item = &'a Header>) -> i128 {
+		chain.into_iter().map(|header| self.weight(header)).sum()
+	}
+}
+
+impl ForkChoice for EquivocationPenalizingRule {
+	fn first_chain_is_better(&self, chain_1: &[Header], chain_2: &[Header]) -> bool {
+		self.total_weight(chain_1) > self.total_weight(chain_2)
+	}
+}
+
+impl StreamingForkChoice for EquivocationPenalizingRule {
+	fn first_stream_is_better<'a>(
+		&self,
+		chain_1: impl Iterator<Item = &'a Header>,
+		chain_2: impl Iterator<Item = &'a Header>,
+	) -> bool {
+		self.total_weight(chain_1) > self.total_weight(chain_2)
+	}
+}
+
+/// Combines two fork choice rules. The primary rule decides, unless it considers the two chains
+/// equally good, in which case the secondary rule breaks the tie.
+///
+/// The primary rule considers two chains equal when it gives the same answer with the chains
+/// swapped. A rule using `>=` says each chain is better than the other, and a rule using `>` says
+/// neither is. Either way it has no real preference.
+pub struct Lexicographic<Primary, Secondary> {
+	primary: Primary,
+	secondary: Secondary,
+}
+
+impl<Primary, Secondary> Lexicographic<Primary, Secondary> {
+	/// Combine the two rules.
+	pub fn new(primary: Primary, secondary: Secondary) -> Self {
+		Lexicographic { primary, secondary }
+	}
+}
+
+impl<Primary: ForkChoice, Secondary: ForkChoice> ForkChoice for Lexicographic<Primary, Secondary> {
+	fn first_chain_is_better(&self, chain_1: &[Header], chain_2: &[Header]) -> bool {
+		let forward = self.primary.first_chain_is_better(chain_1, chain_2);
+		let backward = self.primary.first_chain_is_better(chain_2, chain_1);
+		if forward != backward {
+			forward
+		} else {
+			self.secondary.first_chain_is_better(chain_1, chain_2)
+		}
+	}
+}
+
+/// The interleaved PoW/PoA rule described on [`MostBlocksWithEvenHash`]. The chain with the most
+/// even blocks wins, and ties are broken by accumulated work.
+pub type InterleavedRule = Lexicographic<MostBlocksWithEvenHash, HeaviestChainRule>;
+
+impl Default for InterleavedRule {
+	fn default() -> Self {
+		Lexicographic::new(MostBlocksWithEvenHash, HeaviestChainRule::default())
+	}
+}
+
+/// How to choose between two chains that a fork choice rule considers equally good.
+///
+/// In `first_chain_is_better`, the second chain is the one currently considered best. That is how
+/// `best_chain` calls it, and clients should call it the same way.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TieBreak {
+	/// Keep whichever tip arrived first, according to the given arrival times keyed by tip hash.
+	/// Tips with no recorded arrival are treated as arriving after all the others, and two of
+	/// them keep the current chain. This is what Bitcoin nodes do.
+	FirstSeen(HashMap<u64, u64>),
+	/// Pick the chain whose tip has the lowest hash. Every node makes the same choice, whatever
+	/// order it heard about the chains in.
+	LowestTipHash,
+	/// Keep the current chain. This avoids re-orgs that gain nothing.
+	PreferCurrent,
+}
+
+impl TieBreak {
+	/// Whether the first chain wins a tie with the second, current, chain. Both chains are assumed
+	/// to be non-empty.
+	fn first_chain_wins(&self, chain_1: &[Header], chain_2: &[Header]) -> bool {
+		let tip_hash = |chain: &[Header]| hash(chain.last().expect("chains are not empty"));
+		match self {
+			TieBreak::FirstSeen(arrivals) => {
+				let arrival = |chain| arrivals.get(&tip_hash(chain)).copied().unwrap_or(u64::MAX);
+				arrival(chain_1) < arrival(chain_2)
+			},
+			TieBreak::LowestTipHash => tip_hash(chain_1) < tip_hash(chain_2),
+			TieBreak::PreferCurrent => false,
+		}
+	}
+}
+
+/// Wraps another fork choice rule so that ties are broken by an explicit [`TieBreak`] policy
+/// rather than by whatever the inner rule happens to do.
+///
+/// Like [`Lexicographic`], the inner rule considers two chains equal when it gives the same answer
+/// with the chains swapped.
+pub struct WithTieBreak<R> {
+	inner: R,
+	tie_break: TieBreak,
+}
+
+impl<R> WithTieBreak<R> {
+	/// Break the inner rule's ties with the given policy.
+	pub fn new(inner: R, tie_break: TieBreak) -> Self {
+		WithTieBreak { inner, tie_break }
+	}
+
+	/// Record when a tip arrived, for the first seen policy. Only the first arrival of each tip is
+	/// kept. Does nothing under the other policies.
+	pub fn record_arrival(&mut self, tip_hash: u64, time: u64) {
+		if let TieBreak::FirstSeen(arrivals) = &mut self.tie_break {
+			arrivals.entry(tip_hash).or_insert(time);
+		}
+	}
+}
+
+impl<R: ForkChoice> ForkChoice for WithTieBreak<R> {
+	fn first_chain_is_better(&self, chain_1: &[Header], chain_2: &[Header]) -> bool {
+		let forward = self.inner.first_chain_is_better(chain_1, chain_2);
+		let backward = self.inner.first_chain_is_better(chain_2, chain_1);
+		if forward != backward {
+			forward
+		} else {
+			self.tie_break.first_chain_wins(chain_1, chain_2)
+		}
+	}
+}
+
+/// Wraps another fork choice rule so that it never picks a chain that abandons a finalized header.
+///
+/// Once a header is finalized, the chain must never be reorganized past it, no matter how long or
+/// heavy a competing fork is. So chains that do not descend from the finalized header are
+/// disqualified first, and only then does the inner rule compare the rest. A chain descends from
+/// the finalized header if it contains it, or if it starts right after it.
+pub struct FinalizedBase<R> {
+	finalized: u64,
+	inner: R,
+}
+
+impl<R> FinalizedBase<R> {
+	/// Wrap the inner rule so that only chains descending from the header with the given hash
+	/// qualify.
+	pub fn new(finalized: u64, inner: R) -> Self {
+		FinalizedBase { finalized, inner }
+	}
+
+	/// Whether the chain descends from the finalized header.
+	pub fn qualifies(&self, chain: &[Header]) -> bool {
+		chain.first().is_some_and(|first| first.parent == self.finalized) ||
+			chain.iter().any(|header| hash(header) == self.finalized)
+	}
+}
+
+impl<R: ForkChoice> FinalizedBase<R> {
+	/// The best of the chains that descend from the finalized header, or `None` if no chain does.
+	///
+	/// The provided `best_chain` has to return some chain, so when nothing qualifies it falls back
+	/// to the first candidate. Use this method instead when that matters.
+	pub fn best_qualifying_chain<'a>(
+		&self,
+		candidate_chains: &[&'a [Header]],
+	) -> Option<&'a [Header]> {
+		let qualifying: Vec<&[Header]> =
+			candidate_chains.iter().copied().filter(|chain| self.qualifies(chain)).collect();
+		(!qualifying.is_empty()).then(|| self.inner.best_chain(&qualifying))
+	}
+}
+
+impl<R: ForkChoice> ForkChoice for FinalizedBase<R> {
+	fn first_chain_is_better(&self, chain_1: &[Header], chain_2: &[Header]) -> bool {
+		match (self.qualifies(chain_1), self.qualifies(chain_2)) {
+			(true, true) => self.inner.first_chain_is_better(chain_1, chain_2),
+			(first, _) => first,
+		}
+	}
+}
+
+/// Whether every header in the chain is a well linked child of the one before it. That is, its
+/// parent hash points at the previous header and its height is one more.
+pub fn is_well_linked(chain: &[Header]) -> bool {
+	chain.windows(2).all(|pair| {
+		pair[1].parent == hash(&pair[0]) && Some(pair[1].height) == pair[0].height.checked_add(1)
+	})
+}
+
+/// Wraps another fork choice rule so that malformed chains can never win.
+///
+/// The rules in this lesson assume the chains they compare are valid. When that assumption is
+/// wrong, a chain with broken links can easily look longest or heaviest. This wrapper disqualifies
+/// any chain that is not [well linked](is_well_linked). It does not check anything that needs the
+/// block bodies or the consensus engine.
+pub struct Validated<R> {
+	inner: R,
+}
+
+impl<R> Validated<R> {
+	/// Wrap the inner rule.
+	pub fn new(inner: R) -> Self {
+		Validated { inner }
+	}
+}
+
+impl<R: ForkChoice> Validated<R> {
+	/// The best of the well linked chains, or `None` if every candidate is malformed.
+	pub fn best_valid_chain<'a>(&self, candidate_chains: &[&'a [Header]]) -> Option<&'a [Header]> {
+		let valid: Vec<&[Header]> =
+			candidate_chains.iter().copied().filter(|chain| is_well_linked(chain)).collect();
+		(!valid.is_empty()).then(|| self.inner.best_chain(&valid))
+	}
+}
+
+impl<R: ForkChoice> ForkChoice for Validated<R> {
+	fn first_chain_is_better(&self, chain_1: &[Header], chain_2: &[Header]) -> bool {
+		match (is_well_linked(chain_1), is_well_linked(chain_2)) {
+			(true, true) => self.inner.first_chain_is_better(chain_1, chain_2),
+			(first, _) => first,
+		}
+	}
+}
+
+/// A candidate chain along with the score a fork choice rule gave it. See
+/// [`IncrementalForkChoice::rank_chains`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ChainScore<'a, Score> {
+	/// The candidate chain
+	pub chain: &'a [Header],
+	/// The chain's score, such as its length, its total work, or its number of even hashes
+	pub score: Score,
+}
+
+/// A fork choice rule that scores a chain by accumulating a score one header at a time.
+///
+/// The longest, heaviest, even hash, primary-authored and equivocation penalizing rules all work
+/// this way. That means a
+/// client can remember the score of every header it has seen, and score a new header from its
+/// parent's score alone. Comparing two tips is then a single comparison, however long the chains
+/// are. See [`ScoreCache`].
+pub trait IncrementalForkChoice {
+	/// The accumulated score of a chain.
+	type Score: Copy + Ord + Default;
+
+	/// The score of a chain once the given header is appended to a chain with the parent score.
+	fn extend_score(&self, parent_score: Self::Score, header: &Header) -> Self::Score;
+
+	/// Whether a chain with the first score is better than a chain with the second. By default the
+	/// higher score wins and ties go to the second chain.
+	fn first_score_is_better(&self, score_1: Self::Score, score_2: Self::Score) -> bool {
+		score_1 > score_2
+	}
+
+	/// The score of a complete chain, accumulated one header at a time from the first.
+	fn chain_score<'a>(&self, chain: impl IntoIterator<Item = &'a Header>) -> Self::Score {
+		chain
+			.into_iter()
+			.fold(Self::Score::default(), |score, header| self.extend_score(score, header))
+	}
+
+	/// Compare many chains and return the best one, or `None` if there are no candidates.
+	///
+	/// Each chain's score depends on that chain alone, so the chains are scored in parallel. Only
+	/// the final comparison of the scores is serial, and it happens in order, so the result is the
+	/// same chain that `ForkChoice::best_chain` would pick.
+	#[cfg(feature = "rayon")]
+	fn par_best_chain<'a>(&self, candidate_chains: &[&'a [Header]]) -> Option<&'a [Header]>
+	where
+		Self: Sync,
+		Self::Score: Send,
+	{
+		use rayon::prelude::*;
+
+		let scores: Vec<Self::Score> =
+			candidate_chains.par_iter().map(|chain| self.chain_score(*chain)).collect();
+		let best = (1..scores.len()).fold(0, |best, i| {
+			if self.first_score_is_better(scores[i], scores[best]) {
+				i
+			} else {
+				best
+			}
+		});
+		candidate_chains.get(best).copied()
+	}
+
+	/// Score every candidate chain and sort them best first, so callers can see why the winner won
+	/// rather than just which chain it was.
+	///
+	/// Each chain is scored once, and the scores are sorted with `first_score_is_better`. Ties are
+	/// broken just as `ForkChoice::best_chain` breaks them. When neither score is better, the
+	/// earlier candidate ranks first, and when each counts as better than the other, the later one
+	/// does.
+	fn rank_chains<'a>(
+		&self,
+		candidate_chains: &[&'a [Header]],
+	) -> Vec<ChainScore<'a, Self::Score>> {
+		let mut ranked: Vec<(usize, ChainScore<'a, Self::Score>)> = candidate_chains
+			.iter()
+			.enumerate()
+			.map(|(index, chain)| (index, ChainScore { chain, score: self.chain_score(*chain) }))
+			.collect();
+		ranked.sort_unstable_by(|(index_1, chain_1), (index_2, chain_2)| {
+			let first_is_better = self.first_score_is_better(chain_1.score, chain_2.score);
+			let second_is_better = self.first_score_is_better(chain_2.score, chain_1.score);
+			match (first_is_better, second_is_better) {
+				(true, false) => Ordering::Less,
+				(false, true) => Ordering::Greater,
+				(false, false) => index_1.cmp(index_2),
+				(true, true) => index_2.cmp(index_1),
+			}
+		});
+		ranked.into_iter().map(|(_, ranked)| ranked).collect()
+	}
+}
+
+impl IncrementalForkChoice for LongestChainRule {
+	type Score = u64;
+
+	fn extend_score(&self, parent_score: u64, _: &Header) -> u64 {
+		parent_score.saturating_add(1)
+	}
+}
+
+impl IncrementalForkChoice for HeaviestChainRule {
+	type Score = Work;
+
+	fn extend_score(&self, parent_score: Work, header: &Header) -> Work {
+		parent_score + self.work(header)
+	}
+}
+
+impl IncrementalForkChoice for MostBlocksWithEvenHash {
+	type Score = u64;
+
+	fn extend_score(&self, parent_score: u64, header: &Header) -> u64 {
+		parent_score.saturating_add(u64::from(hash(header).is_multiple_of(2)))
+	}
+}
+
+impl IncrementalForkChoice for MostPrimaryAuthoredRule {
+	type Score = u64;
+
+	fn extend_score(&self, parent_score: u64, header: &Header) -> u64 {
+		parent_score.saturating_add(u64::from(self.is_primary(header)))
+	}
+}
+
+impl IncrementalForkChoice for EquivocationPenalizingRule {
+	type Score = i128;
+
+	fn extend_score(&self, parent_score: i128, header: &Header) -> i128 {
+		parent_score.saturating_add(self.weight(header))
+	}
+}
+
+/// Remembers the accumulated score of every header given to it, according to the rule `R`.
+///
+/// A header whose parent has not been scored is treated as the first header of its chain, just as
+/// the slice based rules treat the first header of a slice. So headers should be inserted parents
+/// first.
+pub struct ScoreCache<R: IncrementalForkChoice> {
+	rule: R,
+	scores: HashMap<u64, R::Score>,
+}
+
+impl<R: IncrementalForkChoice> ScoreCache<R> {
+	/// An empty cache for the given rule.
+	pub fn new(rule: R) -> Self {
+		ScoreCache { rule, scores: HashMap::new() }
+	}
+
+	/// Score a header from its parent's cached score, remember it, and return it.
+	pub fn insert(&mut self, header: &Header) -> R::Score {
+		let parent_score = self.scores.get(&header.parent).copied().unwrap_or_default();
+		let score = self.rule.extend_score(parent_score, header);
+		self.scores.insert(hash(header), score);
+		score
+	}
+
+	/// Score a whole chain and return the score of its tip. Only the suffix of the chain after the
+	/// last header that is already cached is actually scored.
+	pub fn insert_chain(&mut self, chain: &[Header]) -> R::Score {
+		let cached = chain.iter().rposition(|header| self.scores.contains_key(&hash(header)));
+		let mut score = cached.map_or_else(R::Score::default, |i| self.scores[&hash(&chain[i])]);
+		for header in &chain[cached.map_or(0, |i| i + 1)..] {
+			score = self.insert(header);
+		}
+		score
+	}
+
+	/// The cached score of the header with the given hash.
+	pub fn score(&self, header_hash: u64) -> Option<R::Score> {
+		self.scores.get(&header_hash).copied()
+	}
+
+	/// Whether the chain ending at the first tip is better than the chain ending at the second.
+	/// Returns `None` if either tip has not been scored.
+	pub fn first_tip_is_better(&self, tip_1: u64, tip_2: u64) -> Option<bool> {
+		Some(self.rule.first_score_is_better(self.score(tip_1)?, self.score(tip_2)?))
+	}
+
+	/// The number of headers that have been scored.
+	pub fn len(&self) -> usize {
+		self.scores.len()
+	}
+
+	/// Whether no headers have been scored yet.
+	pub fn is_empty(&self) -> bool {
+		self.scores.is_empty()
+	}
+}
+
+/// GHOST - Greedy Heaviest Observed SubTree
+///
+/// GHOST needs information about blocks that are _not_ in the chain it chooses, so unlike the rules
+/// above it works on a whole [`ForkTree`] rather than on linear chains.
+///
+/// Starting from the root, repeatedly step to the child whose subtree contains the most blocks,
+/// until reaching a leaf. Unlike the longest chain rule, blocks on side branches still count, as
+/// long as they build on the same fork. This matters when blocks are produced quickly and many of
+/// them are orphaned, because an attacker's private chain no longer needs to beat the longest
+/// honest chain, but the whole honest subtree.
+///
+/// Ties are broken in favour of the lower hash, so that every node makes the same choice.
+///
+/// The GHOST rule was first published in 2013 by Yonatan Sompolinsky and Aviv Zohar.
+/// Learn more at https://eprint.iacr.org/2013/881.pdf
+pub struct GhostRule;
+
+impl GhostRule {
+	/// The best chain in the tree, from the root to the chosen leaf.
+	pub fn best_chain(tree: &ForkTree) -> Vec<Header> {
+		let subtree_sizes = tree.subtree_sizes();
+		let mut current = tree.root();
+		let mut chain = vec![tree.get(current).expect("the root is in the tree").clone()];
+		while let Some(&next) = tree
+			.children(current)
+			.iter()
+			.max_by_key(|child| (subtree_sizes[*child], Reverse(**child)))
+		{
+			chain.push(tree.get(next).expect("children are in the tree").clone());
+			current = next;
+		}
+		chain
+	}
+}
+
+/// Build and return two different chains with a common prefix.
+/// They should have the same genesis header. Both chains should be valid.
+/// The first chain should be longer (have more blocks), but the second
+/// chain should have more accumulated work.
+///
+/// Return your solutions as three vectors:
+/// 1. The common prefix including genesis
+/// 2. The suffix chain which is longer (non-overlapping with the common prefix)
+/// 3. The suffix chain with more work (non-overlapping with the common prefix)
+pub(crate) fn create_fork_one_side_longer_other_side_heavier(
+) -> (Vec<Header>, Vec<Header>, Vec<Header>) {
+	let g = Header::genesis();
+	let extrinsics = vec![1, 2, 3];
+	let extrinsics_root = hash(&extrinsics);
+	let b1 = g.child(extrinsics_root, 0);
+	let common = vec![b1.clone()];
+
+	let b2_extrinsics = hash(&[4]);
+	let b2 = b1.child(b2_extrinsics, 6);
+	let b3_extrinsics = hash(&[5]);
+	let b3 = b2.child(b3_extrinsics, 10);
+	let longer = vec![b2.clone(), b3];
+
+	let mut c2 = b1.child(b2_extrinsics, 5);
+
+	let mut i = 1;
+	loop {
+		c2.consensus_digest = c2.consensus_digest + i;
+		if hash(&c2) < u64::max_value() / 1000 {
+			break;
+		}
+		i += 1;
+	}
+	let more_work = vec![c2];
+
+	(common, longer, more_work)
+}
+
+const LONGEST_CHAIN_HINTS: Hints = &[
+	"The longest chain rule only cares about how many headers each chain contains.",
+	"Compare the lengths of the two slices with `len()`.",
+	"`best_chain` is provided for you, so once `first_chain_is_better` is right it should pass too.",
+];
+
+const HEAVIEST_CHAIN_HINTS: Hints = &[
+	"The heaviest chain is the one whose blocks contain the most accumulated work in total.",
+	"Compute the work of each header with `Work::of_hash(hash(header))` and sum it over each chain.",
+	"Headers whose hash is not below the threshold were not mined properly. They count as no work.",
+];
+
+const MOST_EVEN_BLOCKS_HINTS: Hints = &[
+	"Only the hashes of the headers matter for this rule, not their contents.",
+	"Count the headers in each chain for which `hash(header).is_multiple_of(2)`.",
+];
+
+const MINE_EXTRA_HARD_HINTS: Hints = &[
+	"Mining means changing the header until its hash happens to fall below the threshold.",
+	"The consensus digest is the only field you are free to change while mining.",
+	"Keep incrementing `block.header.consensus_digest` in a loop until the hash is low enough.",
+];
+
+const FORK_ONE_SIDE_LONGER_OTHER_HEAVIER_HINTS: Hints = &[
+	"Build the shared prefix first, then branch off two suffixes from its last header.",
+	"The heavier suffix can be shorter as long as its blocks were mined to a much lower hash.",
+	"Use a custom threshold well below `THRESHOLD` when mining the blocks of the heavier suffix.",
+];
+
+#[test]
+fn bc_5_longest_chain() {
+	let g = Header::genesis();
+
+	let h_a1 = g.child(hash(&[1]), 1);
+	let h_a2 = h_a1.child(hash(&[2]), 2);
+	let chain_1 = &[g.clone(), h_a1, h_a2];
+
+	let h_b1 = g.child(hash(&[3]), 3);
+	let chain_2 = &[g, h_b1];
+
+	hint_assert!(LongestChainRule.first_chain_is_better(chain_1, chain_2), LONGEST_CHAIN_HINTS);
+
+	hint_assert_eq!(LongestChainRule.best_chain(&[chain_1, chain_2]), chain_1, LONGEST_CHAIN_HINTS);
+}
+
+#[test]
+fn bc_5_mine_to_custom_difficulty() {
+	let g = Block::genesis();
+	let mut b1 = g.child(vec![1, 2, 3]);
+
+	// We want the custom threshold to be high enough that we don't take forever mining
+	// but low enough that it is unlikely we accidentally meet it with the normal
+	// block creation function
+	let custom_threshold = u64::max_value() / 1000;
+	mine_extra_hard(&mut b1, custom_threshold);
+
+	hint_assert!(hash(&b1.header) < custom_threshold, MINE_EXTRA_HARD_HINTS);
+	assert!(HeaviestChainRule::default().work(&b1.header) >= Work::of_hash(custom_threshold));
+}
+
+#[test]
+fn bc_5_heaviest_chain() {
+	let g = Header::genesis();
+
+	let mut i = 0;
+	let h_a1 = loop {
+		let header = g.child(hash(&[i]), i);
+		// Extrinsics root hash must be higher than threshold (less work done)
+		if hash(&header) > THRESHOLD {
+			break header;
+		}
+		i += 1;
+	};
+	let chain_1 = &[g.clone(), h_a1];
+
+	let h_b1 = loop {
+		let header = g.child(hash(&[i]), i);
+		// Extrinsics root hash must be lower than threshold (more work done)
+		if hash(&header) < THRESHOLD {
+			break header;
+		}
+		i += 1;
+	};
+	let chain_2 = &[g, h_b1];
+
+	hint_assert!(
+		HeaviestChainRule::default().first_chain_is_better(chain_2, chain_1),
+		HEAVIEST_CHAIN_HINTS
+	);
+
+	hint_assert_eq!(
+		HeaviestChainRule::default().best_chain(&[chain_1, chain_2]),
+		chain_2,
+		HEAVIEST_CHAIN_HINTS
+	);
+
+	// Measured against a much harder threshold, neither chain contains any work at all.
+	let harder = HeaviestChainRule::new(hash(&chain_2[1]));
+	assert!(!harder.first_chain_is_better(chain_2, chain_1));
+}
+
+#[test]
+fn bc_5_most_even_blocks() {
+	let g = Header::genesis();
+
+	let mut h_a1 = g.child(2, 0);
+	for i in 0..u64::max_value() {
+		h_a1 = g.child(2, i);
+		if hash(&h_a1).is_multiple_of(2) {
+			break;
+		}
+	}
+	let mut h_a2 = g.child(2, 0);
+	for i in 0..u64::max_value() {
+		h_a2 = h_a1.child(2, i);
+		if hash(&h_a2).is_multiple_of(2) {
+			break;
+		}
+	}
+	let chain_1 = &[g.clone(), h_a1, h_a2];
+
+	let mut h_b1 = g.child(2, 0);
+	for i in 0..u64::max_value() {
+		h_b1 = g.child(2, i);
+		if !hash(&h_b1).is_multiple_of(2) {
+			break;
+		}
+	}
+	let mut h_b2 = g.child(2, 0);
+	for i in 0..u64::max_value() {
+		h_b2 = h_b1.child(2, i);
+		if !hash(&h_b2).is_multiple_of(2) {
+			break;
+		}
+	}
+	let chain_2 = &[g, h_b1, h_b2];
+
+	hint_assert!(
+		MostBlocksWithEvenHash.first_chain_is_better(chain_1, chain_2),
+		MOST_EVEN_BLOCKS_HINTS
+	);
+
+	hint_assert_eq!(
+		MostBlocksWithEvenHash.best_chain(&[chain_1, chain_2]),
+		chain_1,
+		MOST_EVEN_BLOCKS_HINTS
+	);
+}
+
+#[test]
+fn bc_5_longest_vs_heaviest() {
+	let (_, longest_chain, pow_chain) = create_fork_one_side_longer_other_side_heavier();
+
+	hint_assert!(
+		LongestChainRule.first_chain_is_better(&longest_chain, &pow_chain),
+		FORK_ONE_SIDE_LONGER_OTHER_HEAVIER_HINTS
+	);
+
+	hint_assert_eq!(
+		LongestChainRule.best_chain(&[&longest_chain, &pow_chain]),
+		&longest_chain,
+		FORK_ONE_SIDE_LONGER_OTHER_HEAVIER_HINTS
+	);
+
+	let (_, longest_chain, pow_chain) = create_fork_one_side_longer_other_side_heavier();
+
+	hint_assert!(
+		HeaviestChainRule::default().first_chain_is_better(&pow_chain, &longest_chain),
+		FORK_ONE_SIDE_LONGER_OTHER_HEAVIER_HINTS
+	);
+
+	hint_assert_eq!(
+		HeaviestChainRule::default().best_chain(&[&longest_chain, &pow_chain]),
+		&pow_chain,
+		FORK_ONE_SIDE_LONGER_OTHER_HEAVIER_HINTS
+	);
+}
+
+#[cfg(test)]
+use crate::test_utils::longest_and_heaviest_chains;
+
+#[test]
+fn bc_5_streaming_agrees_with_slices() {
+	let (longest_chain, heaviest_chain) = longest_and_heaviest_chains();
+
+	assert_eq!(
+		LongestChainRule.first_stream_is_better(longest_chain.iter(), heaviest_chain.iter()),
+		LongestChainRule.first_chain_is_better(&longest_chain, &heaviest_chain)
+	);
+	assert_eq!(
+		HeaviestChainRule::default()
+			.first_stream_is_better(heaviest_chain.iter(), longest_chain.iter()),
+		HeaviestChainRule::default().first_chain_is_better(&heaviest_chain, &longest_chain)
+	);
+	assert_eq!(
+		MostBlocksWithEvenHash.first_stream_is_better(heaviest_chain.iter(), longest_chain.iter()),
+		MostBlocksWithEvenHash.first_chain_is_better(&heaviest_chain, &longest_chain)
+	);
+}
+
+#[test]
+fn bc_5_streaming_best_chain() {
+	let (longest_chain, heaviest_chain) = longest_and_heaviest_chains();
+	let candidates = [longest_chain.iter(), heaviest_chain.iter()];
+
+	let best = LongestChainRule.best_stream(candidates.clone()).unwrap();
+	assert!(best.eq(longest_chain.iter()));
+
+	// The heaviest chain is lazily filtered, just to show that any cloneable iterator will do.
+	let filtered = candidates.iter().map(|chain| chain.clone().filter(|_| true));
+	let best = HeaviestChainRule::default().best_stream(filtered).unwrap();
+	assert!(best.eq(heaviest_chain.iter()));
+
+	let no_candidates: [std::slice::Iter<Header>; 0] = [];
+	assert!(LongestChainRule.best_stream(no_candidates).is_none());
+}
+
+/// A rule that only knows how to compare streams, like one a downstream crate might write.
+#[cfg(test)]
+struct ShortestChainRule;
+
+#[cfg(test)]
+impl StreamingForkChoice for ShortestChainRule {
+	fn first_stream_is_better<'a>(
+		&self,
+		chain_1: impl Iterator<Item = &'a Header>,
+		chain_2: impl Iterator<Item = &'a Header>,
+	) -> bool {
+		chain_1.count() <= chain_2.count()
+	}
+}
+
+#[test]
+fn bc_5_streamed_rule_compares_slices() {
+	let (longest_chain, heaviest_chain) = longest_and_heaviest_chains();
+
+	let rule = Streamed(ShortestChainRule);
+	assert!(rule.first_chain_is_better(&heaviest_chain, &longest_chain));
+	assert_eq!(rule.best_chain(&[&longest_chain, &heaviest_chain]), heaviest_chain.as_slice());
+
+	let mut tree = ForkTree::new(Header::genesis());
+	for header in longest_chain.iter().chain(&heaviest_chain) {
+		tree.insert(header.clone());
+	}
+	assert_eq!(tree.best_leaf_by_chains(&rule), heaviest_chain.last().unwrap());
+	assert_eq!(
+		tree.best_leaf_by_chains(&Streamed(LongestChainRule)),
+		longest_chain.last().unwrap()
+	);
+}
+
+#[cfg(feature = "rayon")]
+#[test]
+fn bc_5_parallel_best_chain_agrees_with_serial() {
+	let (prefix, longer, heavier) = create_fork_one_side_longer_other_side_heavier();
+	let longest_chain = [prefix.clone(), longer].concat();
+	let heaviest_chain = [prefix.clone(), heavier].concat();
+
+	// Plenty of candidates, including ties, so the order of the final comparison matters.
+	let mut candidates: Vec<&[Header]> = Vec::new();
+	for _ in 0..50 {
+		candidates.extend([&prefix[..], &heaviest_chain[..], &longest_chain[..]]);
+	}
+
+	assert_eq!(
+		LongestChainRule.par_best_chain(&candidates),
+		Some(LongestChainRule.best_chain(&candidates))
+	);
+	let heaviest = HeaviestChainRule::default();
+	assert_eq!(heaviest.par_best_chain(&candidates), Some(heaviest.best_chain(&candidates)));
+	assert_eq!(
+		MostBlocksWithEvenHash.par_best_chain(&candidates),
+		Some(MostBlocksWithEvenHash.best_chain(&candidates))
+	);
+	assert_eq!(LongestChainRule.par_best_chain(&[]), None);
+}
+
+#[test]
+fn bc_5_confirmation_depth_matches_whitepaper() {
+	// The table of solutions for a reversal probability below 0.1% in the Bitcoin whitepaper.
+	for (attacker_hashrate, expected) in [
+		(0.10, 5),
+		(0.15, 8),
+		(0.20, 11),
+		(0.25, 15),
+		(0.30, 24),
+		(0.35, 41),
+		(0.40, 89),
+		(0.45, 340),
+	] {
+		assert_eq!(HeaviestChainRule::confirmation_depth(attacker_hashrate, 0.001), Some(expected));
+	}
+
+	assert_eq!(HeaviestChainRule::confirmation_depth(0.5, 0.001), None);
+	assert_eq!(HeaviestChainRule::confirmation_depth(0.1, 0.0), None);
+	assert_eq!(HeaviestChainRule::reversal_probability(0.6, 1_000), 1.0);
+	assert!(HeaviestChainRule::reversal_probability(0.1, 10) < 0.0001);
+}
+
+#[test]
+fn bc_5_confirmation_depth_rejects_invalid_inputs() {
+	assert_eq!(HeaviestChainRule::confirmation_depth(f64::NAN, 0.001), None);
+	assert_eq!(HeaviestChainRule::confirmation_depth(0.1, f64::NAN), None);
+	assert_eq!(HeaviestChainRule::confirmation_depth(-0.1, 0.001), None);
+	assert_eq!(HeaviestChainRule::confirmation_depth(0.1, 1.5), None);
+	assert_eq!(HeaviestChainRule::confirmation_depth(0.1, -0.5), None);
+	assert!(!HeaviestChainRule::is_probably_final(&[Header::genesis()], 0, f64::NAN, 0.001));
+
+	// The edges of the valid range still work. A block with nothing on top can always be reversed.
+	assert_eq!(HeaviestChainRule::confirmation_depth(0.0, 0.001), Some(1));
+	assert_eq!(HeaviestChainRule::confirmation_depth(0.1, 1.0), Some(1));
+}
+
+#[test]
+fn bc_5_confirmation_depth_for_strong_attackers() {
+	// Close to half the hash rate, thousands of blocks are needed. The answer is still the
+	// shallowest depth that is enough.
+	let depth = HeaviestChainRule::confirmation_depth(0.49, 0.001).unwrap();
+	assert!(HeaviestChainRule::reversal_probability(0.49, depth) < 0.001);
+	assert!(HeaviestChainRule::reversal_probability(0.49, depth - 1) >= 0.001);
+}
+
+#[test]
+fn bc_5_reversal_probability_rejects_invalid_inputs() {
+	assert!(HeaviestChainRule::reversal_probability(f64::NAN, 10).is_nan());
+	assert!(HeaviestChainRule::reversal_probability(-0.1, 10).is_nan());
+	assert!(HeaviestChainRule::reversal_probability(1.5, 10).is_nan());
+	assert_eq!(HeaviestChainRule::reversal_probability(0.0, 10), 0.0);
+
+	// Huge depths neither hang nor wrap around into a wrong answer.
+	let deepest = HeaviestChainRule::reversal_probability(0.499, MAX_CONFIRMATION_DEPTH);
+	assert_eq!(HeaviestChainRule::reversal_probability(0.499, u64::MAX), deepest);
+	assert!(deepest < HeaviestChainRule::reversal_probability(0.499, 1_000));
+
+	// Past the deepest depth anyone would wait, there is no answer.
+	assert_eq!(HeaviestChainRule::confirmation_depth(0.4999, 1e-12), None);
+}
+
+#[test]
+fn bc_5_deep_blocks_are_probably_final() {
+	let chain = crate::test_utils::linear_chain(6);
+
+	// Genesis has six blocks on top of it, but the tip has none.
+	assert!(HeaviestChainRule::is_probably_final(&chain, 0, 0.1, 0.001));
+	assert!(HeaviestChainRule::is_probably_final(&chain, 1, 0.1, 0.001));
+	assert!(!HeaviestChainRule::is_probably_final(&chain, 2, 0.1, 0.001));
+	assert!(!HeaviestChainRule::is_probably_final(&chain, 6, 0.1, 0.001));
+	assert!(!HeaviestChainRule::is_probably_final(&chain, 7, 0.1, 0.001));
+
+	// Nothing is final against a majority attacker.
+	assert!(!HeaviestChainRule::is_probably_final(&chain, 0, 0.5, 0.001));
+}
+
+#[test]
+fn bc_5_builtin_rules_break_ties_the_same_way() {
+	let fork = crate::test_utils::Fork::new(1, 1, 1);
+	let (left, right) = (fork.left_chain(), fork.right_chain());
+
+	// Both rules give ties to the second chain, so the first of equal candidates is kept. These
+	// headers are not mined, so both chains have no work at all.
+	assert!(!LongestChainRule.first_chain_is_better(&left, &right));
+	assert!(!LongestChainRule.first_chain_is_better(&right, &left));
+	assert!(!LongestChainRule.first_score_is_better(3, 3));
+	let heaviest = HeaviestChainRule::default();
+	assert!(!heaviest.first_chain_is_better(&left, &right));
+	assert!(!heaviest.first_chain_is_better(&right, &left));
+	assert_best_chain!(LongestChainRule, [left, right], left);
+	assert_best_chain!(heaviest, [left, right], left);
+}
+
+#[test]
+fn bc_5_tie_break_policies() {
+	let fork = crate::test_utils::Fork::new(1, 1, 1);
+	let (left, right) = (fork.left_chain(), fork.right_chain());
+	let longer =
+		[left.clone(), crate::test_utils::extend(fork.left.last().unwrap(), 1, 3)].concat();
+
+	// Whatever the policy, a chain that is actually better still wins.
+	let current = WithTieBreak::new(LongestChainRule, TieBreak::PreferCurrent);
+	assert!(current.first_chain_is_better(&longer, &right));
+	assert!(!current.first_chain_is_better(&left, &right));
+	assert!(!current.first_chain_is_better(&right, &left));
+	assert_best_chain!(current, [left, right], left);
+
+	let lowest = WithTieBreak::new(HeaviestChainRule::default(), TieBreak::LowestTipHash);
+	let (low, high) =
+		if hash(&fork.left[0]) < hash(&fork.right[0]) { (&left, &right) } else { (&right, &left) };
+	assert!(lowest.first_chain_is_better(low, high));
+	assert!(!lowest.first_chain_is_better(high, low));
+	assert_best_chain!(lowest, [high, low], low);
+	assert_best_chain!(lowest, [low, high], low);
+
+	let mut first_seen = WithTieBreak::new(LongestChainRule, TieBreak::FirstSeen(HashMap::new()));
+	first_seen.record_arrival(hash(&fork.right[0]), 5);
+	first_seen.record_arrival(hash(&fork.left[0]), 7);
+	first_seen.record_arrival(hash(&fork.right[0]), 9);
+	assert!(first_seen.first_chain_is_better(&right, &left));
+	assert!(!first_seen.first_chain_is_better(&left, &right));
+	assert_best_chain!(first_seen, [left, right], right);
+
+	// Tips that were never seen arrive last, and two of them keep the current chain.
+	let unseen = WithTieBreak::new(LongestChainRule, TieBreak::FirstSeen(HashMap::new()));
+	assert!(!unseen.first_chain_is_better(&left, &right));
+	assert!(!unseen.first_chain_is_better(&right, &left));
+}
+
+/// Weighs each header by how many extrinsics it claims to have, which these tests keep in the state
+/// field. Just the kind of experiment the weighted rule is for.
+#[cfg(test)]
+struct ByExtrinsicCount;
+
+#[cfg(test)]
+impl HeaderWeight for ByExtrinsicCount {
+	fn weight(&self, header: &Header) -> u128 {
+		u128::from(header.state)
+	}
+}
+
+#[test]
+fn bc_5_weighted_rule_with_custom_weight() {
+	let g = Header::genesis();
+	let busy = [g.clone(), g.child(hash(&[1]), 50)];
+	let quiet = [g.clone(), g.child(hash(&[2]), 1), g.child(hash(&[3]), 1)];
+
+	let rule = WeightedChainRule(ByExtrinsicCount);
+	assert_eq!(rule.total_weight(&quiet), 2);
+	assert!(rule.first_chain_is_better(&busy, &quiet));
+	assert!(!rule.first_chain_is_better(&busy, &busy));
+	assert!(rule.first_stream_is_better(busy.iter(), quiet.iter()));
+	assert_best_chain!(rule, [quiet, busy], busy);
+}
+
+#[test]
+fn bc_5_builtin_rules_are_weighted_rules() {
+	let (longest_chain, heaviest_chain) = longest_and_heaviest_chains();
+	let pairs = [(&longest_chain, &heaviest_chain), (&heaviest_chain, &longest_chain)];
+
+	let heaviest = HeaviestChainRule::default();
+	for (chain_1, chain_2) in pairs {
+		assert_eq!(
+			heaviest.first_chain_is_better(chain_1, chain_2),
+			WeightedChainRule(heaviest).first_chain_is_better(chain_1, chain_2)
+		);
+		assert_eq!(
+			MostBlocksWithEvenHash.first_chain_is_better(chain_1, chain_2),
+			WeightedChainRule(MostBlocksWithEvenHash).first_chain_is_better(chain_1, chain_2)
+		);
+	}
+	assert_eq!(WeightedChainRule(heaviest).total_weight(&heaviest_chain), {
+		let work: Work = heaviest_chain.iter().map(|header| heaviest.work(header)).sum();
+		work.0
+	});
+}
+
+#[test]
+fn bc_5_rank_chains_shows_each_score() {
+	let (prefix, longer, heavier) = create_fork_one_side_longer_other_side_heavier();
+	let longest_chain = [prefix.clone(), longer].concat();
+	let heaviest_chain = [prefix.clone(), heavier].concat();
+	let candidates = [&prefix[..], &heaviest_chain[..], &longest_chain[..]];
+
+	let ranked = LongestChainRule.rank_chains(&candidates);
+	let lengths: Vec<u64> = ranked.iter().map(|ranked| ranked.score).collect();
+	assert_eq!(lengths, vec![3, 2, 1]);
+	assert_eq!(ranked[0].chain, longest_chain.as_slice());
+
+	let heaviest = HeaviestChainRule::default();
+	let ranked = heaviest.rank_chains(&candidates);
+	assert_eq!(ranked[0].chain, heaviest.best_chain(&candidates));
+	assert!(ranked.windows(2).all(|pair| pair[0].score >= pair[1].score));
+
+	// Neither of the other chains was mined, so they are equally good and keep their order.
+	assert_eq!(ranked[1], ChainScore { chain: &prefix[..], score: Work(0) });
+	assert_eq!(ranked[2], ChainScore { chain: &longest_chain[..], score: Work(0) });
+	assert!(LongestChainRule.rank_chains(&[]).is_empty());
+}
+
+/// A longest chain rule that gives ties to the first chain, so that `best_chain` picks the last of
+/// several equally long chains.
+#[cfg(test)]
+struct LastOfEqualLength;
+
+#[cfg(test)]
+impl ForkChoice for LastOfEqualLength {
+	fn first_chain_is_better(&self, chain_1: &[Header], chain_2: &[Header]) -> bool {
+		chain_1.len() >= chain_2.len()
+	}
+}
+
+#[cfg(test)]
+impl IncrementalForkChoice for LastOfEqualLength {
+	type Score = u64;
+
+	fn extend_score(&self, parent_score: u64, _: &Header) -> u64 {
+		parent_score + 1
+	}
+
+	fn first_score_is_better(&self, score_1: u64, score_2: u64) -> bool {
+		score_1 >= score_2
+	}
+}
+
+#[test]
+fn bc_5_rank_chains_breaks_ties_like_best_chain() {
+	let fork = crate::test_utils::Fork::new(1, 1, 1);
+	let (left, right) = (fork.left_chain(), fork.right_chain());
+	let candidates = [&fork.common[..], &left[..], &right[..]];
+
+	let ranked = LongestChainRule.rank_chains(&candidates);
+	assert_eq!(ranked[0].chain, LongestChainRule.best_chain(&candidates));
+	assert_eq!(ranked[0].chain, left.as_slice());
+	assert_eq!(ranked[1].chain, right.as_slice());
+
+	let ranked = LastOfEqualLength.rank_chains(&candidates);
+	assert_eq!(ranked[0].chain, LastOfEqualLength.best_chain(&candidates));
+	assert_eq!(ranked[0].chain, right.as_slice());
+	assert_eq!(ranked[1].chain, left.as_slice());
+	assert_eq!(ranked[2], ChainScore { chain: &fork.common[..], score: 2 });
+}
+
+/// A tree where the longest chain and the GHOST chain disagree.
+///
+/// ```text
+///      ┌─ a1 ─ a2 ─ a3
+/// g ───┤
+///      │      ┌─ b2
+///      └─ b1 ─┼─ c2
+///             └─ d2
+/// ```
+#[cfg(test)]
+fn build_bushy_tree() -> (ForkTree, Vec<Header>, Header) {
+	let g = Header::genesis();
+	let a1 = g.child(hash(&[1]), 1);
+	let a2 = a1.child(hash(&[2]), 3);
+	let a3 = a2.child(hash(&[3]), 6);
+	let b1 = g.child(hash(&[4]), 4);
+	let siblings: Vec<Header> =
+		(5..8).map(|extrinsic| b1.child(hash(&[extrinsic]), 4 + extrinsic)).collect();
+
+	let mut tree = ForkTree::new(g.clone());
+	for header in [a1.clone(), a2.clone(), a3.clone(), b1.clone()].into_iter().chain(siblings) {
+		tree.insert(header);
+	}
+	(tree, vec![g, a1, a2, a3], b1)
+}
+
+#[test]
+fn bc_5_ghost_prefers_heaviest_subtree() {
+	let (tree, longest_chain, b1) = build_bushy_tree();
+	assert_eq!(tree.subtree_size(hash(&b1)), 4);
+
+	let ghost_chain = GhostRule::best_chain(&tree);
+	assert_eq!(ghost_chain.len(), 3);
+	assert_eq!(ghost_chain[1], b1);
+	assert!(LongestChainRule.first_chain_is_better(&longest_chain, &ghost_chain));
+}
+
+#[test]
+fn bc_5_ghost_on_a_single_chain_is_the_chain() {
+	let g = Header::genesis();
+	let b1 = g.child(hash(&[1]), 1);
+	let b2 = b1.child(hash(&[2]), 3);
+	let mut tree = ForkTree::new(g.clone());
+	tree.insert(b1.clone());
+	tree.insert(b2.clone());
+
+	assert_eq!(GhostRule::best_chain(&tree), vec![g, b1, b2]);
+}
+
+#[test]
+fn bc_5_incremental_agrees_with_slices() {
+	let (longest_chain, heaviest_chain) = longest_and_heaviest_chains();
+	let longest_tip = hash(longest_chain.last().unwrap());
+	let heaviest_tip = hash(heaviest_chain.last().unwrap());
+
+	let mut cache = ScoreCache::new(LongestChainRule);
+	cache.insert_chain(&longest_chain);
+	cache.insert_chain(&heaviest_chain);
+	assert_eq!(
+		cache.first_tip_is_better(longest_tip, heaviest_tip),
+		Some(LongestChainRule.first_chain_is_better(&longest_chain, &heaviest_chain))
+	);
+
+	let rule = HeaviestChainRule::default();
+	let mut cache = ScoreCache::new(rule);
+	assert_eq!(
+		cache.insert_chain(&heaviest_chain),
+		Work(WeightedChainRule(rule).total_weight(&heaviest_chain))
+	);
+	cache.insert_chain(&longest_chain);
+	assert_eq!(
+		cache.first_tip_is_better(heaviest_tip, longest_tip),
+		Some(rule.first_chain_is_better(&heaviest_chain, &longest_chain))
+	);
+
+	let mut cache = ScoreCache::new(MostBlocksWithEvenHash);
+	let evens = cache.insert_chain(&heaviest_chain);
+	assert_eq!(
+		u128::from(evens),
+		WeightedChainRule(MostBlocksWithEvenHash).total_weight(&heaviest_chain)
+	);
+}
+
+#[test]
+fn bc_5_incremental_only_scores_new_suffix() {
+	let (prefix, longer, heavier) = create_fork_one_side_longer_other_side_heavier();
+	let mut cache = ScoreCache::new(LongestChainRule);
+
+	cache.insert_chain(&[prefix.clone(), longer].concat());
+	assert_eq!(cache.len(), 3);
+	let score = cache.insert_chain(&[prefix, heavier.clone()].concat());
+	assert_eq!(cache.len(), 4);
+	assert_eq!(score, 2);
+
+	assert_eq!(cache.first_tip_is_better(hash(&heavier[0]), 0), None);
+}
+
+#[test]
+fn bc_5_finalized_base_disqualifies_abandoned_forks() {
+	let (prefix, longer, heavier) = create_fork_one_side_longer_other_side_heavier();
+	let longest_chain = [prefix.clone(), longer.clone()].concat();
+	let heaviest_chain = [prefix, heavier.clone()].concat();
+
+	// The heavier fork's block was finalized, so the longer fork can never win.
+	let rule = FinalizedBase::new(hash(&heavier[0]), LongestChainRule);
+	assert!(LongestChainRule.first_chain_is_better(&longest_chain, &heaviest_chain));
+	assert!(!rule.first_chain_is_better(&longest_chain, &heaviest_chain));
+	assert!(rule.first_chain_is_better(&heaviest_chain, &longest_chain));
+	assert_eq!(rule.best_chain(&[&longest_chain, &heaviest_chain]), &heaviest_chain);
+
+	// A suffix that starts right after the finalized header also qualifies.
+	let rule = FinalizedBase::new(hash(&longer[0]), LongestChainRule);
+	assert!(rule.qualifies(&longer[1..]));
+}
+
+#[test]
+fn bc_5_finalized_base_delegates_among_descendants() {
+	let (longest_chain, heaviest_chain) = longest_and_heaviest_chains();
+
+	let rule = FinalizedBase::new(hash(&heaviest_chain[0]), HeaviestChainRule::default());
+	assert_eq!(
+		rule.best_qualifying_chain(&[&longest_chain, &heaviest_chain]),
+		Some(heaviest_chain.as_slice())
+	);
+
+	let rule = FinalizedBase::new(0xdead_beef, HeaviestChainRule::default());
+	assert_eq!(rule.best_qualifying_chain(&[&longest_chain, &heaviest_chain]), None);
+}
+
+#[test]
+fn bc_5_interleaved_breaks_ties_by_work() {
+	let g = Header::genesis();
+	let even_child = |below_threshold: bool| {
+		(0..)
+			.map(|i| g.child(hash(&[i]), i))
+			.find(|h| hash(h).is_multiple_of(2) && (hash(h) < THRESHOLD) == below_threshold)
+			.unwrap()
+	};
+	let light = [g.clone(), even_child(false)];
+	let heavy = [g.clone(), even_child(true)];
+	let rule = InterleavedRule::default();
+
+	// Both chains have the same number of even blocks, so work decides.
+	assert!(rule.first_chain_is_better(&heavy, &light));
+	assert!(!rule.first_chain_is_better(&light, &heavy));
+
+	// More even blocks beats more work.
+	let grandchild = (0..)
+		.map(|i| light[1].child(hash(&[i]), i))
+		.find(|h| hash(h).is_multiple_of(2) && hash(h) > THRESHOLD)
+		.unwrap();
+	let more_even = [light[0].clone(), light[1].clone(), grandchild];
+	assert!(rule.first_chain_is_better(&more_even, &heavy));
+	assert_eq!(rule.best_chain(&[&heavy, &light]), &heavy);
+}
+
+#[test]
+fn bc_5_validated_rejects_broken_links() {
+	let (prefix, longer, heavier) = create_fork_one_side_longer_other_side_heavier();
+	let valid_chain = [prefix.clone(), heavier].concat();
+	// Splicing the longer fork on twice makes it longer still, but the links no longer line up.
+	let spliced_chain = [prefix, longer.clone(), longer].concat();
+	let rule = Validated::new(LongestChainRule);
+
+	assert!(is_well_linked(&valid_chain));
+	assert!(!is_well_linked(&spliced_chain));
+	assert!(LongestChainRule.first_chain_is_better(&spliced_chain, &valid_chain));
+	assert!(!rule.first_chain_is_better(&spliced_chain, &valid_chain));
+	assert_eq!(rule.best_chain(&[&spliced_chain, &valid_chain]), &valid_chain);
+	assert_eq!(rule.best_valid_chain(&[&spliced_chain]), None);
+}
+
+#[test]
+fn bc_5_validated_rejects_wrong_heights() {
+	let g = Header::genesis();
+	let mut b1 = g.child(hash(&[1]), 1);
+	b1.height = 5;
+
+	assert!(!is_well_linked(&[g.clone(), b1]));
+	assert!(is_well_linked(&[g]));
+}
+
+#[test]
+fn bc_5_work_grows_as_hashes_shrink() {
+	assert_eq!(Work::of_hash(u64::MAX), Work(1));
+	assert_eq!(Work::of_hash(u64::MAX / 2), Work(2));
+	assert_eq!(Work::of_hash(0), Work(MAX_TARGET));
+	assert_eq!(Work(u128::MAX) + Work(1), Work(u128::MAX));
+
+	// A block mined to a hundredth of the hash space is worth about a hundred hashes.
+	let work = Work::of_hash(THRESHOLD);
+	assert!(work >= Work(99) && work <= Work(100));
+
+	let mut digest = [0xff; 32];
+	digest[..8].copy_from_slice(&THRESHOLD.to_be_bytes());
+	assert_eq!(Work::of_digest(&digest), work);
+	assert_eq!(Work::of_digest(&THRESHOLD), work);
+}
+
+#[cfg(test)]
+use crate::test_utils::authored_child;
+
+#[test]
+fn bc_5_primary_authors_take_turns() {
+	let rule = MostPrimaryAuthoredRule::new(vec![10, 20, 30]);
+	assert_eq!(rule.primary_author(1), Some(20));
+	assert_eq!(rule.primary_author(3), Some(10));
+	assert_eq!(MostPrimaryAuthoredRule::default().primary_author(1), None);
+
+	let g = Header::genesis();
+	assert!(!rule.is_primary(&g));
+	assert!(rule.is_primary(&authored_child(&g, 20)));
+	assert!(!rule.is_primary(&authored_child(&g, 30)));
+}
+
+#[test]
+fn bc_5_most_primary_authored_beats_longer_secondary_chain() {
+	let rule = MostPrimaryAuthoredRule::new(vec![10, 20, 30]);
+	let g = Header::genesis();
+
+	// Both primaries showed up.
+	let a1 = authored_child(&g, 20);
+	let a2 = authored_child(&a1, 30);
+	let primary_chain = vec![g.clone(), a1, a2];
+
+	// Only one primary showed up, and secondaries filled in around it.
+	let b1 = authored_child(&g, 10);
+	let b2 = authored_child(&b1, 30);
+	let b3 = authored_child(&b2, 20);
+	let mixed_chain = vec![g, b1, b2, b3];
+
+	assert!(rule.first_chain_is_better(&primary_chain, &mixed_chain));
+	assert!(!rule.first_chain_is_better(&mixed_chain, &primary_chain));
+	assert_eq!(rule.best_chain(&[&mixed_chain, &primary_chain]), primary_chain.as_slice());
+	assert!(rule.first_stream_is_better(primary_chain.iter(), mixed_chain.iter()));
+	assert!(LongestChainRule.first_chain_is_better(&mixed_chain, &primary_chain));
+
+	let mut cache = ScoreCache::new(rule);
+	cache.insert_chain(&primary_chain);
+	cache.insert_chain(&mixed_chain);
+	let primary_tip = hash(primary_chain.last().unwrap());
+	let mixed_tip = hash(mixed_chain.last().unwrap());
+	assert_eq!(cache.first_tip_is_better(primary_tip, mixed_tip), Some(true));
+}
+
+#[test]
+fn bc_5_equivocation_is_detected_between_headers() {
+	let g = Header::genesis();
+	let a1 = authored_child(&g, 10);
+	let mut b1 = a1.clone();
+	b1.extrinsics_root = hash(&[99]);
+
+	let equivocation = Equivocation { author: 10, height: 1 };
+	assert_eq!(Equivocation::between(&a1, &b1), Some(equivocation));
+	assert_eq!(Equivocation::between(&a1, &a1), None);
+	assert_eq!(Equivocation::between(&a1, &authored_child(&g, 20)), None);
+	assert_eq!(Equivocation::between(&a1, &authored_child(&a1, 10)), None);
+	assert!(equivocation.covers(&a1) && equivocation.covers(&b1));
+}
+
+#[test]
+fn bc_5_equivocation_penalizing_discounts_offending_chain() {
+	let g = Header::genesis();
+
+	// Author 10 equivocates at height 1, then builds a longer chain on one of their blocks.
+	let a1 = authored_child(&g, 10);
+	let a2 = authored_child(&a1, 20);
+	let a3 = authored_child(&a2, 30);
+	let offending_chain = vec![g.clone(), a1.clone(), a2, a3];
+	let mut conflicting = a1.clone();
+	conflicting.extrinsics_root = hash(&[99]);
+
+	let b1 = authored_child(&g, 20);
+	let b2 = authored_child(&b1, 30);
+	let honest_chain = vec![g, b1, b2];
+
+	let mut rule = EquivocationPenalizingRule::new([], 2);
+	assert!(rule.first_chain_is_better(&offending_chain, &honest_chain));
+
+	assert!(rule.report(Equivocation::between(&a1, &conflicting).unwrap()));
+	assert!(!rule.report(Equivocation { author: 10, height: 1 }));
+	assert!(rule.is_offending(&a1));
+	assert!(rule.first_chain_is_better(&honest_chain, &offending_chain));
+	assert!(!rule.first_chain_is_better(&offending_chain, &honest_chain));
+	assert!(rule.first_stream_is_better(honest_chain.iter(), offending_chain.iter()));
+	assert_eq!(rule.best_chain(&[&offending_chain, &honest_chain]), honest_chain.as_slice());
+
+	// Without a penalty the offending header is only ignored, so the chains tie.
+	let ignoring = EquivocationPenalizingRule::new([Equivocation { author: 10, height: 1 }], 0);
+	assert!(!ignoring.first_chain_is_better(&offending_chain, &honest_chain));
+	assert!(!ignoring.first_chain_is_better(&honest_chain, &offending_chain));
+
+	let mut cache = ScoreCache::new(rule);
+	cache.insert_chain(&offending_chain);
+	assert_eq!(cache.insert_chain(&honest_chain), 3);
+	assert_eq!(cache.score(hash(offending_chain.last().unwrap())), Some(1));
+}
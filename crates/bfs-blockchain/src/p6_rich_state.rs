@@ -0,0 +1,576 @@
+//! In this lesson we expand our simple notion of state, and show how the state is typically not
+//! stored in the header, Or indeed anywhere in the block at all.
+//!
+//! To facilitate this exercise, consider that we want our blockchain to store not only the sum of
+//! the extrinsics, but also the product. You can also imagine many other calculations the chain may
+//! want to track (min, max, median, mean, etc).
+//!
+//! As the state data gets large, it is no longer reasonable to store it in the blocks. But if the
+//! state isn't in the blocks, then how can we perform the state-related validation checks we
+//! previously performed? We use a state root to cryptographically link our heder to a complete
+//! state.
+//!
+//! This notion of state may sound familiar from our previous work on state machines. Indeed this
+//! naming coincidence foreshadows a key abstraction that we will make in a coming chapter.
+
+use crate::{
+	difficulty::Retargeting,
+	hash,
+	hasher::{FastHasher, HashOutput, Hasher},
+	merkle::{merkle_root, prove_inclusion, MerkleProof},
+	signing::{author_id, sign, verify, AuthorId, Signature, SigningKey},
+	time::Clock,
+	verification::check_child,
+};
+
+/// In this section we will use sum and product together to be our state. While this is only a
+/// doubling of state size remember that in real world blockchains, the state is often really really
+/// large.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "scale-codec", derive(parity_scale_codec::Encode, parity_scale_codec::Decode))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct State {
+	pub(crate) sum: u64,
+	pub(crate) product: u64,
+}
+
+impl State {
+	/// A state with the given sum and product.
+	pub fn new(sum: u64, product: u64) -> Self {
+		State { sum, product }
+	}
+
+	/// The state after executing the given extrinsics on this one.
+	///
+	/// Block bodies come from untrusted authors, so any extrinsics at all must execute. The sum and
+	/// product wrap around on overflow, so every body has a single well defined result, and debug
+	/// and release builds agree on it.
+	pub(crate) fn execute(&self, extrinsics: &[u64]) -> State {
+		let mut new_state = self.clone();
+		for extrinsic in extrinsics {
+			new_state.sum = new_state.sum.wrapping_add(*extrinsic);
+			new_state.product = new_state.product.wrapping_mul(*extrinsic);
+		}
+		new_state
+	}
+}
+
+/// The header no longer contains the state directly, but rather, it contains a hash of
+/// the complete state. This hash will allow block verifiers to cryptographically confirm
+/// that they got the same state as the author without having a complete copy of the
+/// author's state
+///
+/// The header is generic over the hash function that produces all of these commitments. It
+/// defaults to the same fast hasher as every earlier lesson, but a real chain would pick a
+/// cryptographic one from the [`hasher`](crate::hasher) module.
+///
+/// A header may also be signed by its author, as described in the [`signing`](crate::signing)
+/// module. Unsigned headers are still valid, so the earlier exercises work unchanged.
+///
+/// Each header records when it was authored. Timestamps may never go backwards along a chain, but
+/// several blocks may share one.
+///
+/// Each header also records the proof of work target it was mined against. Every child inherits
+/// its parent's target, which starts out so high that no work is needed at all. The
+/// [`difficulty`](crate::difficulty) module adjusts it to keep blocks coming at a steady pace.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "scale-codec", derive(parity_scale_codec::Encode, parity_scale_codec::Decode))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Header<H: Hasher = FastHasher> {
+	pub(crate) parent: H::Output,
+	pub(crate) height: u64,
+	pub(crate) extrinsics_root: H::Output,
+	/// Stores a cryptographic commitment, like a Merkle root or a hash to the complete
+	/// post state.
+	pub(crate) state_root: H::Output,
+	pub(crate) consensus_digest: u64,
+	/// When the block was authored, in milliseconds since the unix epoch.
+	pub(crate) timestamp: u64,
+	/// The leading 64 bits of the header's digest may be no greater than this.
+	pub(crate) target: u64,
+	/// The public key of whoever authored the block, or `None` if it is unsigned.
+	pub(crate) author: Option<AuthorId>,
+	/// The author's signature over the pre-seal header, which is this header without the
+	/// signature.
+	pub(crate) signature: Option<Signature>,
+}
+
+// Methods for creating and verifying headers.
+//
+// We already moved the execution logic to the block level in the last section.
+// So this code is similar to last time. One key addition we are making is that
+// genesis blocks can have an initial state, or "genesis state" other than the
+// default. So we need to commit the initial state root to the genesis header here.
+impl<H: Hasher> Header<H> {
+	/// Returns a new valid genesis header. Its timestamp is zero, the unix epoch.
+	fn genesis(genesis_state_root: H::Output) -> Self {
+		Header {
+			parent: H::Output::default(),
+			height: 0,
+			extrinsics_root: H::Output::default(),
+			state_root: genesis_state_root,
+			consensus_digest: 0,
+			timestamp: 0,
+			target: u64::MAX,
+			author: None,
+			signature: None,
+		}
+	}
+
+	/// Create and return a valid child header.
+	///
+	/// The state root is passed in similarly to how the complete state
+	/// was in the previous section. The child has the same timestamp as its parent.
+	fn child(&self, extrinsics_root: H::Output, state_root: H::Output) -> Self {
+		Header {
+			parent: H::digest(self),
+			height: self.height + 1,
+			extrinsics_root,
+			state_root,
+			consensus_digest: self.consensus_digest,
+			timestamp: self.timestamp,
+			target: self.target,
+			author: None,
+			signature: None,
+		}
+	}
+
+	/// Sign this header as the author whose key is given, replacing any previous signature.
+	fn signed(mut self, key: &SigningKey) -> Self {
+		self.author = Some(author_id(key));
+		self.signature = None;
+		self.signature = Some(sign(key, &self));
+		self
+	}
+
+	/// Whether the header's digest meets its own proof of work target.
+	pub(crate) fn meets_target(&self) -> bool {
+		H::digest(self).leading_u64() <= self.target
+	}
+
+	/// The public key of the header's author, or `None` if it is unsigned.
+	pub fn author(&self) -> Option<AuthorId> {
+		self.author
+	}
+
+	/// Whether the header's signature was made by its author over the pre-seal header. An unsigned
+	/// header has nothing to check, but an author without a signature, or a signature without an
+	/// author, is invalid.
+	pub fn verify_signature(&self) -> bool {
+		match (self.author, self.signature) {
+			(None, None) => true,
+			(Some(author), Some(signature)) => {
+				let pre_seal = Header { signature: None, ..self.clone() };
+				verify(&author, &signature, &pre_seal)
+			},
+			_ => false,
+		}
+	}
+
+	/// Verify a single child header. The child must keep this header's target.
+	///
+	/// The [`verification`](crate::verification) module checks the same rules, and explains which
+	/// one was broken. It also checks chains whose target is retargeted.
+	pub(crate) fn verify_child(&self, child: &Header<H>) -> bool {
+		check_child(&Retargeting::FIXED, std::slice::from_ref(self), child, 1).is_ok()
+	}
+
+	/// Verify that all the given headers form a valid chain from this header to the tip.
+	fn verify_sub_chain(&self, chain: &[Header<H>]) -> bool {
+		if chain.len() == 0 {
+			return true;
+		}
+
+		self.verify_child(&chain[0]) && self.verify_sub_chain(&chain[1..])
+	}
+}
+
+/// A complete Block is a header and the extrinsics.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "scale-codec", derive(parity_scale_codec::Encode, parity_scale_codec::Decode))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+	feature = "serde",
+	serde(bound(
+		serialize = "Header<H>: serde::Serialize",
+		deserialize = "Header<H>: serde::Deserialize<'de>"
+	))
+)]
+pub struct Block<H: Hasher = FastHasher> {
+	pub(crate) header: Header<H>,
+	pub(crate) body: Vec<u64>,
+}
+
+/// Methods for creating and verifying blocks.
+///
+/// We no longer have access to a state simply by having access to a block.
+/// Therefore, we need a pre-state explicitly passed for these block methods.
+/// In a real blockchain network, the client is typically responsible for
+/// storing some likely-to-be-needed states and have them ready for use in
+/// such operations.
+///
+/// These methods also differ from last time because you will need to
+/// calculate state roots to pass to the header-level methods.
+impl<H: Hasher> Block<H> {
+	/// Returns a new valid genesis block. By convention this block has no extrinsics.
+	pub fn genesis(genesis_state: &State) -> Self {
+		let header = Header::genesis(H::digest(genesis_state));
+
+		Block { header, body: vec![] }
+	}
+
+	/// Create and return a valid child block.
+	pub fn child(&self, pre_state: &State, extrinsics: Vec<u64>) -> Self {
+		let extrinsic_root = merkle_root::<H>(&extrinsics);
+		let new_state = pre_state.execute(&extrinsics);
+
+		let header = self.header.child(extrinsic_root, H::digest(&new_state));
+
+		Block { header, body: extrinsics }
+	}
+
+	/// Create and return a valid child block, timestamped with the clock's current time. If the
+	/// clock is behind this block, the child gets this block's timestamp instead, so that time
+	/// never goes backwards along the chain.
+	pub fn child_at(&self, clock: &impl Clock, pre_state: &State, extrinsics: Vec<u64>) -> Self {
+		let mut block = self.child(pre_state, extrinsics);
+		block.header.timestamp = clock.now().max(self.header.timestamp);
+		block
+	}
+
+	/// Create and return a valid child block, signed by the author whose key is given.
+	pub fn child_signed(&self, key: &SigningKey, pre_state: &State, extrinsics: Vec<u64>) -> Self {
+		let Block { header, body } = self.child(pre_state, extrinsics);
+		Block { header: header.signed(key), body }
+	}
+
+	/// Verify that all the given blocks form a valid chain from this block to the tip.
+	///
+	/// This time we need to validate the initial block itself by confirming that we
+	/// have been given a valid pre-state. And we still need to verify the headers,
+	/// execute all transactions, and check the final state.
+	pub fn verify_sub_chain(&self, pre_state: &State, chain: &[Block<H>]) -> bool {
+		let mut current_block = Block::genesis(pre_state);
+		for block in chain {
+			if !current_block.header.verify_child(&block.header) {
+				return false;
+			}
+
+			if merkle_root::<H>(&block.body) != block.header.extrinsics_root {
+				return false;
+			}
+
+			current_block = block.clone();
+		}
+		true
+	}
+
+	/// A proof that the extrinsic at the given index is in this block, which anyone with just the
+	/// header can check with [`verify_inclusion`](crate::merkle::verify_inclusion). Returns `None`
+	/// if the block has no extrinsic at that index.
+	pub fn prove_inclusion(&self, index: usize) -> Option<MerkleProof<H::Output>> {
+		prove_inclusion::<H>(&self.body, index)
+	}
+}
+
+/// Create an invalid child block of the given block. The returned block should have an
+/// incorrect state root. Although the child block is invalid, the header should be valid.
+///
+/// As we saw in the previous section, the logic for checking headers can no longer
+/// not include actual transaction execution, making it possible for invalid blocks
+/// to still contain valid headers. There are now two ways to accomplish this.
+/// 1. Block includes wrong extrinsics that do not match extrinsics root in header (from last time)
+/// 2. Block contains invalid state root that does not match the correct post state (new this time)
+///
+/// As before, you do not need the entire parent block to do this. You only need the header.
+/// You do, however, now need a pre-state as you have throughout much of this section.
+fn build_invalid_child_block_with_valid_header(parent: &Header, pre_state: &State) -> Block {
+	let extrinsics = vec![1, 2];
+	let wrong_extrinsics = vec![2, 3];
+	let extrinsic_root = merkle_root::<FastHasher>(&wrong_extrinsics);
+
+	let mut new_state = pre_state.clone();
+	let header = parent.child(extrinsic_root, hash(&new_state));
+
+	Block { header, body: extrinsics }
+}
+
+#[test]
+fn bc_6_genesis_header() {
+	let state = State { sum: 6, product: 9 };
+	let g: Header = Header::genesis(hash(&state));
+	assert_eq!(g.height, 0);
+	assert_eq!(g.parent, 0);
+	assert_eq!(g.extrinsics_root, 0);
+	assert_eq!(g.state_root, hash(&state));
+}
+
+#[test]
+fn bc_6_genesis_block() {
+	let state = State { sum: 6, product: 9 };
+	let gh: Header = Header::genesis(hash(&state));
+	let gb: Block = Block::genesis(&state);
+
+	assert_eq!(gb.header, gh);
+	assert!(gb.body.is_empty());
+}
+
+#[test]
+fn bc_6_child_block_empty() {
+	let state = State { sum: 6, product: 9 };
+	let b0: Block = Block::genesis(&state);
+	let b1 = b0.child(&state, vec![]);
+
+	assert_eq!(b1.header.height, 1);
+	assert_eq!(b1.header.parent, hash(&b0.header));
+	assert_eq!(b1, Block { header: b1.header.clone(), body: vec![] });
+}
+
+#[test]
+fn bc_6_child_block() {
+	let state = State { sum: 6, product: 9 };
+	let b0: Block = Block::genesis(&state);
+	let b1 = b0.child(&state, vec![1, 2, 3, 4, 5]);
+
+	assert_eq!(b1.header.height, 1);
+	assert_eq!(b1.header.parent, hash(&b0.header));
+	assert_eq!(b1, Block { header: b1.header.clone(), body: vec![1, 2, 3, 4, 5] });
+}
+
+#[test]
+fn bc_6_child_header() {
+	let state_0 = State { sum: 6, product: 9 };
+	let g: Header = Header::genesis(hash(&state_0));
+	let mut extrinsics = vec![1, 2, 3];
+	let mut state_1 = state_0;
+	for extrinsic in extrinsics.iter() {
+		state_1.sum += extrinsic;
+		state_1.product *= extrinsic;
+	}
+	let h1 = g.child(hash(&extrinsics), hash(&state_1));
+
+	assert_eq!(h1.height, 1);
+	assert_eq!(h1.parent, hash(&g));
+	assert_eq!(h1.extrinsics_root, hash(&extrinsics));
+	assert_eq!(h1.state_root, hash(&state_1));
+
+	extrinsics = vec![10, 20];
+	let mut state_2 = state_1;
+	for extrinsic in extrinsics.iter() {
+		state_2.sum += extrinsic;
+		state_2.product *= extrinsic;
+	}
+
+	let h2 = h1.child(hash(&extrinsics), hash(&state_2));
+
+	assert_eq!(h2.height, 2);
+	assert_eq!(h2.parent, hash(&h1));
+	assert_eq!(h2.extrinsics_root, hash(&extrinsics));
+	assert_eq!(h2.state_root, hash(&state_2));
+}
+
+#[test]
+fn bc_6_verify_three_blocks() {
+	let state_1 = State { sum: 6, product: 9 };
+	let g: Block = Block::genesis(&state_1);
+	let b1 = g.child(&state_1, vec![1]);
+	let state_2 = State { sum: 7, product: 9 };
+	let b2 = b1.child(&state_2, vec![2]);
+	let chain = vec![g.clone(), b1, b2];
+	assert!(g.verify_sub_chain(&state_1, &chain[1..]));
+}
+
+#[test]
+fn bc_6_invalid_header_doesnt_check() {
+	let state = State { sum: 6, product: 9 };
+	let g: Header = Header::genesis(hash(&state));
+	let h1 = Header {
+		parent: 0,
+		height: 100,
+		extrinsics_root: 0,
+		state_root: hash(&(State { sum: 0, product: 0 })),
+		consensus_digest: 0,
+		timestamp: 0,
+		target: u64::MAX,
+		author: None,
+		signature: None,
+	};
+
+	assert!(!g.verify_child(&h1));
+}
+
+#[test]
+fn bc_6_invalid_block_state_doesnt_check() {
+	let state = State { sum: 6, product: 9 };
+	let b0: Block = Block::genesis(&state);
+	let mut b1 = b0.child(&state, vec![1, 2, 3]);
+	b1.body = vec![];
+
+	assert!(!b0.verify_sub_chain(&state, &[b1]));
+}
+
+#[test]
+fn bc_6_block_with_invalid_header_doesnt_check() {
+	let state = State { sum: 6, product: 9 };
+	let b0: Block = Block::genesis(&state);
+	let mut b1 = b0.child(&state, vec![1, 2, 3]);
+	b1.header = Header::genesis(hash(&state));
+
+	assert!(!b0.verify_sub_chain(&state, &[b1]));
+}
+
+#[test]
+fn bc_6_student_invalid_block_really_is_invalid() {
+	let state = State { sum: 6, product: 9 };
+	let gb: Block = Block::genesis(&state);
+	let gh = &gb.header;
+
+	let b1 = build_invalid_child_block_with_valid_header(gh, &state);
+	let h1 = &b1.header;
+
+	// Make sure that the header is valid according to header rules.
+	assert!(gh.verify_child(h1));
+
+	// Make sure that the block is not valid when executed.
+	assert!(!gb.verify_sub_chain(&state, &[b1]));
+}
+
+#[test]
+fn bc_6_block_proves_its_extrinsics() {
+	use crate::merkle::verify_inclusion;
+
+	let state = State { sum: 6, product: 9 };
+	let b1: Block = Block::genesis(&state).child(&state, vec![7, 8, 9]);
+	assert_eq!(b1.header.extrinsics_root, merkle_root::<FastHasher>(&[7, 8, 9]));
+
+	let proof = b1.prove_inclusion(1).unwrap();
+	assert!(verify_inclusion::<FastHasher>(b1.header.extrinsics_root, &proof, 8));
+	assert!(!verify_inclusion::<FastHasher>(b1.header.extrinsics_root, &proof, 9));
+	assert_eq!(b1.prove_inclusion(3), None);
+}
+
+#[test]
+fn bc_6_chain_with_cryptographic_hashes() {
+	use crate::hasher::Blake2b256;
+
+	let state = State { sum: 6, product: 9 };
+	let g = Block::<Blake2b256>::genesis(&state);
+	let b1 = g.child(&state, vec![1, 2]);
+	let post_state = State { sum: 9, product: 18 };
+	let b2 = b1.child(&post_state, vec![3]);
+
+	assert_eq!(b1.header.parent, Blake2b256::digest(&g.header));
+	assert_eq!(b2.header.state_root, Blake2b256::digest(&State { sum: 12, product: 54 }));
+	assert!(g.verify_sub_chain(&state, &[b1.clone(), b2]));
+
+	let mut bad = b1;
+	bad.body = vec![2, 1];
+	assert!(!g.verify_sub_chain(&state, &[bad]));
+}
+
+#[test]
+fn bc_6_child_timestamps_come_from_the_clock() {
+	use crate::time::SimulatedClock;
+
+	let state = State { sum: 6, product: 9 };
+	let g: Block = Block::genesis(&state);
+	let clock = SimulatedClock::new(6_000);
+	let b1 = g.child_at(&clock, &state, vec![1]);
+	assert_eq!(g.header.timestamp, 0);
+	assert_eq!(b1.header.timestamp, 6_000);
+
+	// A clock that is behind the parent can't make time go backwards.
+	clock.set(5_000);
+	let b2 = b1.child_at(&clock, &State { sum: 7, product: 9 }, vec![2]);
+	assert_eq!(b2.header.timestamp, 6_000);
+
+	clock.advance(3_000);
+	let b3 = b2.child_at(&clock, &State { sum: 9, product: 18 }, vec![3]);
+	assert_eq!(b3.header.timestamp, 8_000);
+	assert!(g.verify_sub_chain(&state, &[b1, b2, b3]));
+}
+
+#[test]
+fn bc_6_timestamp_going_backwards_doesnt_check() {
+	use crate::time::FixedClock;
+
+	let state = State { sum: 6, product: 9 };
+	let g: Block = Block::genesis(&state);
+	let b1 = g.child_at(&FixedClock(6_000), &state, vec![1]);
+	let mut b2 = b1.child(&State { sum: 7, product: 9 }, vec![2]);
+	assert_eq!(b2.header.timestamp, 6_000);
+	assert!(g.verify_sub_chain(&state, &[b1.clone(), b2.clone()]));
+
+	b2.header.timestamp = 5_999;
+	assert!(!b1.header.verify_child(&b2.header));
+	assert!(!g.verify_sub_chain(&state, &[b1, b2]));
+}
+
+#[test]
+fn bc_6_signed_blocks() {
+	let alice = SigningKey::from_bytes(&[1; 32]);
+	let bob = SigningKey::from_bytes(&[2; 32]);
+	let state = State { sum: 6, product: 9 };
+	let g: Block = Block::genesis(&state);
+	let b1 = g.child_signed(&alice, &state, vec![1, 2]);
+	let b2 = b1.child_signed(&bob, &State { sum: 9, product: 18 }, vec![3]);
+
+	assert_eq!(g.header.author(), None);
+	assert_eq!(b1.header.author(), Some(author_id(&alice)));
+	assert_eq!(b2.header.author(), Some(author_id(&bob)));
+	assert!(b1.header.verify_signature());
+	assert!(g.verify_sub_chain(&state, &[b1.clone(), b2]));
+
+	// Signing doesn't change anything but the author and signature.
+	let unsigned = g.child(&state, vec![1, 2]);
+	assert_eq!(Header { author: None, signature: None, ..b1.header.clone() }, unsigned.header);
+}
+
+#[test]
+fn bc_6_tampered_signed_header_doesnt_check() {
+	let alice = SigningKey::from_bytes(&[1; 32]);
+	let bob = SigningKey::from_bytes(&[2; 32]);
+	let state = State { sum: 6, product: 9 };
+	let g: Block = Block::genesis(&state);
+	let b1 = g.child_signed(&alice, &state, vec![1, 2]);
+
+	let tampered = |change: fn(&mut Header)| {
+		let mut block = b1.clone();
+		change(&mut block.header);
+		assert!(!block.header.verify_signature());
+		assert!(!g.verify_sub_chain(&state, &[block]));
+	};
+	tampered(|header| header.consensus_digest += 1);
+	tampered(|header| header.signature = None);
+	tampered(|header| header.author = None);
+
+	// Claiming someone else authored it doesn't check either.
+	let mut forged = b1.clone();
+	forged.header.author = Some(author_id(&bob));
+	assert!(!forged.header.verify_signature());
+
+	// But the header can be re-signed by anyone with a key.
+	let resigned = Block { header: b1.header.signed(&bob), body: b1.body };
+	assert_eq!(resigned.header.author(), Some(author_id(&bob)));
+	assert!(g.verify_sub_chain(&state, &[resigned]));
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn bc_6_block_json_roundtrip() {
+	use crate::hasher::Sha256;
+
+	let state = State { sum: 6, product: 9 };
+	let b1: Block = Block::genesis(&state).child(&state, vec![1, 2]);
+	let json = serde_json::to_string(&b1).unwrap();
+	assert!(json.ends_with(r#""body":[1,2]}"#));
+	assert_eq!(serde_json::from_str::<Block>(&json).unwrap(), b1);
+
+	let signed = b1.child_signed(&SigningKey::from_bytes(&[1; 32]), &state, vec![3]);
+	let json = serde_json::to_string(&signed).unwrap();
+	assert_eq!(serde_json::from_str::<Block>(&json).unwrap(), signed);
+
+	let wide = Block::<Sha256>::genesis(&state).child(&state, vec![1, 2]);
+	let json = serde_json::to_string(&wide).unwrap();
+	assert_eq!(serde_json::from_str::<Block<Sha256>>(&json).unwrap(), wide);
+}
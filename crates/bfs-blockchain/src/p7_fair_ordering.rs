@@ -0,0 +1,135 @@
+//! So far the block author has been free to put the extrinsics in any order they like. That is a
+//! lot of power. An author who sees a large trade in the pool can put their own trade just before
+//! it and another just after it, profiting at the expense of the user. The profit available from
+//! choosing the order is often called MEV, or maximal extractable value.
+//!
+//! One way to take this power away is to make the order part of the validity rules. In this lesson
+//! the extrinsics must be sorted by their hash, salted with the hash of the parent block. The
+//! author cannot predict the order before the parent exists, and every node can check the order
+//! afterwards, so blocks in any other order are simply rejected.
+//!
+//! This is not a complete fix. The author still chooses _which_ extrinsics to include, and could
+//! grind through many candidate sets looking for a profitable order. There is a cost too:
+//! extrinsics that depend on each other, like two transactions from the same account, may no
+//! longer run in the order they were sent. That is why the rule is optional, and each chain decides
+//! whether to use it.
+
+use crate::{
+	hash,
+	p6_rich_state::{Block, State},
+	Hash,
+};
+
+/// How the extrinsics in a block must be ordered.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum OrderingRule {
+	/// The author may use any order. This is how the previous lessons worked.
+	#[default]
+	AuthorChooses,
+	/// The extrinsics must be sorted by their hash, salted with the parent block's hash.
+	SaltedHash,
+}
+
+impl OrderingRule {
+	/// The key that an extrinsic is sorted by under the salted hash rule.
+	pub fn sort_key(parent_hash: Hash, extrinsic: &u64) -> Hash {
+		hash(&(parent_hash, extrinsic))
+	}
+
+	/// Put the extrinsics for a child of the given parent in the order this rule requires.
+	pub fn arrange(&self, parent_hash: Hash, extrinsics: &mut [u64]) {
+		if *self == OrderingRule::SaltedHash {
+			extrinsics.sort_by_key(|extrinsic| Self::sort_key(parent_hash, extrinsic));
+		}
+	}
+
+	/// Whether the extrinsics for a child of the given parent are in the order this rule requires.
+	pub fn is_arranged(&self, parent_hash: Hash, extrinsics: &[u64]) -> bool {
+		match self {
+			OrderingRule::AuthorChooses => true,
+			OrderingRule::SaltedHash => extrinsics.windows(2).all(|pair| {
+				Self::sort_key(parent_hash, &pair[0]) <= Self::sort_key(parent_hash, &pair[1])
+			}),
+		}
+	}
+
+	/// Author a child of the given block, ordering the extrinsics as this rule requires.
+	pub fn child(&self, parent: &Block, pre_state: &State, mut extrinsics: Vec<u64>) -> Block {
+		self.arrange(hash(&parent.header), &mut extrinsics);
+		parent.child(pre_state, extrinsics)
+	}
+
+	/// Verify the chain just like [`Block::verify_sub_chain`], and additionally check that every
+	/// block's extrinsics are in the order this rule requires.
+	pub fn verify_sub_chain(&self, parent: &Block, pre_state: &State, chain: &[Block]) -> bool {
+		if !parent.verify_sub_chain(pre_state, chain) {
+			return false;
+		}
+
+		let mut parent_hash = hash(&parent.header);
+		for block in chain {
+			if !self.is_arranged(parent_hash, &block.body) {
+				return false;
+			}
+			parent_hash = hash(&block.header);
+		}
+		true
+	}
+}
+
+#[cfg(test)]
+fn genesis_state() -> State {
+	State { sum: 0, product: 1 }
+}
+
+#[test]
+fn bc_7_author_chooses_accepts_any_order() {
+	let rule = OrderingRule::AuthorChooses;
+	let mut extrinsics = vec![3, 1, 2];
+	rule.arrange(7, &mut extrinsics);
+
+	assert_eq!(extrinsics, vec![3, 1, 2]);
+	assert!(rule.is_arranged(7, &[3, 1, 2]));
+}
+
+#[test]
+fn bc_7_salted_hash_order_depends_on_parent() {
+	let rule = OrderingRule::SaltedHash;
+	let extrinsics: Vec<u64> = (0..10).collect();
+
+	let mut first = extrinsics.clone();
+	rule.arrange(1, &mut first);
+	let mut second = extrinsics;
+	rule.arrange(2, &mut second);
+
+	assert!(rule.is_arranged(1, &first));
+	assert!(rule.is_arranged(2, &second));
+	assert_ne!(first, second);
+}
+
+#[test]
+fn bc_7_fairly_ordered_chain_checks() {
+	let rule = OrderingRule::SaltedHash;
+	let g = Block::genesis(&genesis_state());
+	let b1 = rule.child(&g, &genesis_state(), (1..=5).collect());
+	let b1_state = State { sum: 15, product: 120 };
+	let b2 = rule.child(&b1, &b1_state, (6..=10).collect());
+
+	assert!(rule.verify_sub_chain(&g, &genesis_state(), &[b1, b2]));
+}
+
+#[test]
+fn bc_7_unfairly_ordered_block_is_rejected() {
+	let rule = OrderingRule::SaltedHash;
+	let g = Block::genesis(&genesis_state());
+	let fair = rule.child(&g, &genesis_state(), (1..=5).collect());
+
+	// The author puts the extrinsics in whatever order suits them best.
+	let mut unfair_body = fair.body.clone();
+	unfair_body.reverse();
+	let unfair = g.child(&genesis_state(), unfair_body);
+
+	let chain = [unfair];
+	assert!(OrderingRule::AuthorChooses.verify_sub_chain(&g, &genesis_state(), &chain));
+	assert!(!rule.verify_sub_chain(&g, &genesis_state(), &chain));
+}
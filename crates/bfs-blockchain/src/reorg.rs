@@ -0,0 +1,208 @@
+//! When a client's best chain switches from one fork to another, it has to undo the blocks that
+//! are only on the old fork and apply the blocks that are only on the new one. This is called a
+//! re-org. The two forks meet at their last common header, the fork point. Everything after it on
+//! the old fork is retracted, and everything after it on the new fork is enacted.
+
+use crate::{hash, p4_batched_extrinsics::Header, ForkTree, Hash, IncrementalForkChoice};
+use std::collections::HashSet;
+
+/// The last header that both chains contain, or `None` if they have nothing in common.
+///
+/// Like the fork choice rules, this does not assume the chains start at genesis. They only need to
+/// overlap somewhere.
+pub fn fork_point(chain_a: &[Header], chain_b: &[Header]) -> Option<Header> {
+	let in_a: HashSet<Hash> = chain_a.iter().map(hash).collect();
+	chain_b.iter().rev().find(|header| in_a.contains(&hash(*header))).cloned()
+}
+
+/// The headers to undo and redo when switching from one chain to another.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ReorgPath {
+	/// The headers only on the old chain, newest first, which is the order to undo them in.
+	pub retracted: Vec<Header>,
+	/// The headers only on the new chain, oldest first, which is the order to apply them in.
+	pub enacted: Vec<Header>,
+}
+
+impl ReorgPath {
+	/// The re-org from the `from` chain to the `to` chain, or `None` if they have no fork point.
+	pub fn between(from: &[Header], to: &[Header]) -> Option<Self> {
+		let fork_hash = hash(&fork_point(from, to)?);
+		let after_fork = |chain: &[Header]| {
+			let position = chain.iter().position(|header| hash(header) == fork_hash);
+			chain[position.expect("both chains contain the fork point") + 1..].to_vec()
+		};
+
+		let mut retracted = after_fork(from);
+		retracted.reverse();
+		Some(ReorgPath { retracted, enacted: after_fork(to) })
+	}
+
+	/// Whether switching chains needs no work at all, because they end at the same header.
+	pub fn is_empty(&self) -> bool {
+		self.retracted.is_empty() && self.enacted.is_empty()
+	}
+}
+
+impl ForkTree {
+	/// The re-org from the header with hash `from` to the header with hash `to`, or `None` if
+	/// either is not in the tree.
+	pub fn reorg_path(&self, from: Hash, to: Hash) -> Option<ReorgPath> {
+		ReorgPath::between(&self.chain_to(from)?, &self.chain_to(to)?)
+	}
+}
+
+/// A switch to a new best chain, reported by [`BestChain::update`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BestChainUpdate {
+	/// The headers to undo and redo to switch from the old best chain to the new one.
+	pub path: ReorgPath,
+	/// Whether the new best chain has a worse score than the old one had.
+	pub score_went_down: bool,
+}
+
+/// Follows the best chain of a fork tree as headers arrive, and reports every re-org.
+///
+/// For most rules the score of the best chain never goes down. Headers are only ever added to the
+/// tree, and adding a header to a chain can only make it better. Rules with negative weights, like
+/// [`EquivocationPenalizingRule`](crate::EquivocationPenalizingRule), are different. When a
+/// penalized header is added to the tip, the old best chain is no longer a leaf, so it is no longer
+/// a candidate, and the new best chain may be worse than it was. Such a switch is still a re-org,
+/// and is reported like any other, but with [`BestChainUpdate::score_went_down`] set.
+pub struct BestChain<R: IncrementalForkChoice> {
+	rule: R,
+	tip: Hash,
+	score: R::Score,
+}
+
+impl<R: IncrementalForkChoice> BestChain<R> {
+	/// Start following the best chain of the tree according to the rule.
+	pub fn new(tree: &ForkTree, rule: R) -> Self {
+		let tip = hash(tree.best_leaf(&rule));
+		let score = rule.chain_score(&tree.chain_to(tip).expect("the best leaf is in the tree"));
+		BestChain { rule, tip, score }
+	}
+
+	/// The hash of the tip of the best chain.
+	pub fn tip(&self) -> Hash {
+		self.tip
+	}
+
+	/// The score of the best chain.
+	pub fn score(&self) -> R::Score {
+		self.score
+	}
+
+	/// Choose the best chain again, after new headers were inserted into the tree. Returns the
+	/// switch to the new best chain, or `None` if the best chain did not change.
+	///
+	/// The tree must be the one this was created with, or one that grew from it.
+	pub fn update(&mut self, tree: &ForkTree) -> Option<BestChainUpdate> {
+		let new_tip = hash(tree.best_leaf(&self.rule));
+		if new_tip == self.tip {
+			return None;
+		}
+
+		let new_chain = tree.chain_to(new_tip).expect("the best leaf is in the tree");
+		let new_score = self.rule.chain_score(&new_chain);
+		let score_went_down = self.rule.first_score_is_better(self.score, new_score);
+
+		let path = tree.reorg_path(self.tip, new_tip).expect("the old tip is still in the tree");
+		self.tip = new_tip;
+		self.score = new_score;
+		Some(BestChainUpdate { path, score_went_down })
+	}
+}
+
+#[cfg(test)]
+use crate::test_utils::Fork;
+
+#[test]
+fn reorg_fork_point_of_sibling_chains() {
+	let fork = Fork::new(1, 1, 1);
+	let chain_a = fork.left_chain();
+	let chain_c = fork.right_chain();
+	assert_eq!(fork_point(&chain_a, &chain_c).as_ref(), Some(fork.fork_point()));
+
+	// Chains only need to overlap, not start at the same place.
+	assert_eq!(fork_point(&chain_a[1..], &chain_c).as_ref(), Some(fork.fork_point()));
+	assert_eq!(fork_point(&chain_a[2..], &chain_c), None);
+	assert_eq!(fork_point(&chain_a, &chain_a[..1]), Some(Header::genesis()));
+}
+
+#[test]
+fn reorg_path_retracts_and_enacts() {
+	let fork = Fork::new(1, 2, 1);
+	let path = ReorgPath::between(&fork.left_chain(), &fork.right_chain()).unwrap();
+	let mut retracted = fork.left.clone();
+	retracted.reverse();
+	assert_eq!(path, ReorgPath { retracted, enacted: fork.right.clone() });
+
+	let mut tree = ForkTree::new(Header::genesis());
+	for header in fork.left_chain().into_iter().chain(fork.right.clone()).skip(1) {
+		tree.insert(header);
+	}
+	let left_tip = hash(fork.left.last().unwrap());
+	let right_tip = hash(fork.right.last().unwrap());
+	assert_eq!(tree.reorg_path(left_tip, right_tip), Some(path));
+	assert!(tree.reorg_path(right_tip, right_tip).unwrap().is_empty());
+	assert_eq!(tree.reorg_path(right_tip, 0xdead_beef), None);
+}
+
+#[test]
+fn reorg_best_chain_follows_the_tree() {
+	use crate::p5_fork_choice::LongestChainRule;
+
+	let fork = Fork::new(1, 2, 3);
+	let mut tree = ForkTree::new(Header::genesis());
+	let mut best = BestChain::new(&tree, LongestChainRule);
+	assert_eq!(best.tip(), hash(&Header::genesis()));
+
+	for header in fork.left_chain().into_iter().skip(1) {
+		tree.insert(header);
+	}
+	let path = best.update(&tree).unwrap().path;
+	assert!(path.retracted.is_empty());
+	assert_eq!(path.enacted, fork.left_chain()[1..]);
+	assert_eq!(best.update(&tree), None);
+
+	// The right side takes over once it is longer.
+	tree.insert(fork.right[0].clone());
+	assert_eq!(best.update(&tree), None);
+	for header in &fork.right[1..] {
+		tree.insert(header.clone());
+	}
+	let update = best.update(&tree).unwrap();
+	assert!(!update.score_went_down);
+	let path = update.path;
+	assert_eq!(path.retracted.len(), 2);
+	assert_eq!(path.enacted, fork.right);
+	assert_eq!(best.tip(), hash(fork.right.last().unwrap()));
+	assert_eq!(best.score(), 5);
+}
+
+#[test]
+fn reorg_best_chain_reports_penalized_tips() {
+	use crate::{test_utils::authored_child, Equivocation, EquivocationPenalizingRule};
+
+	let fork = Fork::new(0, 2, 1);
+	let mut tree = ForkTree::new(Header::genesis());
+	for header in fork.left.iter().chain(&fork.right) {
+		tree.insert(header.clone());
+	}
+	let left_tip = fork.left.last().unwrap();
+	let offender = authored_child(left_tip, 7);
+	let equivocation = Equivocation { author: 7, height: offender.height };
+	let mut best = BestChain::new(&tree, EquivocationPenalizingRule::new([equivocation], 5));
+	assert_eq!(best.tip(), hash(left_tip));
+	assert_eq!(best.score(), 3);
+
+	// Extending the best chain with a penalized block leaves the old tip with a child, so the
+	// shorter right side is now the best chain, even though it is worse than the old one was.
+	tree.insert(offender);
+	let update = best.update(&tree).unwrap();
+	assert!(update.score_went_down);
+	assert_eq!(update.path.enacted, fork.right);
+	assert_eq!(update.path.retracted.len(), 2);
+	assert_eq!(best.score(), 2);
+}
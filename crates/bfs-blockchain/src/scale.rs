@@ -0,0 +1,100 @@
+//! The headers and blocks of every lesson in this chapter can be encoded with the SCALE codec from
+//! `parity-scale-codec`, the codec used throughout the Substrate ecosystem. This module is only
+//! available with the `scale-codec` feature.
+//!
+//! Unlike the [`encoding`](crate::encoding) module, SCALE has no version byte. The layout follows
+//! directly from the type, so tooling on the other end needs to know exactly which type it is
+//! decoding.
+//!
+//! # Wire layout
+//!
+//! Structs are their fields in declaration order, with nothing between them. Integers are
+//! little-endian, so every `u64` is 8 bytes, and `()` is no bytes at all. Digests from the
+//! [`hasher`](crate::hasher) module are either a `u64` or 32 raw bytes. An `Option` is a single
+//! byte, 0 for `None` or 1 for `Some`, followed by the value if there is one.
+//!
+//! A [`p4_batched_extrinsics::Header`] is 40 bytes.
+//!
+//! | bytes  | field              |
+//! |--------|--------------------|
+//! | 0..8   | `parent`           |
+//! | 8..16  | `height`           |
+//! | 16..24 | `extrinsics_root`  |
+//! | 24..32 | `state`            |
+//! | 32..40 | `consensus_digest` |
+//!
+//! The exported [`Header`](crate::Header) from part 6 starts with the same shape, with `state_root`
+//! in place of `state`, and ends with its `timestamp`, its `target`, and its optional `author` and
+//! `signature`. Unsigned, it is 58 bytes. Signed, the 32 byte author and 64 byte signature bring it
+//! to 154. With a 256 bit hasher,
+//! its `parent`, `extrinsics_root` and `state_root` are 32 bytes each, which adds another 72.
+//!
+//! A block is its header followed by its body. The body is a vector of `u64` extrinsics, encoded
+//! as its length in SCALE's compact integer format, then each extrinsic in order. Lengths below 64
+//! take a single byte, the length shifted left by two.
+
+pub use parity_scale_codec::{Decode, Encode};
+
+#[cfg(test)]
+use crate::{
+	hasher::Blake2b256, p4_batched_extrinsics, p6_rich_state::State, signing::SigningKey, Block,
+	Header,
+};
+
+#[cfg(test)]
+fn decode_all<T: Decode>(bytes: &[u8]) -> Result<T, parity_scale_codec::Error> {
+	parity_scale_codec::DecodeAll::decode_all(&mut &bytes[..])
+}
+
+#[test]
+fn scale_header_layout() {
+	let g = p4_batched_extrinsics::Header::genesis();
+	let b1 = g.child(7, 9);
+	let bytes = Encode::encode(&b1);
+
+	assert_eq!(bytes.len(), 40);
+	assert_eq!(bytes[..8], crate::hash(&g).to_le_bytes());
+	assert_eq!(bytes[8..16], 1u64.to_le_bytes());
+	assert_eq!(bytes[16..24], 7u64.to_le_bytes());
+	assert_eq!(bytes[24..32], 9u64.to_le_bytes());
+	assert_eq!(decode_all::<p4_batched_extrinsics::Header>(&bytes), Ok(b1));
+}
+
+#[test]
+fn scale_block_layout() {
+	let b1 = p4_batched_extrinsics::Block::genesis().child(vec![1, 2, 3]);
+	let bytes = Encode::encode(&b1);
+
+	assert_eq!(bytes.len(), 40 + 1 + 3 * 8);
+	assert_eq!(bytes[..40], Encode::encode(&b1.header));
+	assert_eq!(bytes[40], 3 << 2);
+	assert_eq!(bytes[41..49], 1u64.to_le_bytes());
+	assert_eq!(decode_all::<p4_batched_extrinsics::Block>(&bytes), Ok(b1));
+}
+
+#[test]
+fn scale_rich_state_roundtrip() {
+	let state = State { sum: 6, product: 9 };
+	assert_eq!(decode_all::<State>(&Encode::encode(&state)), Ok(state.clone()));
+
+	let b1 = Block::genesis(&state).child(&state, vec![4, 5]);
+	assert_eq!(Encode::encode(&b1.header).len(), 58);
+	assert_eq!(decode_all::<Block>(&Encode::encode(&b1)), Ok(b1.clone()));
+
+	let signed = b1.child_signed(&SigningKey::from_bytes(&[1; 32]), &state, vec![6]);
+	assert_eq!(Encode::encode(&signed.header).len(), 154);
+	assert_eq!(decode_all::<Block>(&Encode::encode(&signed)), Ok(signed));
+
+	let wide = Block::<Blake2b256>::genesis(&state).child(&state, vec![4, 5]);
+	assert_eq!(Encode::encode(&wide.header).len(), 130);
+	assert_eq!(decode_all::<Header<Blake2b256>>(&Encode::encode(&wide.header)), Ok(wide.header));
+}
+
+#[test]
+fn scale_rejects_truncated_and_padded_input() {
+	let header = p4_batched_extrinsics::Header::genesis();
+	let mut bytes = Encode::encode(&header);
+	assert!(decode_all::<p4_batched_extrinsics::Header>(&bytes[..39]).is_err());
+	bytes.push(0);
+	assert!(decode_all::<p4_batched_extrinsics::Header>(&bytes).is_err());
+}
@@ -0,0 +1,108 @@
+//! Signing block headers with ed25519.
+//!
+//! The Proof of Authority engines in the consensus chapter "sign" a header by writing an
+//! authority's name into its consensus digest. Anyone can write any name, so that signature proves
+//! nothing about who really authored the block. A real signature can only be made by whoever holds
+//! the author's secret key, but anyone can check it with the matching public key.
+//!
+//! A signed header names its author by their public key and carries their signature over the
+//! pre-seal header, which is the header with its author filled in but no signature yet. The
+//! signature can't sign itself, so it is the only part of the header left out.
+//!
+//! Like the hashers, signing covers the bytes that a value's `Hash` implementation writes.
+
+use ed25519_dalek::{Signer, VerifyingKey};
+use std::hash::{Hash, Hasher as StdHasher};
+
+pub use ed25519_dalek::SigningKey;
+
+/// An author's ed25519 public key.
+pub type AuthorId = [u8; 32];
+
+/// An ed25519 signature.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "scale-codec", derive(parity_scale_codec::Encode, parity_scale_codec::Decode))]
+pub struct Signature(pub [u8; 64]);
+
+/// The public key that signatures from this key are checked against.
+pub fn author_id(key: &SigningKey) -> AuthorId {
+	key.verifying_key().to_bytes()
+}
+
+/// Sign the value with the given key.
+pub fn sign<T: Hash + ?Sized>(key: &SigningKey, t: &T) -> Signature {
+	Signature(key.sign(&message(t)).to_bytes())
+}
+
+/// Whether the signature over the value was made by the given author.
+///
+/// This uses ed25519's strict verification, which also rejects the handful of alternative
+/// encodings that would otherwise let anyone turn one valid signature into another.
+pub fn verify<T: Hash + ?Sized>(author: &AuthorId, signature: &Signature, t: &T) -> bool {
+	let Ok(key) = VerifyingKey::from_bytes(author) else {
+		return false;
+	};
+	let signature = ed25519_dalek::Signature::from_bytes(&signature.0);
+	key.verify_strict(&message(t), &signature).is_ok()
+}
+
+/// The bytes that the value's `Hash` implementation writes.
+fn message<T: Hash + ?Sized>(t: &T) -> Vec<u8> {
+	let mut writer = MessageWriter(Vec::new());
+	t.hash(&mut writer);
+	writer.0
+}
+
+/// Collects everything a `Hash` implementation writes.
+struct MessageWriter(Vec<u8>);
+
+impl StdHasher for MessageWriter {
+	fn write(&mut self, bytes: &[u8]) {
+		self.0.extend_from_slice(bytes);
+	}
+
+	fn finish(&self) -> u64 {
+		unreachable!("the message is read straight out of the writer")
+	}
+}
+
+// serde only supports arrays of up to 32 items, so the signature goes through a slice.
+#[cfg(feature = "serde")]
+impl serde::Serialize for Signature {
+	fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+		self.0[..].serialize(serializer)
+	}
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Signature {
+	fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+		let bytes = Vec::<u8>::deserialize(deserializer)?;
+		let bytes = bytes
+			.try_into()
+			.map_err(|bytes: Vec<u8>| serde::de::Error::invalid_length(bytes.len(), &"64 bytes"))?;
+		Ok(Signature(bytes))
+	}
+}
+
+#[test]
+fn signing_roundtrip() {
+	let alice = SigningKey::from_bytes(&[1; 32]);
+	let signature = sign(&alice, &(1u64, 2u64));
+	assert!(verify(&author_id(&alice), &signature, &(1u64, 2u64)));
+}
+
+#[test]
+fn signing_rejects_wrong_message_author_and_signature() {
+	let alice = SigningKey::from_bytes(&[1; 32]);
+	let bob = SigningKey::from_bytes(&[2; 32]);
+	let signature = sign(&alice, &7u64);
+
+	assert!(!verify(&author_id(&alice), &signature, &8u64));
+	assert!(!verify(&author_id(&bob), &signature, &7u64));
+	assert!(!verify(&author_id(&alice), &sign(&bob, &7u64), &7u64));
+
+	let mut corrupted = signature;
+	corrupted.0[0] ^= 1;
+	assert!(!verify(&author_id(&alice), &corrupted, &7u64));
+}
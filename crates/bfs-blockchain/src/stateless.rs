@@ -0,0 +1,110 @@
+//! Validating blocks without holding the state.
+//!
+//! A full node keeps the whole state so it can execute every block. A stateless verifier keeps
+//! only headers. For it to check a block's execution anyway, the author ships each block together
+//! with a witness: the parts of the pre-state that the block reads, along with proof that they
+//! really are what the parent's state root commits to. The verifier checks the witness against the
+//! parent, executes the block on it, and compares the result with the block's state root.
+//!
+//! In a real chain the witness holds a state trie proof for every key the block accesses, and its
+//! size is one of the main costs of going stateless. Every extrinsic in our chain touches the
+//! whole state, which is only two numbers, so the witness is simply the complete pre-state.
+
+use crate::{
+	hasher::{FastHasher, Hasher},
+	merkle::merkle_root,
+	p6_rich_state::State,
+	Block, Header,
+};
+
+/// A block bundled with the witness that a stateless verifier needs to execute it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct WitnessedBlock<H: Hasher = FastHasher> {
+	/// The block itself.
+	pub block: Block<H>,
+	/// The state that the block is executed on.
+	pub witness: State,
+}
+
+impl<H: Hasher> WitnessedBlock<H> {
+	/// Bundle a block with the pre-state it was executed on, as its author does.
+	pub fn new(block: Block<H>, pre_state: &State) -> Self {
+		WitnessedBlock { block, witness: pre_state.clone() }
+	}
+
+	/// Whether the block is a valid child of the given parent, with the witness matching the
+	/// parent's state root, and executing on it giving the block's state root.
+	pub fn verify(&self, parent: &Header<H>) -> bool {
+		let header = &self.block.header;
+		re_execute(parent, &self.witness, &self.block.body, header) == Some(header.state_root)
+	}
+
+	/// The number of bytes the witness adds to the block, when it is sent with the
+	/// [`encoding`](crate::encoding) module.
+	pub fn witness_size(&self) -> usize {
+		self.witness.encode().len()
+	}
+}
+
+/// Whether all the given blocks form a valid chain from the parent to the tip, checking the
+/// execution of every one of them.
+pub fn verify_sub_chain<H: Hasher>(parent: &Header<H>, chain: &[WitnessedBlock<H>]) -> bool {
+	let mut parent = parent;
+	for witnessed in chain {
+		if !witnessed.verify(parent) {
+			return false;
+		}
+		parent = &witnessed.block.header;
+	}
+	true
+}
+
+/// The state root that executing the extrinsics on the pre-state gives. Returns `None` unless the
+/// header is a valid child of the parent, the parent commits to the pre-state, and the header
+/// commits to the extrinsics.
+pub(crate) fn re_execute<H: Hasher>(
+	parent: &Header<H>,
+	pre_state: &State,
+	extrinsics: &[u64],
+	header: &Header<H>,
+) -> Option<H::Output> {
+	let commitments_match = parent.verify_child(header) &&
+		H::digest(pre_state) == parent.state_root &&
+		merkle_root::<H>(extrinsics) == header.extrinsics_root;
+	commitments_match.then(|| H::digest(&pre_state.execute(extrinsics)))
+}
+
+#[test]
+fn stateless_verifies_a_chain_from_witnesses() {
+	let state_0 = State { sum: 6, product: 9 };
+	let state_1 = state_0.execute(&[1, 2]);
+	let g: Block = Block::genesis(&state_0);
+	let b1 = g.child(&state_0, vec![1, 2]);
+	let b2 = b1.child(&state_1, vec![3]);
+
+	let chain = [WitnessedBlock::new(b1, &state_0), WitnessedBlock::new(b2, &state_1)];
+	assert!(verify_sub_chain(&g.header, &chain));
+	assert_eq!(chain[0].witness_size(), 17);
+}
+
+#[test]
+fn stateless_rejects_bad_witnesses_and_execution() {
+	let state = State { sum: 6, product: 9 };
+	let g: Block = Block::genesis(&state);
+	let b1 = WitnessedBlock::new(g.child(&state, vec![1, 2]), &state);
+	assert!(b1.verify(&g.header));
+
+	// A witness that the parent doesn't commit to.
+	let wrong_witness = WitnessedBlock { witness: State { sum: 0, product: 0 }, ..b1.clone() };
+	assert!(!wrong_witness.verify(&g.header));
+
+	// A body that the header doesn't commit to.
+	let mut wrong_body = b1.clone();
+	wrong_body.block.body = vec![2, 1];
+	assert!(!wrong_body.verify(&g.header));
+
+	// A state root that executing the block doesn't give.
+	let mut wrong_root = b1;
+	wrong_root.block.header.state_root = crate::hash(&state);
+	assert!(!wrong_root.verify(&g.header));
+}
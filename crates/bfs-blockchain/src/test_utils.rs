@@ -0,0 +1,120 @@
+//! Fixtures shared by the tests in this chapter.
+//!
+//! The lessons themselves keep their setup code inline, so that students can read each test from
+//! top to bottom. The modules built on top of the lessons, like the fork tree and the re-org
+//! helpers, use these instead of building the same few chains over and over.
+
+use crate::{hash, p4_batched_extrinsics::Header};
+
+/// Assert that the rule picks the expected chain out of the candidates.
+///
+/// ```ignore
+/// assert_best_chain!(LongestChainRule, [short, long], long);
+/// ```
+macro_rules! assert_best_chain {
+	($rule:expr, [$($candidate:expr),+ $(,)?], $expected:expr $(,)?) => {
+		assert_eq!(
+			$crate::ForkChoice::best_chain(&$rule, &[$(&$candidate[..]),+]),
+			&$expected[..],
+		)
+	};
+}
+
+/// The given number of headers built one on top of the other after the parent, not including the
+/// parent itself. Each header commits to a different extrinsics root derived from the seed, so
+/// chains built from the same parent with different seeds are distinct forks.
+pub fn extend(parent: &Header, len: usize, seed: u64) -> Vec<Header> {
+	let mut chain: Vec<Header> = Vec::with_capacity(len);
+	for i in 0..len as u64 {
+		let tip = chain.last().unwrap_or(parent);
+		chain.push(tip.child(hash(&(seed, i)), seed.wrapping_add(i)));
+	}
+	chain
+}
+
+/// A chain starting at genesis with the given number of headers after it.
+pub fn linear_chain(len: usize) -> Vec<Header> {
+	let genesis = Header::genesis();
+	let mut chain = vec![genesis.clone()];
+	chain.extend(extend(&genesis, len, 0));
+	chain
+}
+
+/// Two chains from genesis that share a common prefix and then go their separate ways.
+///
+/// ```text
+///                          ┌─ left
+/// genesis ─ common prefix ─┤
+///                          └─ right
+/// ```
+pub struct Fork {
+	/// Genesis and the headers both sides share. Never empty.
+	pub common: Vec<Header>,
+	/// The headers only on the left side
+	pub left: Vec<Header>,
+	/// The headers only on the right side
+	pub right: Vec<Header>,
+}
+
+impl Fork {
+	/// A fork with `common` headers after genesis before the sides split.
+	pub fn new(common: usize, left: usize, right: usize) -> Self {
+		let common = linear_chain(common);
+		let fork_point = common.last().expect("the common prefix contains genesis");
+		let left = extend(fork_point, left, 1);
+		let right = extend(fork_point, right, 2);
+		Fork { common, left, right }
+	}
+
+	/// The complete chain from genesis to the tip of the left side.
+	pub fn left_chain(&self) -> Vec<Header> {
+		[self.common.clone(), self.left.clone()].concat()
+	}
+
+	/// The complete chain from genesis to the tip of the right side.
+	pub fn right_chain(&self) -> Vec<Header> {
+		[self.common.clone(), self.right.clone()].concat()
+	}
+
+	/// The header both sides were built on.
+	pub fn fork_point(&self) -> &Header {
+		self.common.last().expect("the common prefix contains genesis")
+	}
+}
+
+/// The two chains of the fork that part 5 asks students to build, both complete from its first
+/// header. They share a prefix, after which the first chain has more blocks and the second has more
+/// work.
+pub fn longest_and_heaviest_chains() -> (Vec<Header>, Vec<Header>) {
+	let (prefix, longer, heavier) =
+		crate::p5_fork_choice::create_fork_one_side_longer_other_side_heavier();
+	([prefix.clone(), longer].concat(), [prefix, heavier].concat())
+}
+
+/// A child of the given header, authored by the given authority. The author is recorded in the
+/// consensus digest.
+pub fn authored_child(parent: &Header, author: u64) -> Header {
+	let mut child = parent.child(hash(&[author]), 0);
+	child.consensus_digest = author;
+	child
+}
+
+#[test]
+fn test_utils_fork_shapes() {
+	let chain = linear_chain(3);
+	assert_eq!(chain.len(), 4);
+	assert!(crate::is_well_linked(&chain));
+
+	let fork = Fork::new(1, 2, 3);
+	assert_eq!(fork.left_chain().len(), 4);
+	assert_eq!(fork.right_chain().len(), 5);
+	assert_ne!(fork.left[0], fork.right[0]);
+	assert!(crate::is_well_linked(&fork.left_chain()));
+	assert!(crate::is_well_linked(&fork.right_chain()));
+
+	assert_best_chain!(
+		crate::p5_fork_choice::LongestChainRule,
+		[fork.left_chain(), fork.right_chain()],
+		fork.right_chain()
+	);
+}
@@ -0,0 +1,244 @@
+//! Chain verification that explains what is wrong.
+//!
+//! The lessons' `verify_sub_chain` methods only say whether a chain is valid. That is all a node
+//! needs to decide whether to import it, but when a chain is rejected, whoever built it wants to
+//! know which block broke which rule. The functions here check the same rules for the exported
+//! [`Header`] and [`Block`], and report the first rule broken along with the block that broke it.
+//!
+//! Unlike `verify_sub_chain`, they also check every target against the chain's
+//! [`Retargeting`] algorithm. Chains whose target never changes use [`Retargeting::FIXED`].
+
+use crate::{
+	difficulty::Retargeting,
+	hasher::Hasher,
+	merkle::merkle_root,
+	p6_rich_state::{Block, Header, State},
+};
+
+/// The first reason that a chain is invalid. Every variant holds the index in the chain of the
+/// header or block that is invalid.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ChainError {
+	/// The parent hash is not the hash of the previous header.
+	WrongParentHash { index: usize },
+	/// The height is not one more than the previous header's.
+	WrongHeight { index: usize },
+	/// The timestamp is earlier than the previous header's.
+	TimestampWentBackwards { index: usize },
+	/// The target is not the one that retargeting gives, or can't be checked because the chain
+	/// doesn't include the period that decided it.
+	WrongTarget { index: usize },
+	/// The header's digest does not meet its proof of work target.
+	FailedPowThreshold { index: usize },
+	/// The header has a signature that its author did not make, or only half of a signature.
+	BadSignature { index: usize },
+	/// The extrinsics root does not commit to the block's extrinsics.
+	BadExtrinsicsRoot { index: usize },
+	/// The state root is not the hash of the state after executing the block.
+	BadStateRoot { index: usize },
+}
+
+impl ChainError {
+	/// The index in the chain of the header or block that is invalid.
+	pub fn index(&self) -> usize {
+		match *self {
+			ChainError::WrongParentHash { index } |
+			ChainError::WrongHeight { index } |
+			ChainError::TimestampWentBackwards { index } |
+			ChainError::WrongTarget { index } |
+			ChainError::FailedPowThreshold { index } |
+			ChainError::BadSignature { index } |
+			ChainError::BadExtrinsicsRoot { index } |
+			ChainError::BadStateRoot { index } => index,
+		}
+	}
+}
+
+/// Check that the child header at the given index in a chain follows on from its ancestors, the
+/// last of which is its parent.
+pub(crate) fn check_child<H: Hasher>(
+	retargeting: &Retargeting,
+	ancestors: &[Header<H>],
+	child: &Header<H>,
+	index: usize,
+) -> Result<(), ChainError> {
+	let parent = ancestors.last().expect("every child has a parent");
+	if child.parent != H::digest(parent) {
+		return Err(ChainError::WrongParentHash { index });
+	}
+	if child.height != parent.height + 1 {
+		return Err(ChainError::WrongHeight { index });
+	}
+	if child.timestamp < parent.timestamp {
+		return Err(ChainError::TimestampWentBackwards { index });
+	}
+	if retargeting.next_target(ancestors) != Some(child.target) {
+		return Err(ChainError::WrongTarget { index });
+	}
+	if !child.meets_target() {
+		return Err(ChainError::FailedPowThreshold { index });
+	}
+	if !child.verify_signature() {
+		return Err(ChainError::BadSignature { index });
+	}
+	Ok(())
+}
+
+/// Check that the headers form a valid chain from the first one to the last, with targets set by
+/// the given retargeting algorithm. The first header is trusted, just like the header that
+/// `verify_sub_chain` is called on.
+///
+/// The chain must start early enough to include the period before each retarget, which is always
+/// the case when it starts at genesis.
+pub fn verify_chain<H: Hasher>(
+	retargeting: &Retargeting,
+	chain: &[Header<H>],
+) -> Result<(), ChainError> {
+	for index in 1..chain.len() {
+		check_child(retargeting, &chain[..index], &chain[index], index)?;
+	}
+	Ok(())
+}
+
+/// Check that the blocks form a valid chain from the first one to the last, executing every block
+/// after the first. The given state is the post state of the first block, and must match its state
+/// root. Targets are checked just like [`verify_chain`] checks them.
+pub fn verify_block_chain<H: Hasher>(
+	retargeting: &Retargeting,
+	state: &State,
+	chain: &[Block<H>],
+) -> Result<(), ChainError> {
+	let Some(first) = chain.first() else {
+		return Ok(());
+	};
+	if H::digest(state) != first.header.state_root {
+		return Err(ChainError::BadStateRoot { index: 0 });
+	}
+
+	let headers: Vec<Header<H>> = chain.iter().map(|block| block.header.clone()).collect();
+	let mut state = state.clone();
+	for (index, block) in chain.iter().enumerate().skip(1) {
+		check_child(retargeting, &headers[..index], &block.header, index)?;
+		if merkle_root::<H>(&block.body) != block.header.extrinsics_root {
+			return Err(ChainError::BadExtrinsicsRoot { index });
+		}
+		state = state.execute(&block.body);
+		if H::digest(&state) != block.header.state_root {
+			return Err(ChainError::BadStateRoot { index });
+		}
+	}
+	Ok(())
+}
+
+#[cfg(test)]
+fn valid_chain(state: &State) -> Vec<Block> {
+	let g = Block::genesis(state);
+	let b1 = g.child(state, vec![1, 2]);
+	let b2 = b1.child(&state.execute(&[1, 2]), vec![3]);
+	vec![g, b1, b2]
+}
+
+#[test]
+fn verification_accepts_valid_chains() {
+	let state = State { sum: 6, product: 9 };
+	let chain = valid_chain(&state);
+	let headers: Vec<Header> = chain.iter().map(|block| block.header.clone()).collect();
+
+	assert_eq!(verify_chain(&Retargeting::FIXED, &headers), Ok(()));
+	assert_eq!(verify_chain::<crate::hasher::FastHasher>(&Retargeting::FIXED, &[]), Ok(()));
+	assert_eq!(verify_block_chain(&Retargeting::FIXED, &state, &chain), Ok(()));
+	assert_eq!(
+		verify_block_chain(&Retargeting::FIXED, &state.execute(&[1, 2]), &chain[1..]),
+		Ok(())
+	);
+}
+
+#[test]
+fn verification_reports_broken_headers() {
+	let state = State { sum: 6, product: 9 };
+	let headers: Vec<Header> = valid_chain(&state).into_iter().map(|block| block.header).collect();
+	let broken = |index: usize, change: fn(&mut Header)| {
+		let mut headers = headers.clone();
+		change(&mut headers[index]);
+		verify_chain(&Retargeting::FIXED, &headers)
+	};
+
+	assert_eq!(broken(2, |h| h.parent = 0), Err(ChainError::WrongParentHash { index: 2 }));
+	assert_eq!(broken(2, |h| h.height = 5), Err(ChainError::WrongHeight { index: 2 }));
+	assert_eq!(broken(2, |h| h.target = 0), Err(ChainError::WrongTarget { index: 2 }));
+	assert_eq!(broken(2, |h| h.author = Some([7; 32])), Err(ChainError::BadSignature { index: 2 }));
+
+	// Changing a header also breaks its child's parent hash, but only the first error is reported.
+	assert_eq!(broken(1, |h| h.height = 5), Err(ChainError::WrongHeight { index: 1 }));
+	assert_eq!(ChainError::BadSignature { index: 2 }.index(), 2);
+}
+
+#[test]
+fn verification_reports_timestamps_going_backwards() {
+	use crate::time::FixedClock;
+
+	let state = State { sum: 6, product: 9 };
+	let g: Block = Block::genesis(&state);
+	let b1 = g.child_at(&FixedClock(1_000), &state, vec![]);
+	let mut b2 = b1.child(&state, vec![]);
+	b2.header.timestamp = 999;
+
+	let headers = [g.header, b1.header, b2.header];
+	assert_eq!(
+		verify_chain(&Retargeting::FIXED, &headers),
+		Err(ChainError::TimestampWentBackwards { index: 2 })
+	);
+}
+
+#[test]
+fn verification_reports_broken_blocks() {
+	let state = State { sum: 6, product: 9 };
+	let chain = valid_chain(&state);
+
+	let mut wrong_body = chain.clone();
+	wrong_body[2].body = vec![4];
+	assert_eq!(
+		verify_block_chain(&Retargeting::FIXED, &state, &wrong_body),
+		Err(ChainError::BadExtrinsicsRoot { index: 2 })
+	);
+
+	let mut wrong_root = chain.clone();
+	wrong_root[2].header.state_root = crate::hash(&state);
+	assert_eq!(
+		verify_block_chain(&Retargeting::FIXED, &state, &wrong_root),
+		Err(ChainError::BadStateRoot { index: 2 })
+	);
+
+	let wrong_state = State { sum: 0, product: 0 };
+	assert_eq!(
+		verify_block_chain(&Retargeting::FIXED, &wrong_state, &chain),
+		Err(ChainError::BadStateRoot { index: 0 })
+	);
+}
+
+#[test]
+fn verification_checks_targets_against_retargeting() {
+	use crate::difficulty::mine_chain;
+
+	let rule = Retargeting { interval: 4, block_time: 1_000 };
+	let chain = mine_chain(&rule, &[500; 5]);
+	assert_eq!(verify_chain(&rule, &chain), Ok(()));
+	assert_eq!(
+		verify_chain(&Retargeting::FIXED, &chain),
+		Err(ChainError::WrongTarget { index: 4 })
+	);
+
+	// A header that raises its own target meets it easily, but isn't allowed to.
+	let mut raised = chain.clone();
+	raised[5].target = u64::MAX;
+	assert!(raised[5].meets_target());
+	assert_eq!(verify_chain(&rule, &raised), Err(ChainError::WrongTarget { index: 5 }));
+	assert!(!raised[4].verify_child(&raised[5]));
+
+	// A header with the right target that wasn't mined against it.
+	let mut unmined = chain;
+	while unmined[5].meets_target() {
+		unmined[5].consensus_digest += 1;
+	}
+	assert_eq!(verify_chain(&rule, &unmined), Err(ChainError::FailedPowThreshold { index: 5 }));
+}
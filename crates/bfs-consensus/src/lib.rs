@@ -3,6 +3,33 @@
 //! previous module, then look at PoA, and other consensus engines all implementing the same simple
 //! interface.
 
+/// Marks the body of a lesson exercise. Students see a `todo!()` with the exercise name. When the
+/// `reference-solutions` feature is enabled, the given expression (which typically calls into the
+/// lesson's `solution` module) is used instead so the complete course can be run end-to-end.
+///
+/// The framework chapter uses this macro as well. Its own `reference-solutions` feature turns on
+/// this one.
+#[cfg(not(feature = "reference-solutions"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! exercise {
+	($name:literal, $solution:expr $(,)?) => {
+		todo!($name)
+	};
+}
+
+/// Marks the body of a lesson exercise. Students see a `todo!()` with the exercise name. When the
+/// `reference-solutions` feature is enabled, the given expression (which typically calls into the
+/// lesson's `solution` module) is used instead so the complete course can be run end-to-end.
+#[cfg(feature = "reference-solutions")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! exercise {
+	($name:literal, $solution:expr $(,)?) => {
+		$solution
+	};
+}
+
 mod p1_pow;
 mod p2_dictator;
 mod p3_poa; // exercise: dictator is a special case of poa. Create dictator in terms of PoA.
@@ -10,6 +37,9 @@ mod p4_even_only;
 mod p5_interleave;
 mod p6_forking;
 
+#[cfg(feature = "reference-solutions")]
+mod solution;
+
 type Hash = u64;
 
 /// A Block Header similar to prior chapters of this tutorial.
@@ -20,13 +50,16 @@ type Hash = u64;
 /// Consensus engines do not know or care about the blockchain's state machine,
 /// which means they can operate entirely at the header level. They never need to touch
 /// the complete blocks.
+///
+/// The fields are public so that the framework in the next chapter can build headers for any
+/// consensus engine.
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub struct Header<Digest> {
-	parent: Hash,
-	height: u64,
-	state_root: Hash,
-	extrinsics_root: Hash,
-	consensus_digest: Digest,
+	pub parent: Hash,
+	pub height: u64,
+	pub state_root: Hash,
+	pub extrinsics_root: Hash,
+	pub consensus_digest: Digest,
 }
 /// A Consensus Engine. Responsible for Sealing blocks and verifying their seals
 ///
@@ -74,7 +107,7 @@ pub trait Consensus {
 		parent_digest: &Self::Digest,
 		chain: &[Header<Self::Digest>],
 	) -> bool {
-		todo!("Exercise 1")
+		exercise!("Exercise 1", solution::verify_sub_chain(self, parent_digest, chain))
 	}
 
 	/// A human-readable name for this engine. This may be used in user-facing
@@ -92,12 +125,12 @@ impl Consensus for () {
 
 	/// All blocks are considered valid
 	fn validate(&self, _: &Self::Digest, _: &Header<Self::Digest>) -> bool {
-		todo!("Exercise 2")
+		exercise!("Exercise 2", solution::validate_trivial())
 	}
 
 	/// No real sealing is required. The partial header has all the necessary information
 	fn seal(&self, _: &Self::Digest, partial_header: Header<()>) -> Option<Header<Self::Digest>> {
-		todo!("Exercise 3")
+		exercise!("Exercise 3", solution::seal_trivial(partial_header))
 	}
 }
 
@@ -0,0 +1,85 @@
+//! Proof of Work provides security to the blockchain by requiring block authors
+//! to expend a real-world scarce resource, namely energy, in order to author a valid block.
+//!
+//! This is the same logic we implemented previously. Here we re-implement it in the
+//! generic consensus framework that we will use throughout the rest of the chapter.
+
+use super::{Consensus, Header};
+
+/// A Proof of Work consensus engine. This is the same consensus logic that we
+/// implemented in the previous chapter. Here we simply re-implement it in the
+/// consensus framework that will be used throughout this chapter.
+pub struct PoW {
+	pub(super) threshold: u64,
+}
+
+impl Consensus for PoW {
+	type Digest = u64;
+
+	/// Check that the provided header's hash is below the required threshold.
+	/// This does not rely on the parent digest at all.
+	fn validate(&self, _: &Self::Digest, header: &Header<Self::Digest>) -> bool {
+		exercise!("Exercise 1", solution::validate(self, header))
+	}
+
+	/// Mine a new PoW seal for the partial header provided.
+	/// This does not rely on the parent digest at all.
+	fn seal(&self, _: &Self::Digest, partial_header: Header<()>) -> Option<Header<Self::Digest>> {
+		exercise!("Exercise 2", solution::seal(self, partial_header))
+	}
+}
+
+/// Create a PoW consensus engine that has a difficulty threshold such that roughly 1 in 100 blocks
+/// with randomly drawn nonces will be valid. That is: the threshold should be u64::max_value() /
+/// 100.
+pub fn moderate_difficulty_pow() -> impl Consensus {
+	exercise!("Exercise 3", solution::moderate_difficulty_pow())
+}
+
+/// A Proof of Work consensus engine whose difficulty is part of its type. A header is valid if
+/// its hash is below `u64::MAX >> THRESHOLD_SHIFT`, so every extra bit of shift doubles the
+/// expected work.
+///
+/// Compare this with [`PoW`], which stores its threshold at runtime.
+/// - The difficulty can't be read from a config file or changed while the node is running. A
+///   different difficulty is a different type, so switching between them means a different engine,
+///   like the forks in the last lesson of this chapter.
+/// - In exchange, the engine takes no memory at all, the threshold is a constant the compiler can
+///   fold into the comparison, and the type says exactly what it checks. `ConstPoW<4>` and
+///   `ConstPoW<8>` can't be mixed up by accident.
+/// - Only powers of two are possible. Real chains retarget their difficulty continuously, which is
+///   why they store it at runtime, usually in the header.
+///
+/// Tests are a good fit. [`EasyPoW`] seals a header in two attempts on average, so tests can mine
+/// whole chains without waiting.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ConstPoW<const THRESHOLD_SHIFT: u32>;
+
+impl<const THRESHOLD_SHIFT: u32> ConstPoW<THRESHOLD_SHIFT> {
+	/// Hashes below this are valid. Shifting by 64 or more would not be meaningful, so using such
+	/// an engine is a compile time error.
+	pub const THRESHOLD: u64 = {
+		assert!(THRESHOLD_SHIFT < 64, "the threshold shift must be less than 64");
+		u64::MAX >> THRESHOLD_SHIFT
+	};
+}
+
+/// A PoW engine where half of all hashes are valid, for tests that need to mine quickly.
+pub type EasyPoW = ConstPoW<1>;
+
+impl<const THRESHOLD_SHIFT: u32> Consensus for ConstPoW<THRESHOLD_SHIFT> {
+	type Digest = u64;
+
+	/// Check that the provided header's hash is below the threshold.
+	fn validate(&self, _: &Self::Digest, header: &Header<Self::Digest>) -> bool {
+		exercise!("Exercise 4", solution::validate_const::<THRESHOLD_SHIFT>(header))
+	}
+
+	/// Mine a new PoW seal for the partial header provided.
+	fn seal(&self, _: &Self::Digest, partial_header: Header<()>) -> Option<Header<Self::Digest>> {
+		exercise!("Exercise 5", solution::seal_const::<THRESHOLD_SHIFT>(partial_header))
+	}
+}
+
+#[cfg(feature = "reference-solutions")]
+mod solution;
@@ -0,0 +1,80 @@
+//! Reference solutions for the Proof of Work lesson.
+
+use super::{
+	super::{solution::seal_with, Header},
+	ConstPoW, PoW,
+};
+use bfs_state_machine::hash;
+
+pub(super) fn validate(engine: &PoW, header: &Header<u64>) -> bool {
+	hash(header) < engine.threshold
+}
+
+pub(super) fn seal(engine: &PoW, partial_header: Header<()>) -> Option<Header<u64>> {
+	(0..=u64::MAX)
+		.map(|nonce| seal_with(partial_header.clone(), nonce))
+		.find(|header| validate(engine, header))
+}
+
+pub(super) fn moderate_difficulty_pow() -> PoW {
+	PoW { threshold: u64::MAX / 100 }
+}
+
+pub(super) fn validate_const<const THRESHOLD_SHIFT: u32>(header: &Header<u64>) -> bool {
+	hash(header) < ConstPoW::<THRESHOLD_SHIFT>::THRESHOLD
+}
+
+pub(super) fn seal_const<const THRESHOLD_SHIFT: u32>(
+	partial_header: Header<()>,
+) -> Option<Header<u64>> {
+	(0..=u64::MAX)
+		.map(|nonce| seal_with(partial_header.clone(), nonce))
+		.find(validate_const::<THRESHOLD_SHIFT>)
+}
+
+#[cfg(test)]
+use bfs_state_machine::{hint_assert, hints::Hints};
+
+#[cfg(test)]
+const SEAL_HINTS: Hints = &[
+	"Sealing must produce a header that `validate` accepts.",
+	"The only field you are free to change is the nonce in the consensus digest.",
+	"Try nonces one after another until the hash of the header is below the threshold.",
+];
+
+#[test]
+fn cs_1_sealed_header_is_valid() {
+	let pow = moderate_difficulty_pow();
+	let partial_header =
+		Header { parent: 0, height: 1, state_root: 0, extrinsics_root: 0, consensus_digest: () };
+	let header = seal(&pow, partial_header).expect("PoW can always seal");
+
+	hint_assert!(validate(&pow, &header), SEAL_HINTS);
+}
+
+#[cfg(test)]
+use super::{super::Consensus, EasyPoW};
+
+#[test]
+fn cs_1_const_pow_difficulty_is_in_the_type() {
+	assert_eq!(EasyPoW::THRESHOLD, u64::MAX / 2);
+	assert_eq!(ConstPoW::<0>::THRESHOLD, u64::MAX);
+	assert_eq!(std::mem::size_of::<ConstPoW<8>>(), 0);
+
+	let mut parent =
+		Header { parent: 0, height: 0, state_root: 0, extrinsics_root: 0, consensus_digest: 0 };
+	let mut chain = Vec::new();
+	for height in 1..=10 {
+		let partial_header = Header {
+			parent: hash(&parent),
+			height,
+			state_root: 0,
+			extrinsics_root: 0,
+			consensus_digest: (),
+		};
+		parent = EasyPoW::default().seal(&0, partial_header).expect("PoW can always seal");
+		chain.push(parent.clone());
+	}
+	assert!(EasyPoW::default().verify_sub_chain(&0, &chain));
+	assert!(chain.iter().all(|header| hash(header) < u64::MAX / 2));
+}
@@ -20,11 +20,14 @@ impl Consensus for DictatorConsensus {
 
 	/// Check that the header is signed by the dictator
 	fn validate(&self, _: &Self::Digest, header: &Header<Self::Digest>) -> bool {
-		todo!("Exercise 1")
+		exercise!("Exercise 1", solution::validate(self, header))
 	}
 
 	/// Sign the given partial header by the dictator
 	fn seal(&self, _: &Self::Digest, partial_header: Header<()>) -> Option<Header<Self::Digest>> {
-		todo!("Exercise 2")
+		exercise!("Exercise 2", solution::seal(self, partial_header))
 	}
 }
+
+#[cfg(feature = "reference-solutions")]
+mod solution;
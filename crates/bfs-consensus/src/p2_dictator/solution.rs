@@ -0,0 +1,17 @@
+//! Reference solutions for the Dictator lesson.
+
+use super::{
+	super::{solution::seal_with, ConsensusAuthority, Header},
+	DictatorConsensus,
+};
+
+pub(super) fn validate(engine: &DictatorConsensus, header: &Header<ConsensusAuthority>) -> bool {
+	header.consensus_digest == engine.dictator
+}
+
+pub(super) fn seal(
+	engine: &DictatorConsensus,
+	partial_header: Header<()>,
+) -> Option<Header<ConsensusAuthority>> {
+	Some(seal_with(partial_header, engine.dictator))
+}
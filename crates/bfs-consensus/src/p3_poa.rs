@@ -10,18 +10,19 @@
 //! the proof of authority we are writing here.
 
 use super::{Consensus, ConsensusAuthority, Header};
+use bfs_state_machine::time::Clock;
 
 /// A Proof of Authority consensus engine. If any of the authorities have signed the block, it is
 /// valid.
-struct SimplePoa {
-	authorities: Vec<ConsensusAuthority>,
+pub(super) struct SimplePoa {
+	pub(super) authorities: Vec<ConsensusAuthority>,
 }
 
 impl Consensus for SimplePoa {
 	type Digest = ConsensusAuthority;
 
 	fn validate(&self, parent_digest: &Self::Digest, header: &Header<Self::Digest>) -> bool {
-		todo!("Exercise 1")
+		exercise!("Exercise 1", solution::simple_validate(self, header))
 	}
 
 	fn seal(
@@ -29,7 +30,7 @@ impl Consensus for SimplePoa {
 		parent_digest: &Self::Digest,
 		partial_header: Header<()>,
 	) -> Option<Header<Self::Digest>> {
-		todo!("Exercise 2")
+		exercise!("Exercise 2", solution::simple_seal(self, partial_header))
 	}
 }
 
@@ -44,7 +45,7 @@ impl Consensus for PoaRoundRobinByHeight {
 	type Digest = ConsensusAuthority;
 
 	fn validate(&self, parent_digest: &Self::Digest, header: &Header<Self::Digest>) -> bool {
-		todo!("Exercise 3")
+		exercise!("Exercise 3", solution::by_height_validate(self, header))
 	}
 
 	fn seal(
@@ -52,7 +53,7 @@ impl Consensus for PoaRoundRobinByHeight {
 		parent_digest: &Self::Digest,
 		partial_header: Header<()>,
 	) -> Option<Header<Self::Digest>> {
-		todo!("Exercise 4")
+		exercise!("Exercise 4", solution::by_height_seal(self, partial_header))
 	}
 }
 
@@ -65,8 +66,16 @@ impl Consensus for PoaRoundRobinByHeight {
 ///
 /// A common PoA scheme that works around these weaknesses is to divide time into slots, and then do
 /// a round robin by slot instead of by height
-struct PoaRoundRobinBySlot {
+///
+/// The engine reads the time from its clock rather than from the system, so that tests can control
+/// exactly which slot it is. When sealing, use the current slot according to the clock. If the
+/// clock is behind the parent, use the slot right after the parent's instead.
+struct PoaRoundRobinBySlot<C: Clock> {
 	authorities: Vec<ConsensusAuthority>,
+	/// Where the engine gets the current time from
+	clock: C,
+	/// The length of each slot in milliseconds
+	slot_duration: u64,
 }
 
 /// A digest used for PoaRoundRobinBySlot. The digest contains the slot number as well as the
@@ -78,11 +87,11 @@ struct SlotDigest {
 	signature: ConsensusAuthority,
 }
 
-impl Consensus for PoaRoundRobinBySlot {
+impl<C: Clock> Consensus for PoaRoundRobinBySlot<C> {
 	type Digest = SlotDigest;
 
 	fn validate(&self, parent_digest: &Self::Digest, header: &Header<Self::Digest>) -> bool {
-		todo!("Exercise 5")
+		exercise!("Exercise 5", solution::by_slot_validate(self, parent_digest, header))
 	}
 
 	fn seal(
@@ -90,6 +99,9 @@ impl Consensus for PoaRoundRobinBySlot {
 		parent_digest: &Self::Digest,
 		partial_header: Header<()>,
 	) -> Option<Header<Self::Digest>> {
-		todo!("Exercise 6")
+		exercise!("Exercise 6", solution::by_slot_seal(self, parent_digest, partial_header))
 	}
 }
+
+#[cfg(feature = "reference-solutions")]
+mod solution;
@@ -0,0 +1,127 @@
+//! Reference solutions for the Proof of Authority lesson.
+
+use super::{
+	super::{solution::seal_with, ConsensusAuthority, Header},
+	PoaRoundRobinByHeight, PoaRoundRobinBySlot, SimplePoa, SlotDigest,
+};
+use bfs_state_machine::time::Clock;
+#[cfg(test)]
+use bfs_state_machine::time::{FixedClock, SimulatedClock};
+
+pub(super) fn simple_validate(engine: &SimplePoa, header: &Header<ConsensusAuthority>) -> bool {
+	engine.authorities.contains(&header.consensus_digest)
+}
+
+pub(super) fn simple_seal(
+	engine: &SimplePoa,
+	partial_header: Header<()>,
+) -> Option<Header<ConsensusAuthority>> {
+	engine.authorities.first().map(|author| seal_with(partial_header, *author))
+}
+
+/// The authority whose turn it is at the given height. Block 1 is authored by the first
+/// authority, block 2 by the second, and so on.
+fn author_at_height(engine: &PoaRoundRobinByHeight, height: u64) -> Option<ConsensusAuthority> {
+	if engine.authorities.is_empty() {
+		return None;
+	}
+	let index = height.saturating_sub(1) % engine.authorities.len() as u64;
+	Some(engine.authorities[index as usize])
+}
+
+pub(super) fn by_height_validate(
+	engine: &PoaRoundRobinByHeight,
+	header: &Header<ConsensusAuthority>,
+) -> bool {
+	author_at_height(engine, header.height) == Some(header.consensus_digest)
+}
+
+pub(super) fn by_height_seal(
+	engine: &PoaRoundRobinByHeight,
+	partial_header: Header<()>,
+) -> Option<Header<ConsensusAuthority>> {
+	let author = author_at_height(engine, partial_header.height)?;
+	Some(seal_with(partial_header, author))
+}
+
+/// The authority whose turn it is in the given slot.
+fn author_at_slot<C: Clock>(
+	engine: &PoaRoundRobinBySlot<C>,
+	slot: u64,
+) -> Option<ConsensusAuthority> {
+	if engine.authorities.is_empty() {
+		return None;
+	}
+	Some(engine.authorities[(slot % engine.authorities.len() as u64) as usize])
+}
+
+pub(super) fn by_slot_validate<C: Clock>(
+	engine: &PoaRoundRobinBySlot<C>,
+	parent_digest: &SlotDigest,
+	header: &Header<SlotDigest>,
+) -> bool {
+	let digest = &header.consensus_digest;
+	digest.slot > parent_digest.slot &&
+		author_at_slot(engine, digest.slot) == Some(digest.signature)
+}
+
+/// Seals in the current slot, or the slot immediately following the parent's slot if the clock is
+/// behind, signed by that slot's author.
+pub(super) fn by_slot_seal<C: Clock>(
+	engine: &PoaRoundRobinBySlot<C>,
+	parent_digest: &SlotDigest,
+	partial_header: Header<()>,
+) -> Option<Header<SlotDigest>> {
+	let earliest = parent_digest.slot.checked_add(1)?;
+	let slot = engine.clock.current_slot(engine.slot_duration).max(earliest);
+	let signature = author_at_slot(engine, slot)?;
+	Some(seal_with(partial_header, SlotDigest { slot, signature }))
+}
+
+#[test]
+fn cs_3_round_robin_by_slot_requires_increasing_slots() {
+	use super::super::Consensus;
+
+	let engine = PoaRoundRobinBySlot {
+		authorities: vec![ConsensusAuthority::Alice, ConsensusAuthority::Bob],
+		clock: FixedClock(0),
+		slot_duration: 6_000,
+	};
+	let parent_digest = SlotDigest { slot: 4, signature: ConsensusAuthority::Alice };
+	let partial_header =
+		Header { parent: 0, height: 1, state_root: 0, extrinsics_root: 0, consensus_digest: () };
+	let header = engine.seal(&parent_digest, partial_header).expect("next slot has an author");
+
+	assert_eq!(header.consensus_digest.signature, ConsensusAuthority::Bob);
+	assert!(engine.validate(&parent_digest, &header));
+	assert!(!engine.validate(&header.consensus_digest, &header));
+}
+
+#[test]
+fn cs_3_round_robin_by_slot_seals_in_current_slot() {
+	use super::super::Consensus;
+
+	let clock = SimulatedClock::new(6_000 * 10);
+	let engine = PoaRoundRobinBySlot {
+		authorities: vec![ConsensusAuthority::Alice, ConsensusAuthority::Bob],
+		clock: &clock,
+		slot_duration: 6_000,
+	};
+	let parent_digest = SlotDigest { slot: 4, signature: ConsensusAuthority::Alice };
+	let partial_header =
+		|| Header { parent: 0, height: 1, state_root: 0, extrinsics_root: 0, consensus_digest: () };
+
+	let header = engine.seal(&parent_digest, partial_header()).expect("slot has an author");
+	assert_eq!(
+		header.consensus_digest,
+		SlotDigest { slot: 10, signature: ConsensusAuthority::Alice }
+	);
+
+	clock.advance(6_000);
+	let header = engine.seal(&parent_digest, partial_header()).expect("slot has an author");
+	assert_eq!(
+		header.consensus_digest,
+		SlotDigest { slot: 11, signature: ConsensusAuthority::Bob }
+	);
+	assert!(engine.validate(&parent_digest, &header));
+}
@@ -0,0 +1,73 @@
+//! Reference solutions for the Even Only lesson.
+
+use super::{
+	super::{p1_pow::PoW, Consensus, Header},
+	EvenOnly,
+};
+
+pub(super) fn validate<Inner: Consensus>(
+	engine: &EvenOnly<Inner>,
+	parent_digest: &Inner::Digest,
+	header: &Header<Inner::Digest>,
+) -> bool {
+	header.state_root.is_multiple_of(2) && engine.0.validate(parent_digest, header)
+}
+
+pub(super) fn seal<Inner: Consensus>(
+	engine: &EvenOnly<Inner>,
+	parent_digest: &Inner::Digest,
+	partial_header: Header<()>,
+) -> Option<Header<Inner::Digest>> {
+	if !partial_header.state_root.is_multiple_of(2) {
+		return None;
+	}
+	engine.0.seal(parent_digest, partial_header)
+}
+
+pub(super) fn almost_valid_but_not_all_even() -> Vec<Header<u64>> {
+	// The moderate difficulty engine from section 1 hides its digest type behind `impl Consensus`,
+	// so we build the same engine directly.
+	let pow = PoW { threshold: u64::MAX / 100 };
+	let genesis =
+		Header { parent: 0, height: 0, state_root: 0, extrinsics_root: 0, consensus_digest: 0 };
+
+	let mut chain = vec![genesis];
+	for state_root in [2, 3, 4] {
+		let parent = chain.last().expect("chain starts with genesis");
+		let partial_header = Header {
+			parent: bfs_state_machine::hash(parent),
+			height: parent.height + 1,
+			state_root,
+			extrinsics_root: 0,
+			consensus_digest: (),
+		};
+		let header =
+			pow.seal(&parent.consensus_digest, partial_header).expect("PoW can always seal");
+		chain.push(header);
+	}
+	chain
+}
+
+#[cfg(test)]
+use bfs_state_machine::{hint_assert, hints::Hints};
+
+#[cfg(test)]
+const EVEN_ONLY_HINTS: Hints = &[
+	"A header must satisfy the inner engine and have an even state root.",
+	"Delegate to the inner engine's `validate` first, then check the state root yourself.",
+];
+
+#[test]
+fn cs_4_almost_valid_chain_is_rejected_only_by_even_only() {
+	use super::super::Consensus;
+
+	let chain = almost_valid_but_not_all_even();
+	let pow = PoW { threshold: u64::MAX / 100 };
+	let even_only = EvenOnly(PoW { threshold: u64::MAX / 100 });
+
+	assert!(pow.verify_sub_chain(&chain[0].consensus_digest, &chain[1..]));
+	hint_assert!(
+		!even_only.verify_sub_chain(&chain[0].consensus_digest, &chain[1..]),
+		EVEN_ONLY_HINTS
+	);
+}
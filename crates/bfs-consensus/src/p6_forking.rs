@@ -15,7 +15,11 @@ use super::{Consensus, ConsensusAuthority, Header};
 struct Forked<D, Before, After> {
 	/// The first block height at which the new consensus rules apply
 	fork_height: u64,
-	phdata: PhantomData<(D, Before, After)>,
+	/// The consensus engine whose rules apply before the fork
+	before: Before,
+	/// The consensus engine whose rules apply from the fork onward
+	after: After,
+	phdata: PhantomData<D>,
 }
 
 impl<D, B, A> Consensus for Forked<D, B, A>
@@ -25,11 +29,12 @@ where
 	A: Consensus,
 	B::Digest: Into<D>,
 	A::Digest: Into<D>,
+	D: TryInto<B::Digest> + TryInto<A::Digest>,
 {
 	type Digest = D;
 
 	fn validate(&self, parent_digest: &Self::Digest, header: &Header<Self::Digest>) -> bool {
-		todo!("Exercise 1")
+		exercise!("Exercise 1", solution::validate(self, parent_digest, header))
 	}
 
 	fn seal(
@@ -37,7 +42,7 @@ where
 		parent_digest: &Self::Digest,
 		partial_header: Header<()>,
 	) -> Option<Header<Self::Digest>> {
-		todo!("Exercise 2")
+		exercise!("Exercise 2", solution::seal(self, parent_digest, partial_header))
 	}
 }
 
@@ -49,7 +54,10 @@ fn change_authorities(
 	initial_authorities: Vec<ConsensusAuthority>,
 	final_authorities: Vec<ConsensusAuthority>,
 ) -> impl Consensus {
-	todo!("Exercise 3")
+	exercise!(
+		"Exercise 3",
+		solution::change_authorities(fork_height, initial_authorities, final_authorities)
+	)
 }
 
 /// Create a PoW consensus engine that changes the difficulty part way through the chain's history.
@@ -58,7 +66,10 @@ fn change_difficulty(
 	initial_difficulty: u64,
 	final_difficulty: u64,
 ) -> impl Consensus {
-	todo!("Exercise 4")
+	exercise!(
+		"Exercise 4",
+		solution::change_difficulty(fork_height, initial_difficulty, final_difficulty)
+	)
 }
 
 /// Earlier in this chapter we implemented a consensus rule in which blocks are only considered
@@ -69,8 +80,11 @@ fn change_difficulty(
 /// Create a consensus engine that introduces the even-only logic only after the given fork height.
 /// Other than the evenness requirement, the consensus rules should not change at the fork. This
 /// function should work with either PoW, PoA, or anything else as the underlying consensus engine.
-fn even_after_given_height<Original: Consensus>(fork_height: u64) -> impl Consensus {
-	todo!("Exercise 5")
+fn even_after_given_height<Original: Consensus + Clone>(
+	fork_height: u64,
+	original: Original,
+) -> impl Consensus {
+	exercise!("Exercise 5", solution::even_after_given_height(fork_height, original))
 }
 
 /// So far we have considered the simpler case where the consensus engines before and after the fork
@@ -106,5 +120,8 @@ fn pow_to_poa(
 	difficulty: u64,
 	authorities: Vec<ConsensusAuthority>,
 ) -> impl Consensus {
-	todo!("Exercise 6")
+	exercise!("Exercise 6", solution::pow_to_poa(fork_height, difficulty, authorities))
 }
+
+#[cfg(feature = "reference-solutions")]
+mod solution;
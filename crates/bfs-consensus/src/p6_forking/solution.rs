@@ -0,0 +1,220 @@
+//! Reference solutions for the Forking lesson.
+
+use std::marker::PhantomData;
+
+use super::{
+	super::{
+		p1_pow::PoW, p3_poa::SimplePoa, p4_even_only::EvenOnly, solution::seal_with, Consensus,
+		ConsensusAuthority, Header,
+	},
+	Forked, PowOrPoaDigest,
+};
+
+/// Convert the digest of a header into another digest type, if possible.
+fn convert_header<From: Clone + TryInto<To>, To>(header: &Header<From>) -> Option<Header<To>> {
+	let consensus_digest = header.consensus_digest.clone().try_into().ok()?;
+	Some(Header {
+		parent: header.parent,
+		height: header.height,
+		state_root: header.state_root,
+		extrinsics_root: header.extrinsics_root,
+		consensus_digest,
+	})
+}
+
+/// Validate a header with the given engine after converting the digests into its digest type.
+///
+/// The generic forked engine requires the parent digest to be representable under the rules that
+/// apply to the header. When it isn't, for example at a fork where the digest type changes, the
+/// header is considered invalid.
+fn validate_with<D: Clone + TryInto<C::Digest>, C: Consensus>(
+	engine: &C,
+	parent_digest: &D,
+	header: &Header<D>,
+) -> bool {
+	let Ok(parent_digest) = parent_digest.clone().try_into() else {
+		return false;
+	};
+	match convert_header(header) {
+		Some(header) => engine.validate(&parent_digest, &header),
+		None => false,
+	}
+}
+
+/// Seal a header with the given engine, converting the digests to and from its digest type.
+fn seal_with_engine<D: Clone + TryInto<C::Digest>, C: Consensus>(
+	engine: &C,
+	parent_digest: &D,
+	partial_header: Header<()>,
+) -> Option<Header<D>>
+where
+	C::Digest: Into<D>,
+{
+	let parent_digest = parent_digest.clone().try_into().ok()?;
+	let header = engine.seal(&parent_digest, partial_header)?;
+	Some(Header {
+		parent: header.parent,
+		height: header.height,
+		state_root: header.state_root,
+		extrinsics_root: header.extrinsics_root,
+		consensus_digest: header.consensus_digest.into(),
+	})
+}
+
+pub(super) fn validate<D, B, A>(
+	engine: &Forked<D, B, A>,
+	parent_digest: &D,
+	header: &Header<D>,
+) -> bool
+where
+	D: Clone + TryInto<B::Digest> + TryInto<A::Digest>,
+	B: Consensus,
+	A: Consensus,
+{
+	if header.height < engine.fork_height {
+		validate_with(&engine.before, parent_digest, header)
+	} else {
+		validate_with(&engine.after, parent_digest, header)
+	}
+}
+
+pub(super) fn seal<D, B, A>(
+	engine: &Forked<D, B, A>,
+	parent_digest: &D,
+	partial_header: Header<()>,
+) -> Option<Header<D>>
+where
+	D: Clone + TryInto<B::Digest> + TryInto<A::Digest>,
+	B: Consensus,
+	A: Consensus,
+	B::Digest: Into<D>,
+	A::Digest: Into<D>,
+{
+	if partial_header.height < engine.fork_height {
+		seal_with_engine(&engine.before, parent_digest, partial_header)
+	} else {
+		seal_with_engine(&engine.after, parent_digest, partial_header)
+	}
+}
+
+/// Difficulty is expressed as "roughly one in `difficulty` nonces is valid".
+fn pow_with_difficulty(difficulty: u64) -> PoW {
+	PoW { threshold: u64::MAX / difficulty.max(1) }
+}
+
+pub(super) fn change_authorities(
+	fork_height: u64,
+	initial_authorities: Vec<ConsensusAuthority>,
+	final_authorities: Vec<ConsensusAuthority>,
+) -> Forked<ConsensusAuthority, SimplePoa, SimplePoa> {
+	Forked {
+		fork_height,
+		before: SimplePoa { authorities: initial_authorities },
+		after: SimplePoa { authorities: final_authorities },
+		phdata: PhantomData,
+	}
+}
+
+pub(super) fn change_difficulty(
+	fork_height: u64,
+	initial_difficulty: u64,
+	final_difficulty: u64,
+) -> Forked<u64, PoW, PoW> {
+	Forked {
+		fork_height,
+		before: pow_with_difficulty(initial_difficulty),
+		after: pow_with_difficulty(final_difficulty),
+		phdata: PhantomData,
+	}
+}
+
+pub(super) fn even_after_given_height<Original: Consensus + Clone>(
+	fork_height: u64,
+	original: Original,
+) -> Forked<Original::Digest, Original, EvenOnly<Original>> {
+	Forked { fork_height, before: original.clone(), after: EvenOnly(original), phdata: PhantomData }
+}
+
+/// The PoW to PoA handoff can not be expressed with the generic `Forked` engine because the first
+/// PoA block's parent carries a PoW digest. Neither PoW nor simple PoA look at the parent digest,
+/// so this engine simply hands each engine the header's own digest in its place.
+pub(super) struct PowToPoa {
+	fork_height: u64,
+	pow: PoW,
+	poa: SimplePoa,
+}
+
+impl Consensus for PowToPoa {
+	type Digest = PowOrPoaDigest;
+
+	fn validate(&self, _: &Self::Digest, header: &Header<Self::Digest>) -> bool {
+		let partial_header = partial_header_of(header);
+		match (header.height < self.fork_height, header.consensus_digest) {
+			(true, PowOrPoaDigest::Pow(nonce)) =>
+				self.pow.validate(&nonce, &seal_with(partial_header, nonce)),
+			(false, PowOrPoaDigest::Poa(author)) =>
+				self.poa.validate(&author, &seal_with(partial_header, author)),
+			_ => false,
+		}
+	}
+
+	fn seal(&self, _: &Self::Digest, partial_header: Header<()>) -> Option<Header<Self::Digest>> {
+		if partial_header.height < self.fork_height {
+			let header = self.pow.seal(&0, partial_header)?;
+			Some(seal_with(partial_header_of(&header), header.consensus_digest.into()))
+		} else {
+			let author = *self.poa.authorities.first()?;
+			let header = self.poa.seal(&author, partial_header)?;
+			Some(seal_with(partial_header_of(&header), header.consensus_digest.into()))
+		}
+	}
+}
+
+/// Strip the digest from a header.
+fn partial_header_of<Digest>(header: &Header<Digest>) -> Header<()> {
+	Header {
+		parent: header.parent,
+		height: header.height,
+		state_root: header.state_root,
+		extrinsics_root: header.extrinsics_root,
+		consensus_digest: (),
+	}
+}
+
+pub(super) fn pow_to_poa(
+	fork_height: u64,
+	difficulty: u64,
+	authorities: Vec<ConsensusAuthority>,
+) -> PowToPoa {
+	PowToPoa { fork_height, pow: pow_with_difficulty(difficulty), poa: SimplePoa { authorities } }
+}
+
+#[test]
+fn cs_6_pow_to_poa_seals_both_sides_of_the_fork() {
+	let engine = pow_to_poa(2, 100, vec![ConsensusAuthority::Charlie]);
+	let genesis_digest = PowOrPoaDigest::Pow(0);
+
+	let mut parent = Header {
+		parent: 0,
+		height: 0,
+		state_root: 0,
+		extrinsics_root: 0,
+		consensus_digest: genesis_digest,
+	};
+	let mut chain = Vec::new();
+	for _ in 0..3 {
+		let partial_header = Header {
+			parent: bfs_state_machine::hash(&parent),
+			height: parent.height + 1,
+			state_root: 0,
+			extrinsics_root: 0,
+			consensus_digest: (),
+		};
+		parent = engine.seal(&parent.consensus_digest, partial_header).expect("engine can seal");
+		chain.push(parent.clone());
+	}
+
+	assert!(matches!(chain[0].consensus_digest, PowOrPoaDigest::Pow(_)));
+	assert_eq!(chain[2].consensus_digest, PowOrPoaDigest::Poa(ConsensusAuthority::Charlie));
+	assert!(engine.verify_sub_chain(&genesis_digest, &chain));
+}
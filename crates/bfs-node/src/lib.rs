@@ -0,0 +1,111 @@
+/// Brain dump (TODO revise later):
+///
+/// We have a blockchain data structure featuring:
+/// 1. A built in addition accumulator state machine
+/// 2. A built-in pow consensus mechanism
+///
+/// We also have abstractions over:
+/// 1. State Machines
+/// 2. Consensus Engines
+///
+/// Let's refactor our blockchain to take advantage of these two abstractions
+/// In doing so, we create a blockchain framework
+use bfs_consensus::{exercise, Consensus, Header};
+use bfs_state_machine::StateMachine;
+type Hash = u64;
+
+#[cfg(feature = "reference-solutions")]
+mod solution;
+
+/// The header type comes from the consensus chapter, so the chain-level methods we need on it are
+/// provided through this extension trait.
+trait HeaderChain: Sized {
+	/// Returns a new valid genesis header. Genesis is not sealed, so its digest is the default one.
+	fn genesis(genesis_state_root: Hash) -> Self;
+
+	/// Create and return a child header that is not sealed yet. The consensus engine seals it.
+	fn child(&self, state_root: Hash, extrinsics_root: Hash) -> Header<()>;
+
+	/// Verify a single child header. Only the link to the parent is checked here. The consensus
+	/// engine checks the seal.
+	fn verify_child(&self, child: &Self) -> bool;
+
+	/// Verify that all the given headers form a valid chain from this header to the tip.
+	fn verify_sub_chain(&self, chain: &[Self]) -> bool;
+}
+
+impl<Digest: Default + std::hash::Hash> HeaderChain for Header<Digest> {
+	fn genesis(genesis_state_root: Hash) -> Self {
+		exercise!("Exercise 1", solution::genesis_header(genesis_state_root))
+	}
+
+	fn child(&self, state_root: Hash, extrinsics_root: Hash) -> Header<()> {
+		exercise!("Exercise 2", solution::child_header(self, state_root, extrinsics_root))
+	}
+
+	fn verify_child(&self, child: &Self) -> bool {
+		exercise!("Exercise 3", solution::verify_child_header(self, child))
+	}
+
+	fn verify_sub_chain(&self, chain: &[Self]) -> bool {
+		exercise!("Exercise 4", solution::verify_header_sub_chain(self, chain))
+	}
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+struct Block<C: Consensus, SM: StateMachine> {
+	header: Header<C::Digest>,
+	body: Vec<SM::Transition>,
+}
+
+impl<C, SM> Block<C, SM>
+where
+	C: Consensus,
+	C::Digest: Default,
+	SM: StateMachine,
+	SM::State: Clone + std::hash::Hash,
+	SM::Transition: std::hash::Hash,
+{
+	/// Returns a new valid genesis block. By convention this block has no extrinsics.
+	pub fn genesis(genesis_state: &SM::State) -> Self {
+		exercise!("Exercise 5", solution::genesis_block(genesis_state))
+	}
+
+	/// Create and return a valid child block, sealed by the given consensus engine. Returns `None`
+	/// if the engine can't seal it.
+	pub fn child(
+		&self,
+		engine: &C,
+		pre_state: &SM::State,
+		extrinsics: Vec<SM::Transition>,
+	) -> Option<Self> {
+		exercise!("Exercise 6", solution::child_block(self, engine, pre_state, extrinsics))
+	}
+
+	/// Verify that all the given blocks form a valid chain from this block to the tip. The
+	/// pre-state is the state after this block, and every block in the chain is executed on it.
+	pub fn verify_sub_chain(&self, engine: &C, pre_state: &SM::State, chain: &[Self]) -> bool {
+		exercise!("Exercise 7", solution::verify_block_sub_chain(self, engine, pre_state, chain))
+	}
+}
+
+/// Create and return a block chain that is n blocks long starting from the given genesis state.
+/// The blocks should not contain any transactions. Returns `None` if the engine can't seal one of
+/// them.
+fn create_empty_chain<C, SM>(
+	engine: &C,
+	n: u64,
+	genesis_state: &SM::State,
+) -> Option<Vec<Block<C, SM>>>
+where
+	C: Consensus,
+	C::Digest: Default,
+	SM: StateMachine,
+	SM::State: Clone + std::hash::Hash,
+	SM::Transition: std::hash::Hash,
+{
+	exercise!("Exercise 8", solution::create_empty_chain(engine, n, genesis_state))
+}
+
+//TODO maybe this shouldn't be a whole chapter. Maybe it is the first
+// section in the chapter on building a client
@@ -0,0 +1,227 @@
+//! Reference solutions for the exercises in this chapter.
+
+use super::{Block, Consensus, Hash, Header, HeaderChain, StateMachine};
+use bfs_state_machine::hash;
+
+pub(super) fn genesis_header<Digest: Default>(genesis_state_root: Hash) -> Header<Digest> {
+	Header {
+		parent: 0,
+		height: 0,
+		state_root: genesis_state_root,
+		extrinsics_root: 0,
+		consensus_digest: Digest::default(),
+	}
+}
+
+pub(super) fn child_header<Digest: std::hash::Hash>(
+	parent: &Header<Digest>,
+	state_root: Hash,
+	extrinsics_root: Hash,
+) -> Header<()> {
+	Header {
+		parent: hash(parent),
+		height: parent.height + 1,
+		state_root,
+		extrinsics_root,
+		consensus_digest: (),
+	}
+}
+
+pub(super) fn verify_child_header<Digest: std::hash::Hash>(
+	parent: &Header<Digest>,
+	child: &Header<Digest>,
+) -> bool {
+	child.parent == hash(parent) && child.height == parent.height + 1
+}
+
+pub(super) fn verify_header_sub_chain<Digest: Default + std::hash::Hash>(
+	parent: &Header<Digest>,
+	chain: &[Header<Digest>],
+) -> bool {
+	let mut parent = parent;
+	for header in chain {
+		if !parent.verify_child(header) {
+			return false;
+		}
+		parent = header;
+	}
+	true
+}
+
+/// Execute the given extrinsics one after another, starting from the given state.
+fn execute<SM>(pre_state: &SM::State, extrinsics: &[SM::Transition]) -> SM::State
+where
+	SM: StateMachine,
+	SM::State: Clone,
+{
+	extrinsics
+		.iter()
+		.fold(pre_state.clone(), |state, extrinsic| SM::next_state(&state, extrinsic))
+}
+
+pub(super) fn genesis_block<C, SM>(genesis_state: &SM::State) -> Block<C, SM>
+where
+	C: Consensus,
+	C::Digest: Default,
+	SM: StateMachine,
+	SM::State: std::hash::Hash,
+{
+	Block { header: Header::genesis(hash(genesis_state)), body: Vec::new() }
+}
+
+pub(super) fn child_block<C, SM>(
+	parent: &Block<C, SM>,
+	engine: &C,
+	pre_state: &SM::State,
+	extrinsics: Vec<SM::Transition>,
+) -> Option<Block<C, SM>>
+where
+	C: Consensus,
+	C::Digest: Default,
+	SM: StateMachine,
+	SM::State: Clone + std::hash::Hash,
+	SM::Transition: std::hash::Hash,
+{
+	let post_state = execute::<SM>(pre_state, &extrinsics);
+	let partial_header = parent.header.child(hash(&post_state), hash(&extrinsics));
+	let header = engine.seal(&parent.header.consensus_digest, partial_header)?;
+	Some(Block { header, body: extrinsics })
+}
+
+pub(super) fn verify_block_sub_chain<C, SM>(
+	parent: &Block<C, SM>,
+	engine: &C,
+	pre_state: &SM::State,
+	chain: &[Block<C, SM>],
+) -> bool
+where
+	C: Consensus,
+	C::Digest: Default,
+	SM: StateMachine,
+	SM::State: Clone + std::hash::Hash,
+	SM::Transition: std::hash::Hash,
+{
+	let headers: Vec<_> = chain.iter().map(|block| block.header.clone()).collect();
+	if !parent.header.verify_sub_chain(&headers) ||
+		!engine.verify_sub_chain(&parent.header.consensus_digest, &headers)
+	{
+		return false;
+	}
+
+	let mut state = pre_state.clone();
+	for block in chain {
+		if block.header.extrinsics_root != hash(&block.body) {
+			return false;
+		}
+		state = execute::<SM>(&state, &block.body);
+		if block.header.state_root != hash(&state) {
+			return false;
+		}
+	}
+	true
+}
+
+pub(super) fn create_empty_chain<C, SM>(
+	engine: &C,
+	n: u64,
+	genesis_state: &SM::State,
+) -> Option<Vec<Block<C, SM>>>
+where
+	C: Consensus,
+	C::Digest: Default,
+	SM: StateMachine,
+	SM::State: Clone + std::hash::Hash,
+	SM::Transition: std::hash::Hash,
+{
+	if n == 0 {
+		return Some(Vec::new());
+	}
+	let mut chain = vec![Block::genesis(genesis_state)];
+	for _ in 1..n {
+		let child = chain.last().unwrap().child(engine, genesis_state, Vec::new())?;
+		chain.push(child);
+	}
+	Some(chain)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	/// A state machine that adds every transition to a running total.
+	#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+	struct Adder;
+
+	impl StateMachine for Adder {
+		type State = u64;
+		type Transition = u64;
+
+		fn next_state(starting_state: &u64, t: &u64) -> u64 {
+			starting_state.wrapping_add(*t)
+		}
+	}
+
+	type TestBlock = Block<(), Adder>;
+
+	#[test]
+	fn fw_genesis_header() {
+		let g = Header::<()>::genesis(7);
+		assert_eq!(g.parent, 0);
+		assert_eq!(g.height, 0);
+		assert_eq!(g.state_root, 7);
+		assert_eq!(g.extrinsics_root, 0);
+	}
+
+	#[test]
+	fn fw_child_header_links_to_parent() {
+		let g = Header::<()>::genesis(0);
+		let child = g.child(1, 2);
+		assert_eq!(child.parent, hash(&g));
+		assert_eq!(child.height, 1);
+		assert!(g.verify_child(&child));
+		assert!(g.verify_sub_chain(&[child]));
+	}
+
+	#[test]
+	fn fw_header_chain_rejects_wrong_height() {
+		let g = Header::<()>::genesis(0);
+		let mut child = g.child(0, 0);
+		child.height = 2;
+		assert!(!g.verify_child(&child));
+		assert!(!g.verify_sub_chain(&[child]));
+	}
+
+	#[test]
+	fn fw_block_child_executes_extrinsics() {
+		let g = TestBlock::genesis(&0);
+		let b1 = g.child(&(), &0, vec![3, 4]).unwrap();
+		assert_eq!(b1.header.state_root, hash(&7u64));
+		assert_eq!(b1.header.extrinsics_root, hash(&vec![3u64, 4]));
+		assert!(g.verify_sub_chain(&(), &0, &[b1]));
+	}
+
+	#[test]
+	fn fw_block_chain_rejects_wrong_state_root() {
+		let g = TestBlock::genesis(&0);
+		let mut b1 = g.child(&(), &0, vec![3]).unwrap();
+		b1.header.state_root = hash(&4u64);
+		assert!(!g.verify_sub_chain(&(), &0, &[b1]));
+	}
+
+	#[test]
+	fn fw_block_chain_rejects_tampered_body() {
+		let g = TestBlock::genesis(&0);
+		let mut b1 = g.child(&(), &0, vec![3]).unwrap();
+		b1.body = vec![4];
+		assert!(!g.verify_sub_chain(&(), &0, &[b1]));
+	}
+
+	#[test]
+	fn fw_create_empty_chain() {
+		let chain = create_empty_chain::<(), Adder>(&(), 4, &5).unwrap();
+		assert_eq!(chain.len(), 4);
+		assert!(chain.iter().all(|block| block.body.is_empty()));
+		assert!(chain.iter().all(|block| block.header.state_root == hash(&5u64)));
+		assert!(chain[0].verify_sub_chain(&(), &5, &chain[1..]));
+	}
+}
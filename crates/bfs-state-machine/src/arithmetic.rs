@@ -0,0 +1,261 @@
+//! Deterministic, integer-only arithmetic helpers.
+//!
+//! Every node in a blockchain network must compute exactly the same state transitions. Floating
+//! point arithmetic does not guarantee that. Results may differ between platforms, compilers, and
+//! even optimization levels. So consensus-critical code such as rewards, slashing, and fees
+//! expresses ratios as fixed-point integers instead.
+//!
+//! This module provides two such ratio types that differ only in their precision.
+//! - [`Percent`] - parts per hundred, stored in a `u8`
+//! - [`Perbill`] - parts per billion, stored in a `u32`
+//!
+//! It also provides [`Balance`], a newtype for amounts of currency whose arithmetic must never
+//! silently wrap. Arithmetic on it is checked, because creating or destroying money by accident is
+//! never acceptable. Quantities that are safe to cap, such as the work in a chain, saturate
+//! instead, and live next to the code that accumulates them.
+//!
+//! All operations are saturating or checked. None of them can overflow or panic.
+
+/// Implements a fixed-point ratio type between zero and one, stored as an integer number of parts.
+macro_rules! implement_per_thing {
+	($name:ident, $inner:ty, $accuracy:expr, $doc:literal) => {
+		#[doc = $doc]
+		#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+		pub struct $name($inner);
+
+		impl $name {
+			/// The number of parts that make up one whole.
+			pub const ACCURACY: $inner = $accuracy;
+
+			/// Nothing. Zero parts.
+			pub const fn zero() -> Self {
+				Self(0)
+			}
+
+			/// Everything. All the parts.
+			pub const fn one() -> Self {
+				Self(Self::ACCURACY)
+			}
+
+			/// Build a ratio from its raw number of parts. Values above one are clamped to one.
+			pub fn from_parts(parts: $inner) -> Self {
+				Self(parts.min(Self::ACCURACY))
+			}
+
+			/// The raw number of parts in this ratio.
+			pub fn deconstruct(self) -> $inner {
+				self.0
+			}
+
+			/// Build the ratio `numerator / denominator`, rounding down to the nearest part.
+			///
+			/// Ratios above one are clamped to one. A zero denominator is treated as "everything".
+			pub fn from_rational(numerator: u64, denominator: u64) -> Self {
+				if denominator == 0 || numerator >= denominator {
+					return Self::one();
+				}
+				let parts = numerator as u128 * Self::ACCURACY as u128 / denominator as u128;
+				Self(parts as $inner)
+			}
+
+			/// Multiply `value` by this ratio, rounding down.
+			pub fn mul_floor(self, value: u64) -> u64 {
+				(value as u128 * self.0 as u128 / Self::ACCURACY as u128) as u64
+			}
+
+			/// Multiply `value` by this ratio, rounding up.
+			pub fn mul_ceil(self, value: u64) -> u64 {
+				(value as u128 * self.0 as u128).div_ceil(Self::ACCURACY as u128) as u64
+			}
+
+			/// The complement of this ratio. That is, one minus this ratio.
+			pub fn left_from_one(self) -> Self {
+				Self(Self::ACCURACY - self.0)
+			}
+
+			/// Add two ratios, clamping the result to one.
+			pub fn saturating_add(self, other: Self) -> Self {
+				Self::from_parts(self.0.saturating_add(other.0))
+			}
+
+			/// Subtract two ratios, clamping the result to zero.
+			pub fn saturating_sub(self, other: Self) -> Self {
+				Self(self.0.saturating_sub(other.0))
+			}
+
+			/// Multiply two ratios, rounding down. The product of two ratios can never exceed one.
+			pub fn saturating_mul(self, other: Self) -> Self {
+				let parts = self.0 as u128 * other.0 as u128 / Self::ACCURACY as u128;
+				Self(parts as $inner)
+			}
+
+			/// Add two ratios, or return `None` if the result would exceed one.
+			pub fn checked_add(self, other: Self) -> Option<Self> {
+				let parts = self.0.checked_add(other.0)?;
+				(parts <= Self::ACCURACY).then_some(Self(parts))
+			}
+
+			/// Subtract two ratios, or return `None` if the result would be below zero.
+			pub fn checked_sub(self, other: Self) -> Option<Self> {
+				self.0.checked_sub(other.0).map(Self)
+			}
+		}
+	};
+}
+
+implement_per_thing!(Percent, u8, 100, "A ratio between zero and one, in parts per hundred.");
+implement_per_thing!(
+	Perbill,
+	u32,
+	1_000_000_000,
+	"A ratio between zero and one, in parts per billion."
+);
+
+impl Percent {
+	/// Build a ratio from a whole number of percent. Values above 100 are clamped to 100.
+	pub fn from_percent(percent: u8) -> Self {
+		Self::from_parts(percent)
+	}
+}
+
+impl Perbill {
+	/// Build a ratio from a whole number of percent. Values above 100 are clamped to 100.
+	pub fn from_percent(percent: u32) -> Self {
+		Self::from_parts(percent.saturating_mul(Self::ACCURACY / 100))
+	}
+}
+
+/// An amount of currency.
+///
+/// Balances deliberately do not implement the arithmetic operators. Every operation is checked, and
+/// the caller must decide what happens when it fails.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Balance(pub u64);
+
+impl Balance {
+	/// No money at all.
+	pub const fn zero() -> Self {
+		Balance(0)
+	}
+
+	/// Whether this balance is empty.
+	pub fn is_zero(self) -> bool {
+		self.0 == 0
+	}
+
+	/// Add two balances, or return `None` if the result would overflow.
+	pub fn checked_add(self, other: Self) -> Option<Self> {
+		self.0.checked_add(other.0).map(Balance)
+	}
+
+	/// Subtract two balances, or return `None` if the result would be negative.
+	pub fn checked_sub(self, other: Self) -> Option<Self> {
+		self.0.checked_sub(other.0).map(Balance)
+	}
+
+	/// Multiply a balance by a scalar, or return `None` if the result would overflow.
+	pub fn checked_mul(self, factor: u64) -> Option<Self> {
+		self.0.checked_mul(factor).map(Balance)
+	}
+}
+
+impl From<u64> for Balance {
+	fn from(amount: u64) -> Self {
+		Balance(amount)
+	}
+}
+
+/// Deterministic pseudo-random inputs for the property tests below.
+#[cfg(test)]
+fn sample_inputs() -> impl Iterator<Item = (u64, u64)> {
+	(0..2_000u64).map(|i| {
+		let a = crate::hash(&(i, "a"));
+		let b = crate::hash(&(i, "b"));
+		// Mix in small numbers and extreme values, where rounding bugs tend to hide.
+		match i % 4 {
+			0 => (a % 1_000, b % 1_000 + 1),
+			1 => (a, b),
+			2 => (u64::MAX - a % 10, b),
+			_ => (a % b.max(1), b.max(1)),
+		}
+	})
+}
+
+#[test]
+fn arithmetic_mul_rounding_brackets_exact_result() {
+	for (value, parts) in sample_inputs() {
+		let ratio = Perbill::from_parts((parts % (Perbill::ACCURACY as u64 + 1)) as u32);
+		let exact = value as u128 * ratio.deconstruct() as u128;
+		let accuracy = Perbill::ACCURACY as u128;
+
+		let floor = ratio.mul_floor(value) as u128;
+		let ceil = ratio.mul_ceil(value) as u128;
+
+		assert!(floor * accuracy <= exact);
+		assert!(ceil * accuracy >= exact);
+		assert!(ceil - floor <= 1);
+		assert_eq!(ceil == floor, floor * accuracy == exact);
+	}
+}
+
+#[test]
+fn arithmetic_from_rational_rounds_down() {
+	for (numerator, denominator) in sample_inputs() {
+		let ratio = Percent::from_rational(numerator, denominator);
+
+		if numerator >= denominator {
+			assert_eq!(ratio, Percent::one());
+		} else {
+			// Rounding down means the ratio never exceeds the true value, but is within one part.
+			let parts = ratio.deconstruct() as u128;
+			let scaled = numerator as u128 * Percent::ACCURACY as u128;
+			assert!(parts * denominator as u128 <= scaled);
+			assert!((parts + 1) * denominator as u128 > scaled);
+		}
+	}
+}
+
+#[test]
+fn arithmetic_mul_is_monotonic() {
+	for (a, b) in sample_inputs() {
+		let (small, large) = (a.min(b), a.max(b));
+		let ratio = Perbill::from_rational(a % 1_000, 1_000);
+
+		assert!(ratio.mul_floor(small) <= ratio.mul_floor(large));
+		assert!(ratio.mul_floor(small) <= small);
+	}
+}
+
+#[test]
+fn arithmetic_saturating_and_checked_ops() {
+	let seventy = Percent::from_percent(70);
+	let forty = Percent::from_percent(40);
+
+	assert_eq!(seventy.saturating_add(forty), Percent::one());
+	assert_eq!(seventy.checked_add(forty), None);
+	assert_eq!(forty.saturating_sub(seventy), Percent::zero());
+	assert_eq!(forty.checked_sub(seventy), None);
+	assert_eq!(seventy.checked_sub(forty), Some(Percent::from_percent(30)));
+	assert_eq!(seventy.saturating_mul(forty), Percent::from_percent(28));
+	assert_eq!(seventy.left_from_one(), Percent::from_percent(30));
+	assert_eq!(Percent::from_percent(250), Percent::one());
+}
+
+#[test]
+fn arithmetic_perbill_basics() {
+	assert_eq!(Perbill::from_percent(50).mul_floor(u64::MAX), u64::MAX / 2);
+	assert_eq!(Perbill::one().mul_floor(u64::MAX), u64::MAX);
+	assert_eq!(Perbill::zero().mul_ceil(u64::MAX), 0);
+	assert_eq!(Perbill::from_rational(1, 3).mul_floor(3_000_000_000), 999_999_999);
+	assert_eq!(Perbill::from_rational(1, 0), Perbill::one());
+}
+
+#[test]
+fn arithmetic_balance_is_checked() {
+	assert_eq!(Balance(u64::MAX).checked_add(Balance(1)), None);
+	assert_eq!(Balance(1).checked_sub(Balance(2)), None);
+	assert_eq!(Balance(u64::MAX).checked_mul(10), None);
+	assert_eq!(Balance(5).checked_add(Balance(6)), Some(Balance(11)));
+	assert_eq!(Balance(6).checked_sub(Balance(6)), Some(Balance::zero()));
+}
@@ -0,0 +1,203 @@
+//! Collections with a maximum size, for storing lists and maps on chain.
+//!
+//! Every node has to store the whole state and execute every transition. If a collection in the
+//! state can grow without limit, anyone willing to pay for enough transitions can make it large
+//! enough to fill up the nodes' disks, or to make the transitions that iterate over it take longer
+//! than a block allows. Worse, the cost of a transition that touches the collection depends on how
+//! big it has become, so it can't be known, or charged for, ahead of time.
+//!
+//! The types here refuse to grow beyond a bound fixed in their type. Whatever a transition does
+//! with them, its cost is limited by that bound. Operations that would grow the collection past it
+//! fail and hand the rejected value back, and there is deliberately no way to get mutable access to
+//! the underlying collection.
+
+use std::{collections::BTreeMap, ops::Deref};
+
+/// A `Vec` that never holds more than `N` items.
+///
+/// It dereferences to a slice, so all the read only slice methods are available.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct BoundedVec<T, const N: usize>(Vec<T>);
+
+impl<T, const N: usize> BoundedVec<T, N> {
+	/// The most items this vector may hold.
+	pub const BOUND: usize = N;
+
+	/// An empty vector.
+	pub fn new() -> Self {
+		BoundedVec(Vec::new())
+	}
+
+	/// Add an item to the end, unless the vector is already full, in which case the item is
+	/// handed back.
+	pub fn try_push(&mut self, item: T) -> Result<(), T> {
+		if self.0.len() >= N {
+			return Err(item);
+		}
+		self.0.push(item);
+		Ok(())
+	}
+
+	/// Keep only the items for which the predicate is true. Removing items can't break the bound.
+	pub fn retain(&mut self, predicate: impl FnMut(&T) -> bool) {
+		self.0.retain(predicate);
+	}
+
+	/// Whether the vector holds as many items as it may.
+	pub fn is_full(&self) -> bool {
+		self.0.len() >= N
+	}
+
+	/// Take the items out as an ordinary `Vec`.
+	pub fn into_inner(self) -> Vec<T> {
+		self.0
+	}
+}
+
+impl<T, const N: usize> Default for BoundedVec<T, N> {
+	fn default() -> Self {
+		BoundedVec::new()
+	}
+}
+
+impl<T, const N: usize> Deref for BoundedVec<T, N> {
+	type Target = [T];
+
+	fn deref(&self) -> &[T] {
+		&self.0
+	}
+}
+
+/// Fails, handing the items back, if there are more than `N` of them.
+impl<T, const N: usize> TryFrom<Vec<T>> for BoundedVec<T, N> {
+	type Error = Vec<T>;
+
+	fn try_from(items: Vec<T>) -> Result<Self, Vec<T>> {
+		if items.len() > N {
+			return Err(items);
+		}
+		Ok(BoundedVec(items))
+	}
+}
+
+impl<T: PartialEq, const N: usize> PartialEq<Vec<T>> for BoundedVec<T, N> {
+	fn eq(&self, other: &Vec<T>) -> bool {
+		&self.0 == other
+	}
+}
+
+/// A `BTreeMap` that never holds more than `N` entries.
+///
+/// It dereferences to the map itself, so all the read only map methods are available.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct BoundedBTreeMap<K, V, const N: usize>(BTreeMap<K, V>);
+
+impl<K: Ord, V, const N: usize> BoundedBTreeMap<K, V, N> {
+	/// The most entries this map may hold.
+	pub const BOUND: usize = N;
+
+	/// An empty map.
+	pub fn new() -> Self {
+		BoundedBTreeMap(BTreeMap::new())
+	}
+
+	/// Insert an entry, returning the value it replaced if the key was already present. Replacing
+	/// a value always works, but adding a new key to a full map does not, and hands the entry back.
+	pub fn try_insert(&mut self, key: K, value: V) -> Result<Option<V>, (K, V)> {
+		if self.0.len() >= N && !self.0.contains_key(&key) {
+			return Err((key, value));
+		}
+		Ok(self.0.insert(key, value))
+	}
+
+	/// Remove an entry, returning its value if it was present.
+	pub fn remove(&mut self, key: &K) -> Option<V> {
+		self.0.remove(key)
+	}
+
+	/// Mutable access to the value for a key. Changing a value can't break the bound.
+	pub fn get_mut(&mut self, key: &K) -> Option<&mut V> {
+		self.0.get_mut(key)
+	}
+
+	/// Whether the map holds as many entries as it may.
+	pub fn is_full(&self) -> bool {
+		self.0.len() >= N
+	}
+
+	/// Take the entries out as an ordinary `BTreeMap`.
+	pub fn into_inner(self) -> BTreeMap<K, V> {
+		self.0
+	}
+}
+
+impl<K: Ord, V, const N: usize> Default for BoundedBTreeMap<K, V, N> {
+	fn default() -> Self {
+		BoundedBTreeMap::new()
+	}
+}
+
+impl<K, V, const N: usize> Deref for BoundedBTreeMap<K, V, N> {
+	type Target = BTreeMap<K, V>;
+
+	fn deref(&self) -> &BTreeMap<K, V> {
+		&self.0
+	}
+}
+
+/// Fails, handing the entries back, if there are more than `N` of them.
+impl<K, V, const N: usize> TryFrom<BTreeMap<K, V>> for BoundedBTreeMap<K, V, N> {
+	type Error = BTreeMap<K, V>;
+
+	fn try_from(entries: BTreeMap<K, V>) -> Result<Self, BTreeMap<K, V>> {
+		if entries.len() > N {
+			return Err(entries);
+		}
+		Ok(BoundedBTreeMap(entries))
+	}
+}
+
+impl<K: PartialEq, V: PartialEq, const N: usize> PartialEq<BTreeMap<K, V>>
+	for BoundedBTreeMap<K, V, N>
+{
+	fn eq(&self, other: &BTreeMap<K, V>) -> bool {
+		&self.0 == other
+	}
+}
+
+#[test]
+fn bounded_vec_refuses_to_grow_past_its_bound() {
+	let mut items = BoundedVec::<u32, 2>::new();
+	assert_eq!(items.try_push(1), Ok(()));
+	assert_eq!(items.try_push(2), Ok(()));
+	assert!(items.is_full());
+	assert_eq!(items.try_push(3), Err(3));
+	assert_eq!(items, vec![1, 2]);
+
+	items.retain(|item| *item != 1);
+	assert_eq!(items.try_push(3), Ok(()));
+	assert_eq!(items.into_inner(), vec![2, 3]);
+}
+
+#[test]
+fn bounded_vec_from_vec() {
+	assert_eq!(BoundedVec::<u32, 2>::try_from(vec![1, 2]).map(|items| items.len()), Ok(2));
+	assert_eq!(BoundedVec::<u32, 2>::try_from(vec![1, 2, 3]), Err(vec![1, 2, 3]));
+	assert_eq!(BoundedVec::<u32, 2>::BOUND, 2);
+}
+
+#[test]
+fn bounded_btree_map_replaces_but_does_not_add_when_full() {
+	let mut map = BoundedBTreeMap::<u32, char, 2>::new();
+	assert_eq!(map.try_insert(1, 'a'), Ok(None));
+	assert_eq!(map.try_insert(2, 'b'), Ok(None));
+	assert_eq!(map.try_insert(3, 'c'), Err((3, 'c')));
+	assert_eq!(map.try_insert(2, 'B'), Ok(Some('b')));
+	*map.get_mut(&1).unwrap() = 'A';
+	assert_eq!(map, BTreeMap::from([(1, 'A'), (2, 'B')]));
+
+	assert_eq!(map.remove(&1), Some('A'));
+	assert_eq!(map.try_insert(3, 'c'), Ok(None));
+	assert_eq!(map.len(), 2);
+	assert!(BoundedBTreeMap::<u32, char, 1>::try_from(map.into_inner()).is_err());
+}
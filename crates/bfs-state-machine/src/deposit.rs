@@ -0,0 +1,88 @@
+//! Helpers for the deposit pattern used by state machines that store user data on chain.
+//!
+//! Anything stored on chain costs every node space, so users lock up a deposit for as long as their
+//! data is stored. The deposit leaves their free balance when the data is stored, comes back when
+//! they remove it, and is slashed if the data turns out to be abusive. Getting the bookkeeping
+//! right is fiddly, especially when replacing data, so it all lives here.
+//!
+//! Every helper works on a map of free balances, and either succeeds completely or changes nothing.
+
+use super::{arithmetic::Balance, User};
+use std::collections::HashMap;
+
+/// Take a deposit out of the user's free balance. Returns whether it succeeded, which it does not
+/// if they can't afford it.
+pub fn take(balances: &mut HashMap<User, Balance>, who: User, amount: Balance) -> bool {
+	adjust(balances, who, Balance::zero(), amount)
+}
+
+/// Return a deposit to the user's free balance. Returns whether it succeeded, which it does not if
+/// their balance would overflow.
+pub fn refund(balances: &mut HashMap<User, Balance>, who: User, amount: Balance) -> bool {
+	adjust(balances, who, amount, Balance::zero())
+}
+
+/// Replace the user's old deposit with a new one. The old deposit is returned and the new one taken
+/// in a single step, so the user only needs to be able to afford the difference. Returns whether
+/// it succeeded.
+pub fn adjust(
+	balances: &mut HashMap<User, Balance>,
+	who: User,
+	old: Balance,
+	new: Balance,
+) -> bool {
+	let free = balances.get(&who).copied().unwrap_or_default();
+	match free.checked_add(old).and_then(|free| free.checked_sub(new)) {
+		Some(free) => {
+			balances.insert(who, free);
+			true
+		},
+		None => false,
+	}
+}
+
+/// Slash a deposit. It is paid to the beneficiary, such as whoever reported the abuse, or burned if
+/// there is none. Returns whether it succeeded, which it does not if the beneficiary's balance
+/// would overflow. Burning always succeeds.
+pub fn slash(
+	balances: &mut HashMap<User, Balance>,
+	amount: Balance,
+	beneficiary: Option<User>,
+) -> bool {
+	beneficiary.is_none_or(|beneficiary| refund(balances, beneficiary, amount))
+}
+
+#[test]
+fn deposit_take_and_refund() {
+	let mut balances = HashMap::from([(User::Alice, Balance(10))]);
+	assert!(take(&mut balances, User::Alice, Balance(4)));
+	assert_eq!(balances[&User::Alice], Balance(6));
+
+	assert!(!take(&mut balances, User::Alice, Balance(7)));
+	assert!(!take(&mut balances, User::Bob, Balance(1)));
+	assert_eq!(balances, HashMap::from([(User::Alice, Balance(6))]));
+
+	assert!(refund(&mut balances, User::Alice, Balance(4)));
+	assert_eq!(balances[&User::Alice], Balance(10));
+}
+
+#[test]
+fn deposit_adjust_only_needs_the_difference() {
+	let mut balances = HashMap::from([(User::Alice, Balance(3))]);
+	assert!(adjust(&mut balances, User::Alice, Balance(10), Balance(12)));
+	assert_eq!(balances[&User::Alice], Balance(1));
+	assert!(adjust(&mut balances, User::Alice, Balance(12), Balance(5)));
+	assert_eq!(balances[&User::Alice], Balance(8));
+	assert!(!adjust(&mut balances, User::Alice, Balance(5), Balance(14)));
+	assert_eq!(balances[&User::Alice], Balance(8));
+}
+
+#[test]
+fn deposit_slash_pays_beneficiary_or_burns() {
+	let mut balances = HashMap::from([(User::Bob, Balance(u64::MAX))]);
+	assert!(slash(&mut balances, Balance(5), None));
+	assert!(slash(&mut balances, Balance(5), Some(User::Charlie)));
+	assert_eq!(balances[&User::Charlie], Balance(5));
+	assert!(!slash(&mut balances, Balance(5), Some(User::Bob)));
+	assert_eq!(balances[&User::Bob], Balance(u64::MAX));
+}
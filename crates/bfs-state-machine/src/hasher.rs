@@ -0,0 +1,152 @@
+//! Interchangeable hash functions.
+//!
+//! The course hashes everything with [`crate::hash`], which uses the standard library's
+//! `DefaultHasher`. It is fast and its 64 bit output is easy to print and compare, which is why the
+//! lessons use it. But it was built for hash maps, not for security. Its output is far too short to
+//! resist collisions, and nothing about it is meant to be hard to reverse. Real blockchains use
+//! cryptographic hash functions with at least 256 bit outputs instead.
+//!
+//! The [`Hasher`] trait lets code that cares which hash function it uses be generic over it. Three
+//! are provided.
+//! - [`FastHasher`] - the standard library hasher used throughout the course, with a `u64` output
+//! - [`Blake2b256`] - BLAKE2b with a 256 bit output, the hash Polkadot uses
+//! - [`Sha256`] - SHA-256, the hash Bitcoin uses
+//!
+//! Every one of them accepts anything that implements the standard [`Hash`] trait, and hashes the
+//! bytes that its `Hash` implementation writes. Those bytes use the platform's native endianness,
+//! so nodes on platforms with different endianness would disagree about digests. A real chain uses
+//! a byte encoding that is the same everywhere, like the one in the `encoding` module of the
+//! blockchain chapter.
+
+use blake2::{digest::consts::U32, Blake2b, Digest};
+use std::{
+	collections::hash_map::DefaultHasher,
+	fmt,
+	hash::{Hash, Hasher as StdHasher},
+};
+
+/// The output of a [`Hasher`].
+pub trait HashOutput: Copy + Default + Eq + Ord + Hash + fmt::Debug {
+	/// The first 64 bits of the digest, read as a big endian number.
+	///
+	/// Proof of work thresholds are compared against this. For a good hash function the leading
+	/// bits are as random as all the others, so a threshold of `u64::MAX / 100` lets roughly one
+	/// digest in a hundred through, however wide the digests are.
+	fn leading_u64(&self) -> u64;
+}
+
+impl HashOutput for u64 {
+	fn leading_u64(&self) -> u64 {
+		*self
+	}
+}
+
+impl HashOutput for [u8; 32] {
+	fn leading_u64(&self) -> u64 {
+		let mut leading = [0; 8];
+		leading.copy_from_slice(&self[..8]);
+		u64::from_be_bytes(leading)
+	}
+}
+
+/// A hash function that can hash anything implementing the standard [`Hash`] trait.
+///
+/// The implementing types are only markers that are never constructed. They implement all the
+/// common traits anyway, so that types which are generic over the hasher can derive them too.
+pub trait Hasher: Copy + Default + Eq + Ord + Hash + fmt::Debug {
+	/// The digest this hash function produces.
+	type Output: HashOutput;
+
+	/// Hash the given value.
+	fn digest<T: Hash + ?Sized>(t: &T) -> Self::Output;
+}
+
+/// The standard library's `DefaultHasher`. Fast, but not cryptographically secure.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct FastHasher;
+
+impl Hasher for FastHasher {
+	type Output = u64;
+
+	fn digest<T: Hash + ?Sized>(t: &T) -> u64 {
+		let mut s = DefaultHasher::new();
+		t.hash(&mut s);
+		s.finish()
+	}
+}
+
+/// BLAKE2b with a 256 bit output.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Blake2b256;
+
+impl Hasher for Blake2b256 {
+	type Output = [u8; 32];
+
+	fn digest<T: Hash + ?Sized>(t: &T) -> [u8; 32] {
+		digest_of::<Blake2b<U32>, T>(t)
+	}
+}
+
+/// SHA-256.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Sha256;
+
+impl Hasher for Sha256 {
+	type Output = [u8; 32];
+
+	fn digest<T: Hash + ?Sized>(t: &T) -> [u8; 32] {
+		digest_of::<sha2::Sha256, T>(t)
+	}
+}
+
+/// Feeds everything a `Hash` implementation writes into a cryptographic hash function.
+struct DigestWriter<D>(D);
+
+impl<D: Digest + Clone> StdHasher for DigestWriter<D> {
+	fn write(&mut self, bytes: &[u8]) {
+		self.0.update(bytes);
+	}
+
+	fn finish(&self) -> u64 {
+		let digest = self.0.clone().finalize();
+		let mut leading = [0; 8];
+		leading.copy_from_slice(&digest[..8]);
+		u64::from_be_bytes(leading)
+	}
+}
+
+/// The 256 bit digest of the value, using the given cryptographic hash function.
+fn digest_of<D, T>(t: &T) -> [u8; 32]
+where
+	D: Digest<OutputSize = U32> + Clone,
+	T: Hash + ?Sized,
+{
+	let mut writer = DigestWriter(D::new());
+	t.hash(&mut writer);
+	writer.0.finalize().into()
+}
+
+#[test]
+fn hasher_fast_hasher_matches_hash() {
+	assert_eq!(FastHasher::digest(&(1u64, 2u64)), crate::hash(&(1u64, 2u64)));
+}
+
+#[test]
+fn hasher_known_digests() {
+	// A `u32` hashes as its four native endian bytes, so this hashes the bytes "abc\0" on every
+	// platform.
+	let abc = u32::from_ne_bytes(*b"abc\0");
+	let blake2: [u8; 32] = Blake2b::<U32>::digest(b"abc\0").into();
+	let sha2: [u8; 32] = sha2::Sha256::digest(b"abc\0").into();
+	assert_eq!(Blake2b256::digest(&abc), blake2);
+	assert_eq!(Sha256::digest(&abc), sha2);
+	assert_ne!(blake2, sha2);
+}
+
+#[test]
+fn hasher_leading_u64_is_big_endian() {
+	let mut digest = [0xff; 32];
+	digest[..8].copy_from_slice(&[0, 0, 0, 0, 0, 0, 1, 2]);
+	assert_eq!(digest.leading_u64(), 0x0102);
+	assert_eq!(7u64.leading_u64(), 7);
+}
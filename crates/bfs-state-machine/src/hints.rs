@@ -0,0 +1,122 @@
+//! Learning by running the tests is much more pleasant when a failing test tells you _why_ it
+//! failed. This module provides progressive hints that lesson tests can attach to their
+//! assertions. Each exercise may provide several tiers of hints, starting with a gentle nudge and
+//! ending with something close to the solution.
+//!
+//! How many tiers are revealed is controlled by the `BFS_HINT_LEVEL` environment variable.
+//! For example, to see the first two tiers of hints for a failing test in chapter 2 part 5, run:
+//! `BFS_HINT_LEVEL=2 cargo test bc_5`. Setting the level to 0 disables hints entirely.
+
+/// The tiered hints for a single exercise, ordered from least to most revealing.
+pub type Hints = &'static [&'static str];
+
+/// The environment variable used to select how many tiers of hints are revealed.
+pub const HINT_LEVEL_VAR: &str = "BFS_HINT_LEVEL";
+
+/// The number of hint tiers revealed when the environment variable is not set.
+pub const DEFAULT_HINT_LEVEL: usize = 1;
+
+/// Read the hint level from the environment. Values that are not a number fall back to the
+/// default.
+pub fn hint_level() -> usize {
+	std::env::var(HINT_LEVEL_VAR)
+		.ok()
+		.and_then(|level| level.trim().parse().ok())
+		.unwrap_or(DEFAULT_HINT_LEVEL)
+}
+
+/// Build the message shown when an assertion fails. The raw assertion failure is always included,
+/// followed by as many hint tiers as the given level allows. At level 0 hints are disabled, so the
+/// message doesn't mention them at all.
+pub fn failure_message(failure: &str, hints: Hints, level: usize) -> String {
+	let mut message = String::from(failure);
+	if level == 0 {
+		return message;
+	}
+
+	for (tier, hint) in hints.iter().take(level).enumerate() {
+		message.push_str(&format!("\n  hint {}/{}: {}", tier + 1, hints.len(), hint));
+	}
+
+	if level < hints.len() {
+		message.push_str(&format!(
+			"\n  ({} more hint(s) available. Re-run with {}={})",
+			hints.len() - level,
+			HINT_LEVEL_VAR,
+			level + 1,
+		));
+	}
+
+	message
+}
+
+/// Like `assert!`, but when the assertion fails the progressive hints for the exercise are
+/// included in the panic message.
+#[macro_export]
+macro_rules! hint_assert {
+	($cond:expr, $hints:expr $(,)?) => {
+		if !$cond {
+			panic!(
+				"{}",
+				$crate::hints::failure_message(
+					concat!("assertion failed: ", stringify!($cond)),
+					$hints,
+					$crate::hints::hint_level(),
+				)
+			);
+		}
+	};
+}
+
+/// Like `assert_eq!`, but when the assertion fails the progressive hints for the exercise are
+/// included in the panic message.
+#[macro_export]
+macro_rules! hint_assert_eq {
+	($left:expr, $right:expr, $hints:expr $(,)?) => {
+		match (&$left, &$right) {
+			(left, right) =>
+				if !(*left == *right) {
+					panic!(
+						"{}",
+						$crate::hints::failure_message(
+							&format!(
+								"assertion `left == right` failed\n  left: {:?}\n right: {:?}",
+								left, right
+							),
+							$hints,
+							$crate::hints::hint_level(),
+						)
+					);
+				},
+		}
+	};
+}
+
+#[test]
+fn hints_level_zero_shows_only_failure() {
+	let message = failure_message("assertion failed", &["first", "second"], 0);
+	assert_eq!(message, "assertion failed");
+}
+
+#[test]
+fn hints_are_revealed_progressively() {
+	let hints: Hints = &["first", "second", "third"];
+
+	let message = failure_message("assertion failed", hints, 2);
+	assert!(message.contains("hint 1/3: first"));
+	assert!(message.contains("hint 2/3: second"));
+	assert!(!message.contains("third"));
+	assert!(message.contains("BFS_HINT_LEVEL=3"));
+}
+
+#[test]
+fn hints_level_beyond_available_shows_all() {
+	let message = failure_message("assertion failed", &["first"], 5);
+	assert_eq!(message, "assertion failed\n  hint 1/1: first");
+}
+
+#[test]
+#[should_panic(expected = "assertion failed: 1 + 1 == 3")]
+fn hints_macro_reports_failed_condition() {
+	hint_assert!(1 + 1 == 3, &["look here"]);
+}
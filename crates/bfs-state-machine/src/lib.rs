@@ -0,0 +1,105 @@
+//! This module is all about modeling phenomena and systems as state machines.
+//! We begin with a few simple examples, and then proceed to build bigger and more complex state
+//! machines all implementing the same simple interface.
+//!
+//! This is the first chapter of the course, so it also hosts the few helpers that every later
+//! chapter relies on.
+
+use std::{fmt, hash::Hash, str::FromStr};
+
+#[doc(hidden)]
+#[macro_use]
+pub mod hints;
+
+pub mod arithmetic;
+pub mod bounded;
+pub mod deposit;
+pub mod hasher;
+pub mod time;
+
+mod p10_council;
+mod p11_identity;
+mod p12_recovery;
+mod p13_dev_chain;
+mod p1_switches;
+mod p2_laundry_machine;
+mod p3_atm;
+mod p4_accounted_currency;
+mod p5_digital_cash;
+mod p6_open_ended;
+mod p7_rate_limited;
+mod p8_authorized;
+mod p9_inflation;
+
+/// Simple helper to do some hashing. It uses the fast, but insecure, [`hasher::FastHasher`]. See
+/// the [`hasher`] module for cryptographic alternatives.
+pub fn hash<T: Hash>(t: &T) -> u64 {
+	<hasher::FastHasher as hasher::Hasher>::digest(t)
+}
+
+/// A state machine - Generic over the transition type
+pub trait StateMachine {
+	/// The states that can be occupied by this machine
+	type State;
+
+	/// The transitions that can be made between states
+	type Transition;
+
+	/// Calculate the resulting state when this state undergoes the given transition
+	fn next_state(starting_state: &Self::State, t: &Self::Transition) -> Self::State;
+
+	/// A human-readable name for this state machine. This may be used in user-facing
+	/// programs such as the repl described below. This is not in any way related to
+	/// the correctness of the state machine.
+	fn human_name() -> String {
+		"Unnamed state machine".into()
+	}
+}
+
+/// A set of play users for experimenting with the multi-user state machines
+#[derive(Hash, Eq, PartialEq, Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum User {
+	Alice,
+	Bob,
+	Charlie,
+}
+
+impl User {
+	/// Every play user, in order.
+	pub const ALL: [User; 3] = [User::Alice, User::Bob, User::Charlie];
+}
+
+impl fmt::Display for User {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		fmt::Debug::fmt(self, f)
+	}
+}
+
+/// The error returned when parsing a name that is not one of the play users.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct UnknownUser(pub String);
+
+impl FromStr for User {
+	type Err = UnknownUser;
+
+	/// Parse a user from their name, ignoring case, so that `bob` and `Bob` are the same user.
+	fn from_str(s: &str) -> Result<Self, UnknownUser> {
+		User::ALL
+			.into_iter()
+			.find(|user| user.to_string().eq_ignore_ascii_case(s))
+			.ok_or_else(|| UnknownUser(s.to_string()))
+	}
+}
+
+#[test]
+fn user_names_round_trip() {
+	for user in User::ALL {
+		assert_eq!(user.to_string().parse(), Ok(user));
+	}
+	assert_eq!("charlie".parse(), Ok(User::Charlie));
+	assert_eq!("Dave".parse::<User>(), Err(UnknownUser("Dave".into())));
+}
+
+// TODO Some kind of main program that allows users to interact with their state machine in a
+// repl-like way. Might require From<String> implementation for the transition type.
@@ -0,0 +1,351 @@
+//! Some decisions are too important to leave to a single root account, but too frequent to put to a
+//! vote of every token holder. A common middle ground is a council: a small group of members who
+//! propose motions and vote on them. Once enough members vote aye, the motion is dispatched with
+//! the special `Council` origin, which the underlying machine's policy may grant extra powers.
+//!
+//! Like the previous lessons, the council is written as a wrapper so that it can govern any
+//! machine that uses origins.
+
+use super::{
+	bounded::{BoundedBTreeMap, BoundedVec},
+	p8_authorized::{Authorized, Call, Origin, OriginPolicy},
+	StateMachine, User,
+};
+use std::marker::PhantomData;
+
+/// A council governing the state machine `SM`, whose transitions are checked by `Policy`.
+pub struct Council<SM, Policy>(PhantomData<(SM, Policy)>);
+
+/// Identifies a motion for as long as it is open.
+pub type MotionIndex = u32;
+
+/// The most members a council may have.
+pub const MAX_MEMBERS: usize = 16;
+
+/// The most motions that may be open at once.
+pub const MAX_MOTIONS: usize = 8;
+
+/// A proposed transition that the council is voting on.
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct Motion<T> {
+	/// The transition to dispatch if the motion passes
+	pub call: T,
+	/// How many aye votes are needed for the motion to pass
+	pub threshold: u32,
+	/// The members who voted for the motion
+	pub ayes: BoundedVec<User, MAX_MEMBERS>,
+	/// The members who voted against the motion
+	pub nays: BoundedVec<User, MAX_MEMBERS>,
+}
+
+/// The state of the council along with the machine it governs.
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct CouncilState<S, T> {
+	/// The council members. Only they can propose motions or vote.
+	pub members: BoundedVec<User, MAX_MEMBERS>,
+	/// The motions that have been proposed and not yet closed
+	pub motions: BoundedBTreeMap<MotionIndex, Motion<T>, MAX_MOTIONS>,
+	/// The index the next motion will get
+	pub next_index: MotionIndex,
+	/// The state of the governed machine
+	pub inner: S,
+}
+
+/// The things that can happen in a council governed machine.
+pub enum CouncilTransition<T> {
+	/// A member proposes a motion. The proposer automatically votes aye. Proposals by non-members,
+	/// proposals whose threshold could never be reached, and proposals made while the maximum
+	/// number of motions are open, are ignored.
+	Propose { proposer: User, threshold: u32, call: T },
+	/// A member votes on an open motion. Members may change their vote while the motion is open.
+	Vote { voter: User, index: MotionIndex, aye: bool },
+	/// Anyone may close a motion once its outcome is certain. If it has enough ayes, it is
+	/// dispatched with the council origin. If it can no longer get enough ayes, it is dropped.
+	/// Motions whose outcome is still uncertain stay open.
+	Close { index: MotionIndex },
+	/// An ordinary transition of the governed machine, made with its own origin. Only the council
+	/// itself can produce the council origin, so calls that claim it are ignored.
+	Inner(Call<T>),
+}
+
+impl<S, T> CouncilState<S, T> {
+	/// Whether the given motion has enough ayes to pass.
+	pub fn is_approved(&self, index: MotionIndex) -> bool {
+		self.motions
+			.get(&index)
+			.is_some_and(|motion| motion.ayes.len() as u32 >= motion.threshold)
+	}
+
+	/// Whether the given motion can no longer get enough ayes, even if all the remaining members
+	/// vote aye.
+	pub fn is_rejected(&self, index: MotionIndex) -> bool {
+		self.motions.get(&index).is_some_and(|motion| {
+			self.members.len().saturating_sub(motion.nays.len()) < motion.threshold as usize
+		})
+	}
+}
+
+impl<SM, Policy> StateMachine for Council<SM, Policy>
+where
+	SM: StateMachine,
+	SM::State: Clone,
+	SM::Transition: Clone,
+	Policy: OriginPolicy<SM>,
+{
+	type State = CouncilState<SM::State, SM::Transition>;
+	type Transition = CouncilTransition<SM::Transition>;
+
+	fn next_state(starting_state: &Self::State, t: &Self::Transition) -> Self::State {
+		let mut new_state = starting_state.clone();
+		match t {
+			CouncilTransition::Propose { proposer, threshold, call } => {
+				let reachable = (*threshold as usize) <= starting_state.members.len();
+				if starting_state.members.contains(proposer) && reachable {
+					let motion = Motion {
+						call: call.clone(),
+						threshold: *threshold,
+						ayes: vec![*proposer].try_into().expect("a council has room for one vote"),
+						nays: BoundedVec::new(),
+					};
+					if new_state.motions.try_insert(starting_state.next_index, motion).is_ok() {
+						new_state.next_index = starting_state.next_index.wrapping_add(1);
+					}
+				}
+			},
+			CouncilTransition::Vote { voter, index, aye } => {
+				if !starting_state.members.contains(voter) {
+					return new_state;
+				}
+				if let Some(motion) = new_state.motions.get_mut(index) {
+					motion.ayes.retain(|member| member != voter);
+					motion.nays.retain(|member| member != voter);
+					// Each member votes at most once, so there is always room.
+					let votes = if *aye { &mut motion.ayes } else { &mut motion.nays };
+					votes.try_push(*voter).expect("there are no more votes than members");
+				}
+			},
+			CouncilTransition::Close { index } =>
+				if starting_state.is_approved(*index) {
+					let motion = new_state.motions.remove(index).expect("approved motions exist");
+					new_state.inner = Authorized::<SM, Policy>::next_state(
+						&starting_state.inner,
+						&Call { origin: Origin::Council, transition: motion.call },
+					);
+				} else if starting_state.is_rejected(*index) {
+					new_state.motions.remove(index);
+				},
+			CouncilTransition::Inner(call) =>
+				if call.origin != Origin::Council {
+					new_state.inner =
+						Authorized::<SM, Policy>::next_state(&starting_state.inner, call);
+				},
+		}
+		new_state
+	}
+
+	fn human_name() -> String {
+		format!("Council governed {}", SM::human_name())
+	}
+}
+
+#[cfg(test)]
+use super::{
+	arithmetic::Balance,
+	p4_accounted_currency::{AccountedCurrency, AccountingTransaction},
+	p8_authorized::CurrencyPolicy,
+};
+#[cfg(test)]
+use std::collections::{BTreeMap, HashMap};
+
+#[cfg(test)]
+type CouncilCurrency = Council<AccountedCurrency, CurrencyPolicy>;
+
+#[cfg(test)]
+fn mint_for_charlie() -> AccountingTransaction {
+	AccountingTransaction::Mint { minter: User::Charlie, amount: Balance(50) }
+}
+
+#[cfg(test)]
+fn council_of_alice_and_bob() -> CouncilState<HashMap<User, Balance>, AccountingTransaction> {
+	CouncilState {
+		members: vec![User::Alice, User::Bob].try_into().unwrap(),
+		motions: BoundedBTreeMap::new(),
+		next_index: 0,
+		inner: HashMap::new(),
+	}
+}
+
+#[test]
+fn sm_10_member_proposes() {
+	let start = council_of_alice_and_bob();
+	let end = CouncilCurrency::next_state(
+		&start,
+		&CouncilTransition::Propose {
+			proposer: User::Alice,
+			threshold: 2,
+			call: mint_for_charlie(),
+		},
+	);
+
+	let expected_motion = Motion {
+		call: mint_for_charlie(),
+		threshold: 2,
+		ayes: vec![User::Alice].try_into().unwrap(),
+		nays: BoundedVec::new(),
+	};
+	assert_eq!(end.motions, BTreeMap::from([(0, expected_motion)]));
+	assert_eq!(end.next_index, 1);
+}
+
+#[test]
+fn sm_10_non_member_cannot_propose_or_vote() {
+	let start = council_of_alice_and_bob();
+	let end = CouncilCurrency::next_state(
+		&start,
+		&CouncilTransition::Propose {
+			proposer: User::Charlie,
+			threshold: 1,
+			call: mint_for_charlie(),
+		},
+	);
+	assert_eq!(end, start);
+
+	let proposed = CouncilCurrency::next_state(
+		&start,
+		&CouncilTransition::Propose {
+			proposer: User::Alice,
+			threshold: 2,
+			call: mint_for_charlie(),
+		},
+	);
+	let end = CouncilCurrency::next_state(
+		&proposed,
+		&CouncilTransition::Vote { voter: User::Charlie, index: 0, aye: true },
+	);
+	assert_eq!(end, proposed);
+}
+
+#[test]
+fn sm_10_unreachable_threshold_is_ignored() {
+	let start = council_of_alice_and_bob();
+	let end = CouncilCurrency::next_state(
+		&start,
+		&CouncilTransition::Propose {
+			proposer: User::Alice,
+			threshold: 3,
+			call: mint_for_charlie(),
+		},
+	);
+	assert_eq!(end, start);
+}
+
+#[test]
+fn sm_10_open_motions_are_bounded() {
+	let mut state = council_of_alice_and_bob();
+	let propose = CouncilTransition::Propose {
+		proposer: User::Alice,
+		threshold: 2,
+		call: mint_for_charlie(),
+	};
+	for _ in 0..MAX_MOTIONS {
+		state = CouncilCurrency::next_state(&state, &propose);
+	}
+	assert!(state.motions.is_full());
+
+	let end = CouncilCurrency::next_state(&state, &propose);
+	assert_eq!(end, state);
+}
+
+#[test]
+fn sm_10_approved_motion_dispatches_with_council_origin() {
+	let mut state = council_of_alice_and_bob();
+	state = CouncilCurrency::next_state(
+		&state,
+		&CouncilTransition::Propose {
+			proposer: User::Alice,
+			threshold: 2,
+			call: mint_for_charlie(),
+		},
+	);
+
+	// Closing too early does nothing.
+	let early = CouncilCurrency::next_state(&state, &CouncilTransition::Close { index: 0 });
+	assert_eq!(early, state);
+
+	state = CouncilCurrency::next_state(
+		&state,
+		&CouncilTransition::Vote { voter: User::Bob, index: 0, aye: true },
+	);
+	state = CouncilCurrency::next_state(&state, &CouncilTransition::Close { index: 0 });
+
+	assert!(state.motions.is_empty());
+	assert_eq!(state.inner, HashMap::from([(User::Charlie, Balance(50))]));
+}
+
+#[test]
+fn sm_10_rejected_motion_is_dropped() {
+	let mut state = council_of_alice_and_bob();
+	state = CouncilCurrency::next_state(
+		&state,
+		&CouncilTransition::Propose {
+			proposer: User::Alice,
+			threshold: 2,
+			call: mint_for_charlie(),
+		},
+	);
+	state = CouncilCurrency::next_state(
+		&state,
+		&CouncilTransition::Vote { voter: User::Bob, index: 0, aye: false },
+	);
+	state = CouncilCurrency::next_state(&state, &CouncilTransition::Close { index: 0 });
+
+	assert!(state.motions.is_empty());
+	assert!(state.inner.is_empty());
+}
+
+#[test]
+fn sm_10_members_can_change_their_vote() {
+	let mut state = council_of_alice_and_bob();
+	state = CouncilCurrency::next_state(
+		&state,
+		&CouncilTransition::Propose {
+			proposer: User::Alice,
+			threshold: 1,
+			call: mint_for_charlie(),
+		},
+	);
+	state = CouncilCurrency::next_state(
+		&state,
+		&CouncilTransition::Vote { voter: User::Alice, index: 0, aye: false },
+	);
+
+	let motion = &state.motions[&0];
+	assert!(motion.ayes.is_empty());
+	assert_eq!(motion.nays, vec![User::Alice]);
+}
+
+#[test]
+fn sm_10_ordinary_calls_use_their_own_origin() {
+	let state = council_of_alice_and_bob();
+	let end = CouncilCurrency::next_state(
+		&state,
+		&CouncilTransition::Inner(Call {
+			origin: Origin::Signed(User::Alice),
+			transition: mint_for_charlie(),
+		}),
+	);
+	assert_eq!(end, state);
+
+	// Only a passed motion may use the council origin.
+	let end = CouncilCurrency::next_state(
+		&state,
+		&CouncilTransition::Inner(Call { origin: Origin::Council, transition: mint_for_charlie() }),
+	);
+	assert_eq!(end, state);
+
+	let end = CouncilCurrency::next_state(
+		&state,
+		&CouncilTransition::Inner(Call { origin: Origin::Root, transition: mint_for_charlie() }),
+	);
+	assert_eq!(end.inner, HashMap::from([(User::Charlie, Balance(50))]));
+}
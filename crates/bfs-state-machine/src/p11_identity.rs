@@ -0,0 +1,293 @@
+//! Accounts on a blockchain are just public keys, which makes it hard to know who you are dealing
+//! with. An identity registry lets users attach some human readable information to their account,
+//! and lets trusted registrars judge whether that information is accurate.
+//!
+//! Anything stored on chain costs every node space forever, so the registry is careful about two
+//! things. First, every field has a maximum length. Second, users must lock up a deposit that grows
+//! with the amount of data they store. The deposit is returned when they clear their identity, but
+//! if a registrar finds the data to be erroneous, the deposit is slashed instead.
+
+use super::{arithmetic::Balance, bounded::BoundedVec, deposit, StateMachine, User};
+use std::collections::HashMap;
+
+/// The most bytes any single identity field may hold.
+pub const MAX_FIELD_LENGTH: usize = 32;
+
+/// The most registrars the registry may have.
+pub const MAX_REGISTRARS: usize = 20;
+
+/// The deposit required for any identity at all.
+pub const BASIC_DEPOSIT: Balance = Balance(10);
+
+/// The additional deposit required for every byte of identity data.
+pub const BYTE_DEPOSIT: Balance = Balance(1);
+
+/// This state machine models an on-chain identity registry.
+pub struct IdentityRegistry;
+
+/// The information a user can attach to their account.
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct IdentityInfo {
+	/// The name to show for this account
+	pub display: String,
+	/// An optional contact email address
+	pub email: Option<String>,
+}
+
+impl IdentityInfo {
+	/// The number of bytes of data in all the fields.
+	pub fn len(&self) -> usize {
+		self.display.len() + self.email.as_ref().map_or(0, String::len)
+	}
+
+	/// Whether all the fields are empty.
+	pub fn is_empty(&self) -> bool {
+		self.len() == 0
+	}
+
+	/// Whether every field fits within the maximum length.
+	pub fn is_within_bounds(&self) -> bool {
+		self.display.len() <= MAX_FIELD_LENGTH &&
+			self.email.as_ref().is_none_or(|email| email.len() <= MAX_FIELD_LENGTH)
+	}
+
+	/// The deposit required to store this information.
+	pub fn deposit(&self) -> Option<Balance> {
+		BYTE_DEPOSIT.checked_mul(self.len() as u64)?.checked_add(BASIC_DEPOSIT)
+	}
+}
+
+/// A registrar's opinion of an identity.
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub enum Judgement {
+	/// The information looks plausible, but has not been checked in depth.
+	Reasonable,
+	/// The registrar has checked the information and it is correct.
+	KnownGood,
+	/// The information is wrong or abusive. Giving this judgement slashes the deposit.
+	Erroneous,
+}
+
+/// An identity that has been set, along with everything attached to it.
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct Registration {
+	/// The information itself
+	pub info: IdentityInfo,
+	/// The deposit locked up for this identity
+	pub deposit: Balance,
+	/// Judgements given by registrars, at most one per registrar
+	pub judgements: BoundedVec<(User, Judgement), MAX_REGISTRARS>,
+}
+
+/// The state of the registry.
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct Registry {
+	/// The free balance of each user. Deposits are taken from here.
+	pub balances: HashMap<User, Balance>,
+	/// The users who may give judgements
+	pub registrars: BoundedVec<User, MAX_REGISTRARS>,
+	/// The identities that have been set
+	pub identities: HashMap<User, Registration>,
+}
+
+/// The things users and registrars can do with the registry.
+pub enum IdentityTransition {
+	/// Set or replace a user's identity. The deposit is adjusted to match the new information, and
+	/// any existing judgements are cleared because they were about the old information. Nothing
+	/// happens if a field is too long or the user can't afford the deposit.
+	SetIdentity { who: User, info: IdentityInfo },
+	/// Remove a user's identity and return their deposit.
+	ClearIdentity { who: User },
+	/// A registrar judges a user's identity. An erroneous judgement removes the identity and
+	/// slashes the deposit. Nothing happens if the registrar is not a registrar or the user has no
+	/// identity.
+	ProvideJudgement { registrar: User, target: User, judgement: Judgement },
+}
+
+impl StateMachine for IdentityRegistry {
+	type State = Registry;
+	type Transition = IdentityTransition;
+
+	fn next_state(starting_state: &Registry, t: &IdentityTransition) -> Registry {
+		let mut new_state = starting_state.clone();
+		match t {
+			IdentityTransition::SetIdentity { who, info } => {
+				if !info.is_within_bounds() {
+					return new_state;
+				}
+				let Some(deposit) = info.deposit() else {
+					return new_state;
+				};
+				let old_deposit =
+					starting_state.identities.get(who).map_or(Balance::zero(), |r| r.deposit);
+				if !deposit::adjust(&mut new_state.balances, *who, old_deposit, deposit) {
+					return new_state;
+				}
+				new_state.identities.insert(
+					*who,
+					Registration { info: info.clone(), deposit, judgements: BoundedVec::new() },
+				);
+			},
+			IdentityTransition::ClearIdentity { who } => {
+				if let Some(registration) = new_state.identities.remove(who) {
+					if !deposit::refund(&mut new_state.balances, *who, registration.deposit) {
+						// The refund doesn't fit, so the identity stays as it was.
+						return starting_state.clone();
+					}
+				}
+			},
+			IdentityTransition::ProvideJudgement { registrar, target, judgement } => {
+				if !starting_state.registrars.contains(registrar) {
+					return new_state;
+				}
+				if *judgement == Judgement::Erroneous {
+					if let Some(registration) = new_state.identities.remove(target) {
+						// Burning always succeeds.
+						deposit::slash(&mut new_state.balances, registration.deposit, None);
+					}
+				} else if let Some(registration) = new_state.identities.get_mut(target) {
+					registration.judgements.retain(|(r, _)| r != registrar);
+					// Each registrar judges at most once, so there is always room.
+					registration
+						.judgements
+						.try_push((*registrar, *judgement))
+						.expect("there are no more judgements than registrars");
+				}
+			},
+		}
+		new_state
+	}
+
+	fn human_name() -> String {
+		"Identity registry".into()
+	}
+}
+
+#[cfg(test)]
+fn alice_info() -> IdentityInfo {
+	IdentityInfo { display: "Alice".into(), email: Some("alice@example.com".into()) }
+}
+
+#[cfg(test)]
+fn registry() -> Registry {
+	Registry {
+		balances: HashMap::from([(User::Alice, Balance(100))]),
+		registrars: vec![User::Charlie].try_into().unwrap(),
+		identities: HashMap::new(),
+	}
+}
+
+#[test]
+fn sm_11_set_identity_takes_deposit() {
+	let end = IdentityRegistry::next_state(
+		&registry(),
+		&IdentityTransition::SetIdentity { who: User::Alice, info: alice_info() },
+	);
+
+	// 10 for the identity plus 1 for each of the 22 bytes.
+	let registration = &end.identities[&User::Alice];
+	assert_eq!(registration.deposit, Balance(32));
+	assert_eq!(end.balances[&User::Alice], Balance(68));
+}
+
+#[test]
+fn sm_11_fields_are_bounded() {
+	let start = registry();
+	let info = IdentityInfo { display: "A".repeat(MAX_FIELD_LENGTH + 1), email: None };
+	let end = IdentityRegistry::next_state(
+		&start,
+		&IdentityTransition::SetIdentity { who: User::Alice, info },
+	);
+	assert_eq!(end, start);
+}
+
+#[test]
+fn sm_11_deposit_must_be_affordable() {
+	let start = Registry { balances: HashMap::from([(User::Alice, Balance(31))]), ..registry() };
+	let end = IdentityRegistry::next_state(
+		&start,
+		&IdentityTransition::SetIdentity { who: User::Alice, info: alice_info() },
+	);
+	assert_eq!(end, start);
+}
+
+#[test]
+fn sm_11_replacing_identity_adjusts_deposit_and_clears_judgements() {
+	let mut state = IdentityRegistry::next_state(
+		&registry(),
+		&IdentityTransition::SetIdentity { who: User::Alice, info: alice_info() },
+	);
+	state = IdentityRegistry::next_state(
+		&state,
+		&IdentityTransition::ProvideJudgement {
+			registrar: User::Charlie,
+			target: User::Alice,
+			judgement: Judgement::KnownGood,
+		},
+	);
+	assert_eq!(
+		state.identities[&User::Alice].judgements,
+		vec![(User::Charlie, Judgement::KnownGood)]
+	);
+
+	let shorter = IdentityInfo { display: "Al".into(), email: None };
+	state = IdentityRegistry::next_state(
+		&state,
+		&IdentityTransition::SetIdentity { who: User::Alice, info: shorter },
+	);
+	assert_eq!(state.identities[&User::Alice].deposit, Balance(12));
+	assert!(state.identities[&User::Alice].judgements.is_empty());
+	assert_eq!(state.balances[&User::Alice], Balance(88));
+}
+
+#[test]
+fn sm_11_clear_identity_returns_deposit() {
+	let set = IdentityRegistry::next_state(
+		&registry(),
+		&IdentityTransition::SetIdentity { who: User::Alice, info: alice_info() },
+	);
+	let end =
+		IdentityRegistry::next_state(&set, &IdentityTransition::ClearIdentity { who: User::Alice });
+	assert_eq!(end, registry());
+}
+
+#[test]
+fn sm_11_only_registrars_judge() {
+	let set = IdentityRegistry::next_state(
+		&registry(),
+		&IdentityTransition::SetIdentity { who: User::Alice, info: alice_info() },
+	);
+	let end = IdentityRegistry::next_state(
+		&set,
+		&IdentityTransition::ProvideJudgement {
+			registrar: User::Bob,
+			target: User::Alice,
+			judgement: Judgement::Erroneous,
+		},
+	);
+	assert_eq!(end, set);
+}
+
+#[test]
+fn sm_11_erroneous_judgement_slashes_deposit() {
+	let set = IdentityRegistry::next_state(
+		&registry(),
+		&IdentityTransition::SetIdentity { who: User::Alice, info: alice_info() },
+	);
+	let judged = IdentityRegistry::next_state(
+		&set,
+		&IdentityTransition::ProvideJudgement {
+			registrar: User::Charlie,
+			target: User::Alice,
+			judgement: Judgement::Erroneous,
+		},
+	);
+	assert!(judged.identities.is_empty());
+
+	// Clearing afterwards doesn't bring the deposit back.
+	let end = IdentityRegistry::next_state(
+		&judged,
+		&IdentityTransition::ClearIdentity { who: User::Alice },
+	);
+	assert_eq!(end.balances[&User::Alice], Balance(68));
+}
@@ -0,0 +1,325 @@
+//! Losing the key to a blockchain account usually means losing the account forever. Social
+//! recovery offers a way out. Ahead of time, the account owner chooses some friends and a
+//! threshold. If the key is ever lost, the owner makes a new key and asks their friends to vouch
+//! for it. Once enough friends have vouched, and a delay has passed, the new key gains control of
+//! the old account.
+//!
+//! The delay is what keeps this safe. If a group of malicious friends tries to take over an account
+//! whose owner still has their key, the owner has the whole delay period to notice and close the
+//! recovery attempt.
+
+use super::{bounded::BoundedVec, StateMachine, User};
+use std::collections::{HashMap, HashSet};
+
+/// The most friends an account may choose.
+pub const MAX_FRIENDS: usize = 9;
+
+/// This state machine models social recovery of lost accounts.
+pub struct SocialRecovery;
+
+/// How an account may be recovered.
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct RecoveryConfig {
+	/// The users who may vouch for a rescuer
+	pub friends: BoundedVec<User, MAX_FRIENDS>,
+	/// How many friends must vouch before the account can be claimed
+	pub threshold: u32,
+	/// How many blocks must pass between starting a recovery and claiming the account
+	pub delay: u64,
+}
+
+/// A recovery attempt in progress.
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct ActiveRecovery {
+	/// The block at which the attempt started
+	pub started: u64,
+	/// The friends who have vouched for the rescuer so far
+	pub vouches: BoundedVec<User, MAX_FRIENDS>,
+}
+
+/// The state of the recovery system.
+#[derive(PartialEq, Eq, Debug, Clone, Default)]
+pub struct RecoveryState {
+	/// The current block number
+	pub now: u64,
+	/// The recovery configuration of each account that has one
+	pub configs: HashMap<User, RecoveryConfig>,
+	/// Recovery attempts in progress, keyed by the lost account and then the rescuer
+	pub active: HashMap<(User, User), ActiveRecovery>,
+	/// Accounts that have been recovered. Maps each rescuer to the account it now controls.
+	pub recovered: HashMap<User, User>,
+}
+
+/// The things that can happen during social recovery. The first user named in each transition is
+/// the one who signed it.
+pub enum RecoveryTransition {
+	/// An account owner chooses their friends. There may be at most [`MAX_FRIENDS`] of them, each
+	/// named only once, the threshold must be between one and the number of friends, and an
+	/// account can't be its own friend. An existing configuration can't be changed.
+	CreateRecovery { account: User, friends: Vec<User>, threshold: u32, delay: u64 },
+	/// A rescuer, usually the owner's new key, starts an attempt to recover a lost account.
+	InitiateRecovery { rescuer: User, lost: User },
+	/// A friend of the lost account vouches for a rescuer.
+	Vouch { friend: User, lost: User, rescuer: User },
+	/// The rescuer takes control of the lost account. Only possible once enough friends have
+	/// vouched and the delay has passed.
+	ClaimRecovery { rescuer: User, lost: User },
+	/// The owner of an account that is not actually lost cancels a recovery attempt.
+	CloseRecovery { lost: User, rescuer: User },
+	/// A new block is produced.
+	Tick,
+}
+
+impl StateMachine for SocialRecovery {
+	type State = RecoveryState;
+	type Transition = RecoveryTransition;
+
+	fn next_state(starting_state: &RecoveryState, t: &RecoveryTransition) -> RecoveryState {
+		let mut new_state = starting_state.clone();
+		match t {
+			RecoveryTransition::CreateRecovery { account, friends, threshold, delay } => {
+				let Ok(friends) = BoundedVec::try_from(friends.clone()) else {
+					return new_state;
+				};
+				let valid_threshold = *threshold >= 1 && *threshold as usize <= friends.len();
+				// Each friend vouches only once, so a repeated friend could make the threshold
+				// unreachable.
+				let distinct = friends.iter().collect::<HashSet<_>>().len() == friends.len();
+				if valid_threshold &&
+					distinct && !friends.contains(account) &&
+					!starting_state.configs.contains_key(account)
+				{
+					let config = RecoveryConfig { friends, threshold: *threshold, delay: *delay };
+					new_state.configs.insert(*account, config);
+				}
+			},
+			RecoveryTransition::InitiateRecovery { rescuer, lost } => {
+				let key = (*lost, *rescuer);
+				if rescuer != lost &&
+					starting_state.configs.contains_key(lost) &&
+					!starting_state.active.contains_key(&key)
+				{
+					new_state.active.insert(
+						key,
+						ActiveRecovery { started: starting_state.now, vouches: BoundedVec::new() },
+					);
+				}
+			},
+			RecoveryTransition::Vouch { friend, lost, rescuer } => {
+				let Some(config) = starting_state.configs.get(lost) else {
+					return new_state;
+				};
+				if let Some(attempt) = new_state.active.get_mut(&(*lost, *rescuer)) {
+					if config.friends.contains(friend) && !attempt.vouches.contains(friend) {
+						// Only friends vouch, and only once each, so there is always room.
+						attempt
+							.vouches
+							.try_push(*friend)
+							.expect("there are no more vouches than friends");
+					}
+				}
+			},
+			RecoveryTransition::ClaimRecovery { rescuer, lost } => {
+				let key = (*lost, *rescuer);
+				let (Some(config), Some(attempt)) =
+					(starting_state.configs.get(lost), starting_state.active.get(&key))
+				else {
+					return new_state;
+				};
+				let enough_vouches = attempt.vouches.len() >= config.threshold as usize;
+				let delay_passed =
+					starting_state.now >= attempt.started.saturating_add(config.delay);
+				if enough_vouches && delay_passed {
+					new_state.active.remove(&key);
+					new_state.recovered.insert(*rescuer, *lost);
+				}
+			},
+			RecoveryTransition::CloseRecovery { lost, rescuer } => {
+				new_state.active.remove(&(*lost, *rescuer));
+			},
+			RecoveryTransition::Tick => {
+				new_state.now = starting_state.now.saturating_add(1);
+			},
+		}
+		new_state
+	}
+
+	fn human_name() -> String {
+		"Social recovery".into()
+	}
+}
+
+/// Alice has made Bob and Charlie her friends. Both must vouch, and the delay is 10 blocks.
+#[cfg(test)]
+fn alice_configured() -> RecoveryState {
+	SocialRecovery::next_state(
+		&RecoveryState::default(),
+		&RecoveryTransition::CreateRecovery {
+			account: User::Alice,
+			friends: vec![User::Bob, User::Charlie],
+			threshold: 2,
+			delay: 10,
+		},
+	)
+}
+
+/// Bob claims to be Alice's new key, and both friends vouch for him.
+#[cfg(test)]
+fn bob_vouched_for() -> RecoveryState {
+	let mut state = alice_configured();
+	for transition in [
+		RecoveryTransition::InitiateRecovery { rescuer: User::Bob, lost: User::Alice },
+		RecoveryTransition::Vouch { friend: User::Bob, lost: User::Alice, rescuer: User::Bob },
+		RecoveryTransition::Vouch { friend: User::Charlie, lost: User::Alice, rescuer: User::Bob },
+	] {
+		state = SocialRecovery::next_state(&state, &transition);
+	}
+	state
+}
+
+#[cfg(test)]
+fn after_blocks(mut state: RecoveryState, blocks: u64) -> RecoveryState {
+	for _ in 0..blocks {
+		state = SocialRecovery::next_state(&state, &RecoveryTransition::Tick);
+	}
+	state
+}
+
+#[test]
+fn sm_12_invalid_configs_are_rejected() {
+	let start = RecoveryState::default();
+	for transition in [
+		RecoveryTransition::CreateRecovery {
+			account: User::Alice,
+			friends: vec![User::Bob],
+			threshold: 2,
+			delay: 0,
+		},
+		RecoveryTransition::CreateRecovery {
+			account: User::Alice,
+			friends: vec![User::Bob],
+			threshold: 0,
+			delay: 0,
+		},
+		RecoveryTransition::CreateRecovery {
+			account: User::Alice,
+			friends: vec![User::Alice],
+			threshold: 1,
+			delay: 0,
+		},
+		RecoveryTransition::CreateRecovery {
+			account: User::Alice,
+			friends: vec![User::Bob; MAX_FRIENDS + 1],
+			threshold: 1,
+			delay: 0,
+		},
+	] {
+		assert_eq!(SocialRecovery::next_state(&start, &transition), start);
+	}
+}
+
+#[test]
+fn sm_12_duplicate_friends_are_rejected() {
+	let start = RecoveryState::default();
+	let end = SocialRecovery::next_state(
+		&start,
+		&RecoveryTransition::CreateRecovery {
+			account: User::Alice,
+			friends: vec![User::Bob, User::Bob],
+			threshold: 2,
+			delay: 0,
+		},
+	);
+	assert_eq!(end, start);
+}
+
+#[test]
+fn sm_12_config_cannot_be_replaced() {
+	let start = alice_configured();
+	let end = SocialRecovery::next_state(
+		&start,
+		&RecoveryTransition::CreateRecovery {
+			account: User::Alice,
+			friends: vec![User::Bob],
+			threshold: 1,
+			delay: 0,
+		},
+	);
+	assert_eq!(end, start);
+}
+
+#[test]
+fn sm_12_only_friends_vouch_once() {
+	let mut state = alice_configured();
+	for transition in [
+		RecoveryTransition::InitiateRecovery { rescuer: User::Charlie, lost: User::Alice },
+		RecoveryTransition::Vouch {
+			friend: User::Alice,
+			lost: User::Alice,
+			rescuer: User::Charlie,
+		},
+		RecoveryTransition::Vouch { friend: User::Bob, lost: User::Alice, rescuer: User::Charlie },
+		RecoveryTransition::Vouch { friend: User::Bob, lost: User::Alice, rescuer: User::Charlie },
+	] {
+		state = SocialRecovery::next_state(&state, &transition);
+	}
+
+	assert_eq!(state.active[&(User::Alice, User::Charlie)].vouches, vec![User::Bob]);
+}
+
+#[test]
+fn sm_12_claim_after_delay() {
+	let state = after_blocks(bob_vouched_for(), 10);
+	let end = SocialRecovery::next_state(
+		&state,
+		&RecoveryTransition::ClaimRecovery { rescuer: User::Bob, lost: User::Alice },
+	);
+
+	assert!(end.active.is_empty());
+	assert_eq!(end.recovered, HashMap::from([(User::Bob, User::Alice)]));
+}
+
+#[test]
+fn sm_12_cannot_claim_before_delay() {
+	let state = after_blocks(bob_vouched_for(), 9);
+	let end = SocialRecovery::next_state(
+		&state,
+		&RecoveryTransition::ClaimRecovery { rescuer: User::Bob, lost: User::Alice },
+	);
+	assert_eq!(end, state);
+}
+
+#[test]
+fn sm_12_cannot_claim_without_enough_vouches() {
+	let mut state = alice_configured();
+	state = SocialRecovery::next_state(
+		&state,
+		&RecoveryTransition::InitiateRecovery { rescuer: User::Bob, lost: User::Alice },
+	);
+	state = SocialRecovery::next_state(
+		&state,
+		&RecoveryTransition::Vouch { friend: User::Charlie, lost: User::Alice, rescuer: User::Bob },
+	);
+	state = after_blocks(state, 20);
+	let end = SocialRecovery::next_state(
+		&state,
+		&RecoveryTransition::ClaimRecovery { rescuer: User::Bob, lost: User::Alice },
+	);
+	assert_eq!(end, state);
+}
+
+#[test]
+fn sm_12_owner_blocks_malicious_recovery() {
+	let mut state = after_blocks(bob_vouched_for(), 5);
+	state = SocialRecovery::next_state(
+		&state,
+		&RecoveryTransition::CloseRecovery { lost: User::Alice, rescuer: User::Bob },
+	);
+	state = after_blocks(state, 5);
+	let end = SocialRecovery::next_state(
+		&state,
+		&RecoveryTransition::ClaimRecovery { rescuer: User::Bob, lost: User::Alice },
+	);
+
+	assert!(end.recovered.is_empty());
+}
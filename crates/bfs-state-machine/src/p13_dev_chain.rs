@@ -0,0 +1,284 @@
+//! On a development chain nobody cares about decentralization. What developers want is to get
+//! tokens quickly and to fix mistakes without waiting on governance. So dev chains usually have two
+//! extra features.
+//!
+//! The first is a single superuser account known as the _sudo_ key. Whoever holds it can dispatch
+//! any call with the root origin. The second is a _faucet_ that hands out test tokens to anyone who
+//! asks. The faucet is rate limited, because otherwise one greedy script could mint an unbounded
+//! amount of tokens and make balances meaningless for everyone else.
+//!
+//! A cooldown per account only goes so far, since making new accounts is free. So the faucet can
+//! also demand a small proof of work with every request. This is the same idea as proof of work
+//! consensus, but used at the application level: it costs the honest user a fraction of a second,
+//! and it costs the spammer that much for every single account.
+//!
+//! This lesson builds both on top of the authorized currency from part 8.
+
+use super::{
+	arithmetic::Balance,
+	hash,
+	p4_accounted_currency::AccountingTransaction,
+	p8_authorized::{AuthorizedCurrency, Call, Origin},
+	StateMachine, User,
+};
+use std::collections::HashMap;
+
+/// This state machine models a development chain with a sudo key and a faucet.
+pub struct DevChain;
+
+/// How generous the faucet is.
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub struct FaucetConfig {
+	/// How many tokens each request is paid
+	pub drip: Balance,
+	/// How many blocks an account must wait between requests
+	pub cooldown: u64,
+	/// When set, every request must come with a nonce whose challenge hash is below this
+	/// threshold. See [`DevChainState::faucet_challenge`].
+	pub pow_threshold: Option<u64>,
+}
+
+/// The state of a development chain.
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct DevChainState {
+	/// The current block number
+	pub now: u64,
+	/// The account that may dispatch calls as root. Once removed, it can never be set again.
+	pub sudo_key: Option<User>,
+	/// The faucet settings
+	pub faucet: FaucetConfig,
+	/// The block at which each account last used the faucet
+	pub last_drip: HashMap<User, u64>,
+	/// The balances of the currency
+	pub balances: HashMap<User, Balance>,
+}
+
+/// The things that can happen on a development chain.
+pub enum DevChainTransition {
+	/// An ordinary currency transaction signed by the given user.
+	Signed { who: User, call: AccountingTransaction },
+	/// The sudo key dispatches a call with the root origin. Ignored unless `who` holds the key.
+	Sudo { who: User, call: AccountingTransaction },
+	/// The sudo key hands itself over to a new account, or gives itself up forever when `new` is
+	/// `None`. Ignored unless `who` holds the key.
+	SetKey { who: User, new: Option<User> },
+	/// A user asks the faucet for some test tokens. Ignored if they used the faucet less than the
+	/// cooldown ago, if the faucet requires proof of work and the nonce is not a solution, or if
+	/// the payment would overflow their balance. The nonce is ignored when no work is required.
+	Drip { who: User, nonce: u64 },
+	/// A new block is produced.
+	Tick,
+}
+
+impl DevChainState {
+	/// A fresh chain with the given sudo key and faucet, and no balances.
+	pub fn new(sudo_key: User, faucet: FaucetConfig) -> Self {
+		DevChainState {
+			now: 0,
+			sudo_key: Some(sudo_key),
+			faucet,
+			last_drip: HashMap::new(),
+			balances: HashMap::new(),
+		}
+	}
+
+	/// Whether the given user may use the faucet in the current block.
+	pub fn can_drip(&self, who: &User) -> bool {
+		self.last_drip
+			.get(who)
+			.is_none_or(|last| self.now >= last.saturating_add(self.faucet.cooldown))
+	}
+
+	/// The hash that the given user's faucet request must bring below the threshold. It commits to
+	/// the user's previous request, so each solution can only be used once.
+	pub fn faucet_challenge(&self, who: &User, nonce: u64) -> u64 {
+		hash(&(who, self.last_drip.get(who), nonce))
+	}
+
+	/// Whether the nonce is a valid proof of work for the given user's next faucet request.
+	pub fn check_faucet_pow(&self, who: &User, nonce: u64) -> bool {
+		self.faucet
+			.pow_threshold
+			.is_none_or(|threshold| self.faucet_challenge(who, nonce) < threshold)
+	}
+
+	/// Search for a nonce that satisfies the faucet's proof of work. This is the client side of the
+	/// challenge, and works just like a miner searching for a block.
+	pub fn solve_faucet_pow(&self, who: &User) -> u64 {
+		(0..)
+			.find(|nonce| self.check_faucet_pow(who, *nonce))
+			.expect("a solution always exists")
+	}
+}
+
+impl StateMachine for DevChain {
+	type State = DevChainState;
+	type Transition = DevChainTransition;
+
+	fn next_state(starting_state: &DevChainState, t: &DevChainTransition) -> DevChainState {
+		let mut new_state = starting_state.clone();
+		match t {
+			DevChainTransition::Signed { who, call } => {
+				new_state.balances = AuthorizedCurrency::next_state(
+					&starting_state.balances,
+					&Call { origin: Origin::Signed(*who), transition: call.clone() },
+				);
+			},
+			DevChainTransition::Sudo { who, call } =>
+				if starting_state.sudo_key == Some(*who) {
+					new_state.balances = AuthorizedCurrency::next_state(
+						&starting_state.balances,
+						&Call { origin: Origin::Root, transition: call.clone() },
+					);
+				},
+			DevChainTransition::SetKey { who, new } =>
+				if starting_state.sudo_key == Some(*who) {
+					new_state.sudo_key = *new;
+				},
+			DevChainTransition::Drip { who, nonce } => {
+				if !starting_state.can_drip(who) || !starting_state.check_faucet_pow(who, *nonce) {
+					return new_state;
+				}
+				let old_balance = starting_state.balances.get(who).copied().unwrap_or_default();
+				if let Some(balance) = old_balance.checked_add(starting_state.faucet.drip) {
+					new_state.balances.insert(*who, balance);
+					new_state.last_drip.insert(*who, starting_state.now);
+				}
+			},
+			DevChainTransition::Tick => {
+				new_state.now = starting_state.now.saturating_add(1);
+			},
+		}
+		new_state
+	}
+
+	fn human_name() -> String {
+		"Development chain".into()
+	}
+}
+
+#[cfg(test)]
+fn dev_chain() -> DevChainState {
+	DevChainState::new(
+		User::Alice,
+		FaucetConfig { drip: Balance(10), cooldown: 5, pow_threshold: None },
+	)
+}
+
+/// A faucet where roughly 1 in 100 nonces is a valid proof of work.
+#[cfg(test)]
+fn pow_dev_chain() -> DevChainState {
+	let mut state = dev_chain();
+	state.faucet.pow_threshold = Some(u64::MAX / 100);
+	state
+}
+
+#[test]
+fn sm_13_sudo_dispatches_as_root() {
+	let end = DevChain::next_state(
+		&dev_chain(),
+		&DevChainTransition::Sudo {
+			who: User::Alice,
+			call: AccountingTransaction::Mint { minter: User::Bob, amount: Balance(100) },
+		},
+	);
+	assert_eq!(end.balances, HashMap::from([(User::Bob, Balance(100))]));
+}
+
+#[test]
+fn sm_13_only_sudo_key_uses_sudo() {
+	let start = dev_chain();
+	let end = DevChain::next_state(
+		&start,
+		&DevChainTransition::Sudo {
+			who: User::Bob,
+			call: AccountingTransaction::Mint { minter: User::Bob, amount: Balance(100) },
+		},
+	);
+	assert_eq!(end, start);
+}
+
+#[test]
+fn sm_13_signed_calls_are_not_root() {
+	let start = dev_chain();
+	let end = DevChain::next_state(
+		&start,
+		&DevChainTransition::Signed {
+			who: User::Alice,
+			call: AccountingTransaction::Mint { minter: User::Alice, amount: Balance(100) },
+		},
+	);
+	assert_eq!(end, start);
+}
+
+#[test]
+fn sm_13_set_key() {
+	let mut state = dev_chain();
+	state = DevChain::next_state(
+		&state,
+		&DevChainTransition::SetKey { who: User::Bob, new: Some(User::Bob) },
+	);
+	assert_eq!(state.sudo_key, Some(User::Alice));
+
+	state = DevChain::next_state(
+		&state,
+		&DevChainTransition::SetKey { who: User::Alice, new: Some(User::Bob) },
+	);
+	assert_eq!(state.sudo_key, Some(User::Bob));
+
+	state = DevChain::next_state(&state, &DevChainTransition::SetKey { who: User::Bob, new: None });
+	assert_eq!(state.sudo_key, None);
+}
+
+#[test]
+fn sm_13_faucet_is_rate_limited() {
+	let mut state =
+		DevChain::next_state(&dev_chain(), &DevChainTransition::Drip { who: User::Bob, nonce: 0 });
+	assert_eq!(state.balances[&User::Bob], Balance(10));
+
+	// Asking again straight away does nothing.
+	let again =
+		DevChain::next_state(&state, &DevChainTransition::Drip { who: User::Bob, nonce: 0 });
+	assert_eq!(again, state);
+
+	// Other users have their own limit.
+	let charlie =
+		DevChain::next_state(&state, &DevChainTransition::Drip { who: User::Charlie, nonce: 0 });
+	assert_eq!(charlie.balances[&User::Charlie], Balance(10));
+
+	for _ in 0..5 {
+		state = DevChain::next_state(&state, &DevChainTransition::Tick);
+	}
+	state = DevChain::next_state(&state, &DevChainTransition::Drip { who: User::Bob, nonce: 0 });
+	assert_eq!(state.balances[&User::Bob], Balance(20));
+}
+
+#[test]
+fn sm_13_faucet_requires_pow_when_configured() {
+	let start = pow_dev_chain();
+	let bad_nonce = (0..).find(|nonce| !start.check_faucet_pow(&User::Bob, *nonce)).unwrap();
+	let end = DevChain::next_state(
+		&start,
+		&DevChainTransition::Drip { who: User::Bob, nonce: bad_nonce },
+	);
+	assert_eq!(end, start);
+
+	let nonce = start.solve_faucet_pow(&User::Bob);
+	let end = DevChain::next_state(&start, &DevChainTransition::Drip { who: User::Bob, nonce });
+	assert_eq!(end.balances[&User::Bob], Balance(10));
+}
+
+#[test]
+fn sm_13_faucet_pow_cannot_be_reused() {
+	let mut state = pow_dev_chain();
+	let nonce = state.solve_faucet_pow(&User::Bob);
+	state = DevChain::next_state(&state, &DevChainTransition::Drip { who: User::Bob, nonce });
+	for _ in 0..5 {
+		state = DevChain::next_state(&state, &DevChainTransition::Tick);
+	}
+
+	// The challenge has changed, so the old solution no longer works.
+	assert!(!state.check_faucet_pow(&User::Bob, nonce));
+	let end = DevChain::next_state(&state, &DevChainTransition::Drip { who: User::Bob, nonce });
+	assert_eq!(end, state);
+}
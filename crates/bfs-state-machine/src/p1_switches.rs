@@ -0,0 +1,280 @@
+//! We begin our hands on exploration of state machines with two very simple examples.
+//! In these examples, we use actually switch boards as the state machine. The state is,
+//! well, just the state of the switches.
+//!
+//! Once the switches are done, we generalize to a dial with any number of positions. The number of
+//! positions is a const generic parameter, so one implementation covers every dial size.
+
+use super::{hints::Hints, StateMachine};
+use std::marker::PhantomData;
+
+/// This state machine models a single light switch.
+/// The internal state, a bool, represents whether the switch is on or not.
+pub struct LightSwitch;
+
+/// We model this simple system as a state machine with a single transition - toggling the switch
+/// Because there is only a single kind of transition, we can use a unit struct.
+impl StateMachine for LightSwitch {
+	type State = bool;
+	type Transition = ();
+
+	fn next_state(starting_state: &bool, t: &()) -> bool {
+		!*starting_state
+	}
+}
+
+/// This second  state machine models two light switches with one weird property.
+/// Whenever switch one is turned off, switch two also goes off.
+pub struct WeirdSwitchMachine;
+
+/// The state is now two switches instead of one so we use a struct.
+#[derive(PartialEq, Eq, Debug)]
+pub struct TwoSwitches {
+	first_switch: bool,
+	second_switch: bool,
+}
+
+/// Now there are two switches so we need a proper type for the transition.
+pub enum Toggle {
+	FirstSwitch,
+	SecondSwitch,
+}
+
+/// We model this system as a state machine with two possible transitions
+impl StateMachine for WeirdSwitchMachine {
+	type State = TwoSwitches;
+	type Transition = Toggle;
+
+	fn next_state(starting_state: &TwoSwitches, t: &Toggle) -> TwoSwitches {
+		match t {
+			Toggle::FirstSwitch => {
+				let mut second_switch = starting_state.second_switch;
+				if (starting_state.first_switch) {
+					second_switch = false;
+				}
+				return TwoSwitches { first_switch: !starting_state.first_switch, second_switch }
+			},
+			Toggle::SecondSwitch => TwoSwitches {
+				first_switch: starting_state.first_switch,
+				second_switch: !starting_state.second_switch,
+			},
+		}
+	}
+}
+
+/// A rotary dial with `N` positions, numbered `0` through `N - 1`. Think of the knob on a stove or
+/// the selector on a washing machine.
+///
+/// Not every dial can be turned every way. The `Guard` decides which turns are allowed from which
+/// positions. A turn that the guard rejects leaves the dial where it was.
+pub struct Dial<const N: usize, Guard = Unguarded>(PhantomData<Guard>);
+
+/// The position a dial with `N` positions is pointing at. It is always less than `N`.
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub struct DialPosition<const N: usize>(usize);
+
+impl<const N: usize> DialPosition<N> {
+	/// Point at the given position, or return `None` if the dial doesn't have that many positions.
+	pub fn new(position: usize) -> Option<Self> {
+		(position < N).then_some(DialPosition(position))
+	}
+
+	/// The position as a plain number.
+	pub fn get(&self) -> usize {
+		self.0
+	}
+}
+
+/// The ways to move a dial.
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub enum Turn {
+	/// Move to the next position. Turning clockwise from the last position wraps around to `0`.
+	Clockwise,
+	/// Move to the previous position. Turning counter-clockwise from `0` wraps around to the last
+	/// position.
+	CounterClockwise,
+	/// Move directly to the given position. Positions the dial doesn't have are ignored.
+	JumpTo(usize),
+}
+
+/// Decides which turns a dial allows from each position.
+pub trait DialGuard<const N: usize> {
+	/// Whether the dial may make the given turn from the given position.
+	fn allowed(from: DialPosition<N>, turn: &Turn) -> bool;
+}
+
+/// A dial that may be turned any way from anywhere.
+pub struct Unguarded;
+
+impl<const N: usize> DialGuard<N> for Unguarded {
+	fn allowed(_from: DialPosition<N>, _turn: &Turn) -> bool {
+		true
+	}
+}
+
+/// A dial with a hard stop at both ends, like a stove knob. It can't wrap around, and it must be
+/// turned one position at a time.
+pub struct HardStops;
+
+impl<const N: usize> DialGuard<N> for HardStops {
+	fn allowed(from: DialPosition<N>, turn: &Turn) -> bool {
+		match turn {
+			Turn::Clockwise => from.get() + 1 < N,
+			Turn::CounterClockwise => from.get() > 0,
+			Turn::JumpTo(_) => false,
+		}
+	}
+}
+
+/// A dial with a child lock. It can only be jumped to a new position from position `0`, which is
+/// conventionally "off". Stepping one position at a time is always allowed.
+pub struct ChildLock;
+
+impl<const N: usize> DialGuard<N> for ChildLock {
+	fn allowed(from: DialPosition<N>, turn: &Turn) -> bool {
+		match turn {
+			Turn::JumpTo(_) => from.get() == 0,
+			_ => true,
+		}
+	}
+}
+
+impl<const N: usize, Guard: DialGuard<N>> StateMachine for Dial<N, Guard> {
+	type State = DialPosition<N>;
+	type Transition = Turn;
+
+	fn next_state(starting_state: &DialPosition<N>, t: &Turn) -> DialPosition<N> {
+		if !Guard::allowed(*starting_state, t) {
+			return *starting_state;
+		}
+		let position = starting_state.get();
+		match t {
+			Turn::Clockwise => DialPosition((position + 1) % N),
+			Turn::CounterClockwise => DialPosition((position + N - 1) % N),
+			Turn::JumpTo(target) => DialPosition::new(*target).unwrap_or(*starting_state),
+		}
+	}
+}
+
+const FIRST_SWITCH_HINTS: Hints = &[
+	"Turning the first switch off is the only transition that touches both switches.",
+	"Look at whether the first switch was on before the toggle, not after it.",
+	"When the first switch goes from on to off, the second switch ends up off whatever it was.",
+];
+
+#[test]
+fn sm_1_light_switch_toggles_off() {
+	assert!(!LightSwitch::next_state(&true, &()));
+}
+
+#[test]
+fn sm_1_light_switch_toggles_on() {
+	assert!(LightSwitch::next_state(&false, &()));
+}
+
+#[test]
+fn sm_1_two_switches_first_goes_on() {
+	let state = TwoSwitches { first_switch: false, second_switch: false };
+
+	assert_eq!(
+		WeirdSwitchMachine::next_state(&state, &Toggle::FirstSwitch),
+		TwoSwitches { first_switch: true, second_switch: false }
+	);
+}
+
+#[test]
+fn sm_1_two_switches_first_goes_off_second_was_on() {
+	// This is the special case. We have to make sure the second one goes off with it.
+	let state = TwoSwitches { first_switch: true, second_switch: true };
+
+	hint_assert_eq!(
+		WeirdSwitchMachine::next_state(&state, &Toggle::FirstSwitch),
+		TwoSwitches { first_switch: false, second_switch: false },
+		FIRST_SWITCH_HINTS
+	);
+}
+
+#[test]
+fn sm_1_two_switches_first_goes_off_second_was_off() {
+	// This is adjacent to the special case. We have to make sure the second one stays off.
+	let state = TwoSwitches { first_switch: true, second_switch: false };
+
+	hint_assert_eq!(
+		WeirdSwitchMachine::next_state(&state, &Toggle::FirstSwitch),
+		TwoSwitches { first_switch: false, second_switch: false },
+		FIRST_SWITCH_HINTS
+	);
+}
+
+#[test]
+fn sm_1_two_switches_second_goes_on() {
+	let state = TwoSwitches { first_switch: false, second_switch: false };
+
+	assert_eq!(
+		WeirdSwitchMachine::next_state(&state, &Toggle::SecondSwitch),
+		TwoSwitches { first_switch: false, second_switch: true }
+	);
+}
+
+#[test]
+fn sm_1_two_switches_second_goes_off() {
+	let state = TwoSwitches { first_switch: true, second_switch: true };
+
+	assert_eq!(
+		WeirdSwitchMachine::next_state(&state, &Toggle::SecondSwitch),
+		TwoSwitches { first_switch: true, second_switch: false }
+	);
+}
+
+#[test]
+fn sm_1_dial_position_bounds() {
+	assert_eq!(DialPosition::<3>::new(2).map(|p| p.get()), Some(2));
+	assert_eq!(DialPosition::<3>::new(3), None);
+}
+
+#[test]
+fn sm_1_dial_turns_clockwise() {
+	let state = DialPosition::<4>::new(1).unwrap();
+
+	assert_eq!(Dial::<4>::next_state(&state, &Turn::Clockwise), DialPosition(2));
+}
+
+#[test]
+fn sm_1_dial_wraps_around() {
+	let last = DialPosition::<4>::new(3).unwrap();
+	let first = DialPosition::<4>::new(0).unwrap();
+
+	assert_eq!(Dial::<4>::next_state(&last, &Turn::Clockwise), first);
+	assert_eq!(Dial::<4>::next_state(&first, &Turn::CounterClockwise), last);
+}
+
+#[test]
+fn sm_1_dial_jumps() {
+	let state = DialPosition::<5>::new(1).unwrap();
+
+	assert_eq!(Dial::<5>::next_state(&state, &Turn::JumpTo(4)), DialPosition(4));
+	// There is no position 5, so the dial doesn't move.
+	assert_eq!(Dial::<5>::next_state(&state, &Turn::JumpTo(5)), state);
+}
+
+#[test]
+fn sm_1_dial_with_hard_stops() {
+	let first = DialPosition::<3>::new(0).unwrap();
+	let middle = DialPosition::<3>::new(1).unwrap();
+	let last = DialPosition::<3>::new(2).unwrap();
+
+	assert_eq!(Dial::<3, HardStops>::next_state(&first, &Turn::CounterClockwise), first);
+	assert_eq!(Dial::<3, HardStops>::next_state(&last, &Turn::Clockwise), last);
+	assert_eq!(Dial::<3, HardStops>::next_state(&middle, &Turn::Clockwise), last);
+	assert_eq!(Dial::<3, HardStops>::next_state(&middle, &Turn::JumpTo(0)), middle);
+}
+
+#[test]
+fn sm_1_dial_with_child_lock() {
+	let off = DialPosition::<6>::new(0).unwrap();
+	let on = DialPosition::<6>::new(3).unwrap();
+
+	assert_eq!(Dial::<6, ChildLock>::next_state(&off, &Turn::JumpTo(3)), on);
+	assert_eq!(Dial::<6, ChildLock>::next_state(&on, &Turn::JumpTo(5)), on);
+	assert_eq!(Dial::<6, ChildLock>::next_state(&on, &Turn::Clockwise), DialPosition(4));
+}
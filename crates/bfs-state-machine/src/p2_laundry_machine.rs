@@ -0,0 +1,551 @@
+//! When you wear clothes they get dirty. When you wash them they get wet. When you dry them,
+//! they're ready to be worn again. Or course washing and wearing clothes takes its toll on the
+//! clothes, and eventually they get tattered.
+//!
+//! The second half of this lesson models the washing machine itself. It is a coin-operated washer
+//! at a laundromat, which has quite a bit more going on than the clothes do.
+
+use super::StateMachine;
+
+/// This state machine models the typical life cycle of clothes as they make their way through the
+/// laundry cycle several times before ultimately becoming tattered.
+pub struct ClothesMachine;
+
+/// Models a piece of clothing throughout its lifecycle.
+#[derive(PartialEq, Eq, Debug)]
+pub enum ClothesState {
+	/// Clean clothes ready to be worn. With some given life left.
+	Clean(u64),
+	/// Dirty clothes. With some given life left.
+	Dirty(u64),
+	/// Wet clothes. With some given life left. The clothes are assumed to be wet because
+	/// they were just washed. And washing clothes is the only modeled way to get them wet.
+	Wet(u64),
+	/// Tattered clothes beyond their useful life. These clothes will always be tattered no matter
+	/// what is done with them.
+	Tattered,
+}
+
+/// Something you can do with clothes
+pub enum ClothesAction {
+	/// Wearing clothes decreases their life by 1 and makes them dirty.
+	Wear,
+	/// Washing clothes decreases their life by 1, and makes them wet.
+	Wash,
+	/// This operation models a tumble drier. Drying clothes decreases their life by 1.
+	/// If the clothes were clean or wet to begin with they will be clean after drying.
+	/// If they were dirty to begin with, they will still be dirty after drying.
+	Dry,
+}
+
+impl StateMachine for ClothesMachine {
+	type State = ClothesState;
+	type Transition = ClothesAction;
+
+	fn next_state(starting_state: &ClothesState, t: &ClothesAction) -> ClothesState {
+		match starting_state {
+			ClothesState::Tattered => ClothesState::Tattered,
+			_ => match t {
+				ClothesAction::Dry => match starting_state {
+					ClothesState::Wet(life) | ClothesState::Clean(life) =>
+						if *life == 1 {
+							ClothesState::Tattered
+						} else {
+							ClothesState::Clean(*life - 1)
+						},
+					ClothesState::Dirty(life) =>
+						if *life == 1 {
+							ClothesState::Tattered
+						} else {
+							ClothesState::Dirty(*life - 1)
+						},
+					_ => unreachable!(),
+				},
+				ClothesAction::Wear => match starting_state {
+					ClothesState::Clean(life) =>
+						if *life == 1 {
+							ClothesState::Tattered
+						} else {
+							ClothesState::Dirty(*life - 1)
+						},
+					ClothesState::Dirty(life) | ClothesState::Wet(life) =>
+						if *life == 1 {
+							ClothesState::Tattered
+						} else {
+							ClothesState::Dirty(*life - 1)
+						},
+					_ => unreachable!(),
+				},
+				ClothesAction::Wash => match starting_state {
+					ClothesState::Clean(life) |
+					ClothesState::Dirty(life) |
+					ClothesState::Wet(life) =>
+						if *life == 1 {
+							ClothesState::Tattered
+						} else {
+							ClothesState::Wet(*life - 1)
+						},
+					_ => unreachable!(),
+				},
+			},
+		}
+	}
+}
+
+/// A coin-operated washing machine at a laundromat.
+///
+/// Customers insert coins, which are held in escrow, and select a cycle. Once enough money is in
+/// escrow they can start the machine. Any excess is handed back as change. While the machine is
+/// running it steps through the phases of the selected cycle, one `Tick` at a time. Customers may
+/// cancel and get their coins back at any point before the machine starts, but not after.
+pub struct CoinWasher;
+
+/// The washing programs a customer can choose from.
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub enum Cycle {
+	Quick,
+	Normal,
+	Heavy,
+}
+
+impl Cycle {
+	/// How many coins it costs to run this cycle.
+	pub fn price(&self) -> u64 {
+		match self {
+			Cycle::Quick => 3,
+			Cycle::Normal => 5,
+			Cycle::Heavy => 7,
+		}
+	}
+
+	/// The phases that make up this cycle, in order, along with how many ticks each one lasts.
+	pub fn phases(&self) -> &'static [(WashPhase, u64)] {
+		use WashPhase::*;
+		match self {
+			Cycle::Quick => &[(Wash, 2), (Spin, 1)],
+			Cycle::Normal => &[(Fill, 1), (Wash, 3), (Rinse, 2), (Spin, 2)],
+			Cycle::Heavy => &[(Fill, 2), (Wash, 5), (Rinse, 3), (Spin, 3)],
+		}
+	}
+}
+
+/// The individual steps of a wash cycle.
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub enum WashPhase {
+	Fill,
+	Wash,
+	Rinse,
+	Spin,
+}
+
+/// What the washer is currently doing.
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub enum WasherProgram {
+	/// Not running. The customer may or may not have chosen a cycle yet.
+	Idle { selected: Option<Cycle> },
+	/// Running the given cycle. The current phase will last for the given number of ticks.
+	Running { cycle: Cycle, phase: WashPhase, ticks_left: u64 },
+}
+
+/// The complete state of the washer.
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct Washer {
+	/// Coins that have been inserted but not yet spent on a cycle.
+	pub escrow: u64,
+	/// Coins the machine has handed back through the coin return, either as change or as a refund.
+	pub coin_return: u64,
+	/// What the machine is doing.
+	pub program: WasherProgram,
+}
+
+/// Something you can do with the washer
+pub enum WasherAction {
+	/// Insert a coin of the given value. Coins inserted while the machine is running fall straight
+	/// through to the coin return.
+	InsertCoin(u64),
+	/// Choose a cycle. This can be changed freely until the machine starts.
+	SelectCycle(Cycle),
+	/// Start the selected cycle. Nothing happens unless a cycle is selected and enough coins are
+	/// in escrow.
+	Start,
+	/// Time passes.
+	Tick,
+	/// Cancel before starting. The escrow is refunded and the selection is cleared.
+	Cancel,
+}
+
+impl StateMachine for CoinWasher {
+	type State = Washer;
+	type Transition = WasherAction;
+
+	fn next_state(starting_state: &Washer, t: &WasherAction) -> Washer {
+		let mut new_state = starting_state.clone();
+		match (&starting_state.program, t) {
+			(WasherProgram::Idle { .. }, WasherAction::InsertCoin(value)) => {
+				match starting_state.escrow.checked_add(*value) {
+					Some(escrow) => new_state.escrow = escrow,
+					None =>
+						new_state.coin_return = starting_state.coin_return.saturating_add(*value),
+				}
+			},
+			(WasherProgram::Running { .. }, WasherAction::InsertCoin(value)) => {
+				new_state.coin_return = starting_state.coin_return.saturating_add(*value);
+			},
+			(WasherProgram::Idle { .. }, WasherAction::SelectCycle(cycle)) => {
+				new_state.program = WasherProgram::Idle { selected: Some(*cycle) };
+			},
+			(WasherProgram::Idle { selected: Some(cycle) }, WasherAction::Start) => {
+				if let Some(change) = starting_state.escrow.checked_sub(cycle.price()) {
+					let (phase, ticks_left) = cycle.phases()[0];
+					new_state.escrow = 0;
+					new_state.coin_return = starting_state.coin_return.saturating_add(change);
+					new_state.program = WasherProgram::Running { cycle: *cycle, phase, ticks_left };
+				}
+			},
+			(WasherProgram::Running { cycle, phase, ticks_left }, WasherAction::Tick) => {
+				new_state.program = if *ticks_left > 1 {
+					WasherProgram::Running {
+						cycle: *cycle,
+						phase: *phase,
+						ticks_left: ticks_left - 1,
+					}
+				} else {
+					let phases = cycle.phases();
+					let current =
+						phases.iter().position(|(p, _)| p == phase).unwrap_or(phases.len());
+					match phases.get(current + 1) {
+						Some((next, ticks)) => WasherProgram::Running {
+							cycle: *cycle,
+							phase: *next,
+							ticks_left: *ticks,
+						},
+						None => WasherProgram::Idle { selected: None },
+					}
+				};
+			},
+			(WasherProgram::Idle { .. }, WasherAction::Cancel) => {
+				new_state.escrow = 0;
+				new_state.coin_return =
+					starting_state.coin_return.saturating_add(starting_state.escrow);
+				new_state.program = WasherProgram::Idle { selected: None };
+			},
+			_ => (),
+		}
+		new_state
+	}
+}
+
+#[test]
+fn sm_2_wear_clean_clothes() {
+	let start = ClothesState::Clean(4);
+	let end = ClothesMachine::next_state(&start, &ClothesAction::Wear);
+	let expected = ClothesState::Dirty(3);
+	assert_eq!(end, expected);
+}
+
+#[test]
+fn sm_2_wear_dirty_clothes() {
+	let start = ClothesState::Dirty(4);
+	let end = ClothesMachine::next_state(&start, &ClothesAction::Wear);
+	let expected = ClothesState::Dirty(3);
+	assert_eq!(end, expected);
+}
+
+#[test]
+fn sm_2_wear_wet_clothes() {
+	let start = ClothesState::Wet(4);
+	let end = ClothesMachine::next_state(&start, &ClothesAction::Wear);
+	let expected = ClothesState::Dirty(3);
+	assert_eq!(end, expected);
+}
+
+#[test]
+fn sm_2_wear_tattered_clothes() {
+	let start = ClothesState::Tattered;
+	let end = ClothesMachine::next_state(&start, &ClothesAction::Wear);
+	let expected = ClothesState::Tattered;
+	assert_eq!(end, expected);
+}
+
+#[test]
+fn sm_2_wear_clean_until_tattered() {
+	let start = ClothesState::Clean(1);
+	let end = ClothesMachine::next_state(&start, &ClothesAction::Wear);
+	let expected = ClothesState::Tattered;
+	assert_eq!(end, expected);
+}
+
+#[test]
+fn sm_2_wear_wet_until_tattered() {
+	let start = ClothesState::Wet(1);
+	let end = ClothesMachine::next_state(&start, &ClothesAction::Wear);
+	let expected = ClothesState::Tattered;
+	assert_eq!(end, expected);
+}
+
+#[test]
+fn sm_2_wear_dirty_until_tattered() {
+	let start = ClothesState::Dirty(1);
+	let end = ClothesMachine::next_state(&start, &ClothesAction::Wear);
+	let expected = ClothesState::Tattered;
+	assert_eq!(end, expected);
+}
+
+#[test]
+fn sm_2_wash_clean_clothes() {
+	let start = ClothesState::Clean(4);
+	let end = ClothesMachine::next_state(&start, &ClothesAction::Wash);
+	let expected = ClothesState::Wet(3);
+	assert_eq!(end, expected);
+}
+
+#[test]
+fn sm_2_wash_dirty_clothes() {
+	let start = ClothesState::Dirty(4);
+	let end = ClothesMachine::next_state(&start, &ClothesAction::Wash);
+	let expected = ClothesState::Wet(3);
+	assert_eq!(end, expected);
+}
+
+#[test]
+fn sm_2_wash_wet_clothes() {
+	let start = ClothesState::Wet(4);
+	let end = ClothesMachine::next_state(&start, &ClothesAction::Wash);
+	let expected = ClothesState::Wet(3);
+	assert_eq!(end, expected);
+}
+
+#[test]
+fn sm_2_wash_tattered_clothes() {
+	let start = ClothesState::Tattered;
+	let end = ClothesMachine::next_state(&start, &ClothesAction::Wash);
+	let expected = ClothesState::Tattered;
+	assert_eq!(end, expected);
+}
+
+#[test]
+fn sm_2_wash_clean_until_tattered() {
+	let start = ClothesState::Clean(1);
+	let end = ClothesMachine::next_state(&start, &ClothesAction::Wash);
+	let expected = ClothesState::Tattered;
+	assert_eq!(end, expected);
+}
+
+#[test]
+fn sm_2_wash_wet_until_tattered() {
+	let start = ClothesState::Wet(1);
+	let end = ClothesMachine::next_state(&start, &ClothesAction::Wash);
+	let expected = ClothesState::Tattered;
+	assert_eq!(end, expected);
+}
+
+#[test]
+fn sm_2_wash_dirty_until_tattered() {
+	let start = ClothesState::Dirty(1);
+	let end = ClothesMachine::next_state(&start, &ClothesAction::Wash);
+	let expected = ClothesState::Tattered;
+	assert_eq!(end, expected);
+}
+
+#[test]
+fn sm_2_dry_clean_clothes() {
+	let start = ClothesState::Clean(4);
+	let end = ClothesMachine::next_state(&start, &ClothesAction::Dry);
+	let expected = ClothesState::Clean(3);
+	assert_eq!(end, expected);
+}
+
+#[test]
+fn sm_2_dry_dirty_clothes() {
+	let start = ClothesState::Dirty(4);
+	let end = ClothesMachine::next_state(&start, &ClothesAction::Dry);
+	let expected = ClothesState::Dirty(3);
+	assert_eq!(end, expected);
+}
+
+#[test]
+fn sm_2_dry_wet_clothes() {
+	let start = ClothesState::Wet(4);
+	let end = ClothesMachine::next_state(&start, &ClothesAction::Dry);
+	let expected = ClothesState::Clean(3);
+	assert_eq!(end, expected);
+}
+
+#[test]
+fn sm_2_dry_tattered_clothes() {
+	let start = ClothesState::Tattered;
+	let end = ClothesMachine::next_state(&start, &ClothesAction::Dry);
+	let expected = ClothesState::Tattered;
+	assert_eq!(end, expected);
+}
+
+#[test]
+fn sm_2_dry_clean_until_tattered() {
+	let start = ClothesState::Clean(1);
+	let end = ClothesMachine::next_state(&start, &ClothesAction::Dry);
+	let expected = ClothesState::Tattered;
+	assert_eq!(end, expected);
+}
+
+#[test]
+fn sm_2_dry_cwet_until_tattered() {
+	let start = ClothesState::Wet(1);
+	let end = ClothesMachine::next_state(&start, &ClothesAction::Dry);
+	let expected = ClothesState::Tattered;
+	assert_eq!(end, expected);
+}
+
+#[test]
+fn sm_2_dry_dirty_until_tattered() {
+	let start = ClothesState::Dirty(1);
+	let end = ClothesMachine::next_state(&start, &ClothesAction::Dry);
+	let expected = ClothesState::Tattered;
+	assert_eq!(end, expected);
+}
+
+#[test]
+fn sm_2_washer_insert_coins() {
+	let start =
+		Washer { escrow: 0, coin_return: 0, program: WasherProgram::Idle { selected: None } };
+	let mid = CoinWasher::next_state(&start, &WasherAction::InsertCoin(2));
+	let end = CoinWasher::next_state(&mid, &WasherAction::InsertCoin(1));
+	let expected =
+		Washer { escrow: 3, coin_return: 0, program: WasherProgram::Idle { selected: None } };
+	assert_eq!(end, expected);
+}
+
+#[test]
+fn sm_2_washer_start_without_enough_coins() {
+	let start = Washer {
+		escrow: 4,
+		coin_return: 0,
+		program: WasherProgram::Idle { selected: Some(Cycle::Normal) },
+	};
+	let end = CoinWasher::next_state(&start, &WasherAction::Start);
+	assert_eq!(end, start);
+}
+
+#[test]
+fn sm_2_washer_start_without_selecting_cycle() {
+	let start =
+		Washer { escrow: 10, coin_return: 0, program: WasherProgram::Idle { selected: None } };
+	let end = CoinWasher::next_state(&start, &WasherAction::Start);
+	assert_eq!(end, start);
+}
+
+#[test]
+fn sm_2_washer_start_gives_change() {
+	let start = Washer {
+		escrow: 5,
+		coin_return: 0,
+		program: WasherProgram::Idle { selected: Some(Cycle::Quick) },
+	};
+	let end = CoinWasher::next_state(&start, &WasherAction::Start);
+	let expected = Washer {
+		escrow: 0,
+		coin_return: 2,
+		program: WasherProgram::Running {
+			cycle: Cycle::Quick,
+			phase: WashPhase::Wash,
+			ticks_left: 2,
+		},
+	};
+	assert_eq!(end, expected);
+}
+
+#[test]
+fn sm_2_washer_ticks_through_phases() {
+	let mut state = Washer {
+		escrow: 3,
+		coin_return: 0,
+		program: WasherProgram::Idle { selected: Some(Cycle::Quick) },
+	};
+	state = CoinWasher::next_state(&state, &WasherAction::Start);
+	state = CoinWasher::next_state(&state, &WasherAction::Tick);
+	assert_eq!(
+		state.program,
+		WasherProgram::Running { cycle: Cycle::Quick, phase: WashPhase::Wash, ticks_left: 1 }
+	);
+	state = CoinWasher::next_state(&state, &WasherAction::Tick);
+	assert_eq!(
+		state.program,
+		WasherProgram::Running { cycle: Cycle::Quick, phase: WashPhase::Spin, ticks_left: 1 }
+	);
+	state = CoinWasher::next_state(&state, &WasherAction::Tick);
+	assert_eq!(state.program, WasherProgram::Idle { selected: None });
+}
+
+#[test]
+fn sm_2_washer_full_normal_cycle() {
+	let mut state = Washer {
+		escrow: 5,
+		coin_return: 0,
+		program: WasherProgram::Idle { selected: Some(Cycle::Normal) },
+	};
+	state = CoinWasher::next_state(&state, &WasherAction::Start);
+	for _ in 0..7 {
+		state = CoinWasher::next_state(&state, &WasherAction::Tick);
+		assert!(matches!(state.program, WasherProgram::Running { .. }));
+	}
+	state = CoinWasher::next_state(&state, &WasherAction::Tick);
+	assert_eq!(state.program, WasherProgram::Idle { selected: None });
+}
+
+#[test]
+fn sm_2_washer_cancel_refunds_escrow() {
+	let start = Washer {
+		escrow: 4,
+		coin_return: 1,
+		program: WasherProgram::Idle { selected: Some(Cycle::Heavy) },
+	};
+	let end = CoinWasher::next_state(&start, &WasherAction::Cancel);
+	let expected =
+		Washer { escrow: 0, coin_return: 5, program: WasherProgram::Idle { selected: None } };
+	assert_eq!(end, expected);
+}
+
+#[test]
+fn sm_2_washer_cannot_cancel_once_running() {
+	let start = Washer {
+		escrow: 0,
+		coin_return: 0,
+		program: WasherProgram::Running {
+			cycle: Cycle::Heavy,
+			phase: WashPhase::Rinse,
+			ticks_left: 2,
+		},
+	};
+	let end = CoinWasher::next_state(&start, &WasherAction::Cancel);
+	assert_eq!(end, start);
+}
+
+#[test]
+fn sm_2_washer_rejects_coins_while_running() {
+	let start = Washer {
+		escrow: 0,
+		coin_return: 0,
+		program: WasherProgram::Running {
+			cycle: Cycle::Normal,
+			phase: WashPhase::Fill,
+			ticks_left: 1,
+		},
+	};
+	let end = CoinWasher::next_state(&start, &WasherAction::InsertCoin(2));
+	let expected = Washer { coin_return: 2, ..start };
+	assert_eq!(end, expected);
+}
+
+#[test]
+fn sm_2_washer_cannot_change_cycle_while_running() {
+	let start = Washer {
+		escrow: 0,
+		coin_return: 0,
+		program: WasherProgram::Running {
+			cycle: Cycle::Normal,
+			phase: WashPhase::Wash,
+			ticks_left: 3,
+		},
+	};
+	let end = CoinWasher::next_state(&start, &WasherAction::SelectCycle(Cycle::Quick));
+	assert_eq!(end, start);
+}
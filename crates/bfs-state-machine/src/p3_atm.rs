@@ -0,0 +1,863 @@
+//! The automated teller machine gives you cash after you swipe your card and enter your pin.
+//! The atm may fail to give you cash if it is empty or you haven't swiped your card, or you have
+//! entered the wrong pin.
+//!
+//! The bank's operator uses the same machine to refill it with notes, collect any cards that
+//! customers left behind, and check how many withdrawals have been made.
+
+use super::{arithmetic::Balance, StateMachine};
+use std::collections::BTreeMap;
+
+/// The keys on the ATM keypad
+#[derive(Hash, Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Key {
+	One,
+	Two,
+	Three,
+	Four,
+	Enter,
+}
+
+/// Something you can do to the ATM
+pub enum Action {
+	/// Swipe your card at the ATM. The attached value is the hash of the pin
+	/// that should be keyed in on the keypad next.
+	SwipeCard(u64),
+	/// Press a key on the keypad
+	PressKey(Key),
+	/// Swipe the operator's card. The operator's pin hash is part of the machine's config.
+	SwipeOperatorCard,
+	/// Walk away in the middle of a session. The ATM keeps the customer's card.
+	Abandon,
+	/// Operator only: load more notes into the machine.
+	Refill(CashInventory),
+	/// Operator only: take out all the cards that customers have left behind.
+	RetrieveCards,
+	/// Operator only: finish the maintenance session.
+	EndOperatorSession,
+}
+
+/// The various states of authentication possible with the ATM
+#[derive(Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+enum Auth {
+	/// No session has begun yet. Waiting for the user to swipe their card
+	Waiting,
+	/// The user has swiped their card, providing the enclosed PIN hash.
+	/// Waiting for the user to key in their pin
+	Authenticating(u64),
+	/// The user has authenticated. Waiting for them to key in the amount
+	/// of cash to withdraw
+	Authenticated,
+	/// The operator has swiped their card. Waiting for them to key in the operator pin
+	OperatorAuthenticating,
+	/// The operator has authenticated. Maintenance actions are allowed until the
+	/// session is ended
+	Operator,
+}
+
+/// The face value of a bank note.
+pub type Denomination = u64;
+
+/// Exactly which notes are handed out for a single withdrawal. Maps each denomination to the
+/// number of notes of that denomination.
+pub type Dispense = BTreeMap<Denomination, u64>;
+
+/// The notes stored inside the ATM. Maps each denomination to the number of notes of that
+/// denomination. Denominations with no notes left are not stored at all.
+#[derive(Debug, Default, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CashInventory(BTreeMap<Denomination, u64>);
+
+impl CashInventory {
+	/// The total value of all the notes, or `None` if it is too large to represent.
+	pub fn total(&self) -> Option<Balance> {
+		self.0.iter().try_fold(Balance::zero(), |total, (denomination, count)| {
+			total.checked_add(Balance(*denomination).checked_mul(*count)?)
+		})
+	}
+
+	/// Work out which notes to hand out so that they add up to exactly `amount`.
+	///
+	/// Always handing out the largest note that fits (the greedy approach) is not enough in
+	/// general. With one 30 and two 20s, the greedy approach hands out the 30 first and can then
+	/// never make 40. So when the greedy choice leads to a dead end, we back off and try one fewer
+	/// of the larger note.
+	///
+	/// Returns `None` if the amount can't be composed from the notes available.
+	pub fn compose(&self, amount: Balance) -> Option<Dispense> {
+		let notes: Vec<(Denomination, u64)> = self.0.iter().rev().map(|(d, c)| (*d, *c)).collect();
+		let mut dispense = Dispense::new();
+		Self::compose_from(&notes, amount.0, &mut dispense).then_some(dispense)
+	}
+
+	/// Helper for `compose`. The notes must be sorted from the largest denomination to the
+	/// smallest.
+	fn compose_from(notes: &[(Denomination, u64)], amount: u64, dispense: &mut Dispense) -> bool {
+		let Some(((denomination, available), smaller)) = notes.split_first() else {
+			return amount == 0;
+		};
+		if *denomination == 0 {
+			return Self::compose_from(smaller, amount, dispense);
+		}
+
+		let most = (*available).min(amount / denomination);
+		for count in (0..=most).rev() {
+			if Self::compose_from(smaller, amount - count * denomination, dispense) {
+				if count > 0 {
+					dispense.insert(*denomination, count);
+				}
+				return true;
+			}
+			// The smallest denomination has nothing below it to back off to.
+			if smaller.is_empty() {
+				return false;
+			}
+		}
+		false
+	}
+
+	/// Add more notes to the inventory, or return `None` if a count would overflow.
+	pub fn add(&self, notes: &CashInventory) -> Option<Self> {
+		let mut combined = self.clone();
+		for (denomination, count) in &notes.0 {
+			let existing = combined.0.get(denomination).copied().unwrap_or(0);
+			combined.0.insert(*denomination, existing.checked_add(*count)?);
+		}
+		Some(combined)
+	}
+
+	/// Remove the given notes from the inventory, or return `None` if there aren't enough of them.
+	pub fn remove(&self, dispense: &Dispense) -> Option<Self> {
+		let mut remaining = self.clone();
+		for (denomination, count) in dispense {
+			let available = remaining.0.get(denomination).copied().unwrap_or(0);
+			match available.checked_sub(*count)? {
+				0 => remaining.0.remove(denomination),
+				left => remaining.0.insert(*denomination, left),
+			};
+		}
+		Some(remaining)
+	}
+}
+
+impl<const N: usize> From<[(Denomination, u64); N]> for CashInventory {
+	fn from(notes: [(Denomination, u64); N]) -> Self {
+		CashInventory(notes.into_iter().filter(|(_, count)| *count > 0).collect())
+	}
+}
+
+/// Settings chosen by whoever installs the ATM. They never change while the machine is running.
+#[derive(Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AtmConfig {
+	/// The most keys that will be remembered between presses of `Enter`. Any further keys are
+	/// ignored, so neither a pin nor an amount can be longer than this.
+	pub max_keystrokes: usize,
+	/// The hash of the pin that the operator must key in after swiping the operator card.
+	pub operator_pin_hash: u64,
+}
+
+impl Default for AtmConfig {
+	fn default() -> Self {
+		AtmConfig {
+			// A u64 has at most 20 decimal digits, so every amount that fits can be keyed in.
+			max_keystrokes: 20,
+			operator_pin_hash: crate::hash(&vec![Key::One, Key::Two, Key::Three, Key::Four]),
+		}
+	}
+}
+
+/// Bookkeeping that only the operator may look at.
+#[derive(Debug, Default, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AtmRecords {
+	/// Cards left behind by customers who abandoned their session.
+	pub retained_cards: u64,
+	/// The number of successful withdrawals since the machine was installed.
+	pub withdrawals: u64,
+}
+
+/// The ATM. When a card is swiped, the ATM learns the correct pin's hash.
+/// It waits for you to key in your pin. You can press as many numeric keys as
+/// you like followed by enter. If the pin is incorrect, your card is returned
+/// and the ATM automatically goes back to the main menu. If your pin is correct,
+/// the ATM waits for you to key in an amount of money to withdraw. Withdraws
+/// are bounded only by the cash in the machine (there is no account balance).
+/// The machine holds notes of several denominations, and a withdrawal only succeeds if the amount
+/// can be made exactly from the notes available.
+///
+/// The keypad only remembers a limited number of keystrokes, as specified in the config. Keys
+/// pressed once the register is full are silently dropped. If the keyed amount is still somehow
+/// too large to represent, the withdrawal is refused as if the machine couldn't dispense it.
+#[derive(Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Atm {
+	/// The installation settings
+	config: AtmConfig,
+	/// The operator's records
+	records: AtmRecords,
+	/// The notes in the ATM
+	cash_inside: CashInventory,
+	/// The machine's authentication status.
+	expected_pin_hash: Auth,
+	/// All the keys that have been pressed since the last `Enter`
+	keystroke_register: Vec<Key>,
+}
+
+impl Atm {
+	/// Read the operator's records. They are only visible during an operator session.
+	pub fn audit(&self) -> Option<&AtmRecords> {
+		match self.expected_pin_hash {
+			Auth::Operator => Some(&self.records),
+			_ => None,
+		}
+	}
+}
+
+fn verify_pin(_keys: &Vec<Key>, hash: u64) -> bool {
+	let pin_hash = crate::hash(_keys);
+	pin_hash == hash
+}
+
+/// Interpret the keyed digits as an amount of money. Returns `None` if the amount is too large to
+/// be represented, in which case it certainly can't be withdrawn.
+fn keys_to_amount(_keys: &Vec<Key>) -> Option<Balance> {
+	let mut amount = Balance::zero();
+	for key in _keys {
+		let digit = match key {
+			Key::One => 1,
+			Key::Two => 2,
+			Key::Three => 3,
+			Key::Four => 4,
+			_ => 0,
+		};
+		amount = amount.checked_mul(10)?.checked_add(Balance(digit))?;
+	}
+	Some(amount)
+}
+
+impl StateMachine for Atm {
+	// Notice that we are using the same type for the state as we are using for the machine this
+	// time.
+	type State = Self;
+	type Transition = Action;
+
+	fn next_state(starting_state: &Self::State, t: &Self::Transition) -> Self::State {
+		match t {
+			Action::PressKey(key) => {
+				let mut new_state = starting_state.clone();
+				match key {
+					Key::Enter => {
+						// Authenticate
+						match starting_state.expected_pin_hash {
+							Auth::Authenticating(correct_pin) => {
+								if verify_pin(&new_state.keystroke_register, correct_pin) {
+									new_state.expected_pin_hash = Auth::Authenticated;
+									new_state.keystroke_register.clear();
+								} else {
+									new_state.expected_pin_hash = Auth::Waiting;
+									new_state.keystroke_register.clear();
+								}
+							},
+							Auth::OperatorAuthenticating => {
+								if verify_pin(
+									&new_state.keystroke_register,
+									starting_state.config.operator_pin_hash,
+								) {
+									new_state.expected_pin_hash = Auth::Operator;
+								} else {
+									new_state.expected_pin_hash = Auth::Waiting;
+								}
+								new_state.keystroke_register.clear();
+							},
+							Auth::Authenticated => {
+								let remaining = keys_to_amount(&starting_state.keystroke_register)
+									.and_then(|amount| new_state.cash_inside.compose(amount))
+									.and_then(|dispense| new_state.cash_inside.remove(&dispense));
+								if let Some(remaining) = remaining {
+									new_state.cash_inside = remaining;
+									new_state.records.withdrawals += 1;
+								}
+								new_state.keystroke_register.clear();
+								new_state.expected_pin_hash = Auth::Waiting;
+							},
+							_ => (),
+						}
+					},
+					_ => {
+						let register_full = starting_state.keystroke_register.len() >=
+							starting_state.config.max_keystrokes;
+						match starting_state.expected_pin_hash {
+							_ if register_full => (),
+							Auth::Authenticating(_) => {
+								new_state.keystroke_register.push(key.clone());
+							},
+							Auth::Authenticated | Auth::OperatorAuthenticating => {
+								new_state.keystroke_register.push(key.clone());
+							},
+							_ => (),
+						}
+					},
+				}
+				new_state
+			},
+			Action::SwipeCard(hash) => match starting_state.expected_pin_hash {
+				Auth::Waiting => Atm {
+					config: starting_state.config.clone(),
+					records: starting_state.records.clone(),
+					expected_pin_hash: Auth::Authenticating(*hash),
+					cash_inside: starting_state.cash_inside.clone(),
+					keystroke_register: vec![],
+				},
+				_ => starting_state.clone(),
+			},
+			Action::SwipeOperatorCard => {
+				let mut new_state = starting_state.clone();
+				if starting_state.expected_pin_hash == Auth::Waiting {
+					new_state.expected_pin_hash = Auth::OperatorAuthenticating;
+					new_state.keystroke_register.clear();
+				}
+				new_state
+			},
+			Action::Abandon => {
+				let mut new_state = starting_state.clone();
+				match starting_state.expected_pin_hash {
+					Auth::Authenticating(_) | Auth::Authenticated => {
+						new_state.records.retained_cards += 1;
+						new_state.expected_pin_hash = Auth::Waiting;
+						new_state.keystroke_register.clear();
+					},
+					_ => (),
+				}
+				new_state
+			},
+			Action::Refill(notes) => {
+				let mut new_state = starting_state.clone();
+				if starting_state.expected_pin_hash == Auth::Operator {
+					if let Some(refilled) = starting_state.cash_inside.add(notes) {
+						new_state.cash_inside = refilled;
+					}
+				}
+				new_state
+			},
+			Action::RetrieveCards => {
+				let mut new_state = starting_state.clone();
+				if starting_state.expected_pin_hash == Auth::Operator {
+					new_state.records.retained_cards = 0;
+				}
+				new_state
+			},
+			Action::EndOperatorSession => {
+				let mut new_state = starting_state.clone();
+				if starting_state.expected_pin_hash == Auth::Operator {
+					new_state.expected_pin_hash = Auth::Waiting;
+				}
+				new_state
+			},
+		}
+	}
+}
+
+#[test]
+fn sm_3_simple_swipe_card() {
+	let start = Atm {
+		config: AtmConfig::default(),
+		records: AtmRecords::default(),
+		cash_inside: CashInventory::from([(1, 10)]),
+		expected_pin_hash: Auth::Waiting,
+		keystroke_register: Vec::new(),
+	};
+	let end = Atm::next_state(&start, &Action::SwipeCard(1234));
+	let expected = Atm {
+		config: AtmConfig::default(),
+		records: AtmRecords::default(),
+		cash_inside: CashInventory::from([(1, 10)]),
+		expected_pin_hash: Auth::Authenticating(1234),
+		keystroke_register: Vec::new(),
+	};
+
+	assert_eq!(end, expected);
+}
+
+#[test]
+fn sm_3_swipe_card_again_part_way_through() {
+	let start = Atm {
+		config: AtmConfig::default(),
+		records: AtmRecords::default(),
+		cash_inside: CashInventory::from([(1, 10)]),
+		expected_pin_hash: Auth::Authenticating(1234),
+		keystroke_register: Vec::new(),
+	};
+	let end = Atm::next_state(&start, &Action::SwipeCard(1234));
+	let expected = Atm {
+		config: AtmConfig::default(),
+		records: AtmRecords::default(),
+		cash_inside: CashInventory::from([(1, 10)]),
+		expected_pin_hash: Auth::Authenticating(1234),
+		keystroke_register: Vec::new(),
+	};
+
+	assert_eq!(end, expected);
+
+	let start = Atm {
+		config: AtmConfig::default(),
+		records: AtmRecords::default(),
+		cash_inside: CashInventory::from([(1, 10)]),
+		expected_pin_hash: Auth::Authenticating(1234),
+		keystroke_register: vec![Key::One, Key::Three],
+	};
+	let end = Atm::next_state(&start, &Action::SwipeCard(1234));
+	let expected = Atm {
+		config: AtmConfig::default(),
+		records: AtmRecords::default(),
+		cash_inside: CashInventory::from([(1, 10)]),
+		expected_pin_hash: Auth::Authenticating(1234),
+		keystroke_register: vec![Key::One, Key::Three],
+	};
+
+	assert_eq!(end, expected);
+}
+
+#[test]
+fn sm_3_press_key_before_card_swipe() {
+	let start = Atm {
+		config: AtmConfig::default(),
+		records: AtmRecords::default(),
+		cash_inside: CashInventory::from([(1, 10)]),
+		expected_pin_hash: Auth::Waiting,
+		keystroke_register: Vec::new(),
+	};
+	let end = Atm::next_state(&start, &Action::PressKey(Key::One));
+	let expected = Atm {
+		config: AtmConfig::default(),
+		records: AtmRecords::default(),
+		cash_inside: CashInventory::from([(1, 10)]),
+		expected_pin_hash: Auth::Waiting,
+		keystroke_register: Vec::new(),
+	};
+
+	assert_eq!(end, expected);
+}
+
+#[test]
+fn sm_3_enter_single_digit_of_pin() {
+	let start = Atm {
+		config: AtmConfig::default(),
+		records: AtmRecords::default(),
+		cash_inside: CashInventory::from([(1, 10)]),
+		expected_pin_hash: Auth::Authenticating(1234),
+		keystroke_register: Vec::new(),
+	};
+	let end = Atm::next_state(&start, &Action::PressKey(Key::One));
+	let expected = Atm {
+		config: AtmConfig::default(),
+		records: AtmRecords::default(),
+		cash_inside: CashInventory::from([(1, 10)]),
+		expected_pin_hash: Auth::Authenticating(1234),
+		keystroke_register: vec![Key::One],
+	};
+
+	assert_eq!(end, expected);
+
+	let start = Atm {
+		config: AtmConfig::default(),
+		records: AtmRecords::default(),
+		cash_inside: CashInventory::from([(1, 10)]),
+		expected_pin_hash: Auth::Authenticating(1234),
+		keystroke_register: vec![Key::One],
+	};
+	let end1 = Atm::next_state(&start, &Action::PressKey(Key::Two));
+	let expected1 = Atm {
+		config: AtmConfig::default(),
+		records: AtmRecords::default(),
+		cash_inside: CashInventory::from([(1, 10)]),
+		expected_pin_hash: Auth::Authenticating(1234),
+		keystroke_register: vec![Key::One, Key::Two],
+	};
+
+	assert_eq!(end1, expected1);
+}
+
+#[test]
+fn sm_3_enter_wrong_pin() {
+	// Create hash of pin
+	let pin = vec![Key::One, Key::Two, Key::Three, Key::Four];
+	let pin_hash = crate::hash(&pin);
+
+	let start = Atm {
+		config: AtmConfig::default(),
+		records: AtmRecords::default(),
+		cash_inside: CashInventory::from([(1, 10)]),
+		expected_pin_hash: Auth::Authenticating(pin_hash),
+		keystroke_register: vec![Key::Three, Key::Three, Key::Three, Key::Three],
+	};
+	let end = Atm::next_state(&start, &Action::PressKey(Key::Enter));
+	let expected = Atm {
+		config: AtmConfig::default(),
+		records: AtmRecords::default(),
+		cash_inside: CashInventory::from([(1, 10)]),
+		expected_pin_hash: Auth::Waiting,
+		keystroke_register: Vec::new(),
+	};
+
+	assert_eq!(end, expected);
+}
+
+#[test]
+fn sm_3_enter_correct_pin() {
+	// Create hash of pin
+	let pin = vec![Key::One, Key::Two, Key::Three, Key::Four];
+	let pin_hash = crate::hash(&pin);
+
+	let start = Atm {
+		config: AtmConfig::default(),
+		records: AtmRecords::default(),
+		cash_inside: CashInventory::from([(1, 10)]),
+		expected_pin_hash: Auth::Authenticating(pin_hash),
+		keystroke_register: vec![Key::One, Key::Two, Key::Three, Key::Four],
+	};
+	let end = Atm::next_state(&start, &Action::PressKey(Key::Enter));
+	let expected = Atm {
+		config: AtmConfig::default(),
+		records: AtmRecords::default(),
+		cash_inside: CashInventory::from([(1, 10)]),
+		expected_pin_hash: Auth::Authenticated,
+		keystroke_register: Vec::new(),
+	};
+
+	assert_eq!(end, expected);
+}
+
+#[test]
+fn sm_3_enter_single_digit_of_withdraw_amount() {
+	let start = Atm {
+		config: AtmConfig::default(),
+		records: AtmRecords::default(),
+		cash_inside: CashInventory::from([(1, 10)]),
+		expected_pin_hash: Auth::Authenticated,
+		keystroke_register: Vec::new(),
+	};
+	let end = Atm::next_state(&start, &Action::PressKey(Key::One));
+	let expected = Atm {
+		config: AtmConfig::default(),
+		records: AtmRecords::default(),
+		cash_inside: CashInventory::from([(1, 10)]),
+		expected_pin_hash: Auth::Authenticated,
+		keystroke_register: vec![Key::One],
+	};
+
+	assert_eq!(end, expected);
+
+	let start = Atm {
+		config: AtmConfig::default(),
+		records: AtmRecords::default(),
+		cash_inside: CashInventory::from([(1, 10)]),
+		expected_pin_hash: Auth::Authenticated,
+		keystroke_register: vec![Key::One],
+	};
+	let end1 = Atm::next_state(&start, &Action::PressKey(Key::Four));
+	let expected1 = Atm {
+		config: AtmConfig::default(),
+		records: AtmRecords::default(),
+		cash_inside: CashInventory::from([(1, 10)]),
+		expected_pin_hash: Auth::Authenticated,
+		keystroke_register: vec![Key::One, Key::Four],
+	};
+
+	assert_eq!(end1, expected1);
+}
+
+#[test]
+fn sm_3_try_to_withdraw_too_much() {
+	let start = Atm {
+		config: AtmConfig::default(),
+		records: AtmRecords::default(),
+		cash_inside: CashInventory::from([(1, 10)]),
+		expected_pin_hash: Auth::Authenticated,
+		keystroke_register: vec![Key::One, Key::Four],
+	};
+	let end = Atm::next_state(&start, &Action::PressKey(Key::Enter));
+	let expected = Atm {
+		config: AtmConfig::default(),
+		records: AtmRecords::default(),
+		cash_inside: CashInventory::from([(1, 10)]),
+		expected_pin_hash: Auth::Waiting,
+		keystroke_register: Vec::new(),
+	};
+
+	assert_eq!(end, expected);
+}
+
+#[test]
+fn sm_3_withdraw_acceptable_amount() {
+	let start = Atm {
+		config: AtmConfig::default(),
+		records: AtmRecords::default(),
+		cash_inside: CashInventory::from([(1, 10)]),
+		expected_pin_hash: Auth::Authenticated,
+		keystroke_register: vec![Key::One],
+	};
+	let end = Atm::next_state(&start, &Action::PressKey(Key::Enter));
+	let expected = Atm {
+		config: AtmConfig::default(),
+		records: AtmRecords { retained_cards: 0, withdrawals: 1 },
+		cash_inside: CashInventory::from([(1, 9)]),
+		expected_pin_hash: Auth::Waiting,
+		keystroke_register: Vec::new(),
+	};
+
+	assert_eq!(end, expected);
+}
+
+#[test]
+fn sm_3_withdraw_amount_too_large_to_represent() {
+	let start = Atm {
+		config: AtmConfig { max_keystrokes: 25, ..AtmConfig::default() },
+		records: AtmRecords::default(),
+		cash_inside: CashInventory::from([(1, 10)]),
+		expected_pin_hash: Auth::Authenticated,
+		keystroke_register: vec![Key::Four; 25],
+	};
+	let end = Atm::next_state(&start, &Action::PressKey(Key::Enter));
+	let expected = Atm {
+		config: AtmConfig { max_keystrokes: 25, ..AtmConfig::default() },
+		records: AtmRecords::default(),
+		cash_inside: CashInventory::from([(1, 10)]),
+		expected_pin_hash: Auth::Waiting,
+		keystroke_register: Vec::new(),
+	};
+
+	assert_eq!(end, expected);
+}
+
+#[test]
+fn sm_3_keystrokes_beyond_limit_are_ignored() {
+	let start = Atm {
+		config: AtmConfig { max_keystrokes: 2, ..AtmConfig::default() },
+		records: AtmRecords::default(),
+		cash_inside: CashInventory::from([(1, 1000)]),
+		expected_pin_hash: Auth::Authenticated,
+		keystroke_register: vec![Key::One, Key::Two],
+	};
+	let end = Atm::next_state(&start, &Action::PressKey(Key::Three));
+
+	assert_eq!(end, start);
+}
+
+#[test]
+fn sm_3_withdraw_with_full_register() {
+	let start = Atm {
+		config: AtmConfig { max_keystrokes: 2, ..AtmConfig::default() },
+		records: AtmRecords::default(),
+		cash_inside: CashInventory::from([(1, 1000)]),
+		expected_pin_hash: Auth::Authenticated,
+		keystroke_register: vec![Key::One, Key::Two],
+	};
+	let full = Atm::next_state(&start, &Action::PressKey(Key::Four));
+	let end = Atm::next_state(&full, &Action::PressKey(Key::Enter));
+	let expected = Atm {
+		config: AtmConfig { max_keystrokes: 2, ..AtmConfig::default() },
+		records: AtmRecords { retained_cards: 0, withdrawals: 1 },
+		cash_inside: CashInventory::from([(1, 988)]),
+		expected_pin_hash: Auth::Waiting,
+		keystroke_register: Vec::new(),
+	};
+
+	assert_eq!(end, expected);
+}
+
+#[test]
+fn sm_3_compose_prefers_large_notes() {
+	let cash = CashInventory::from([(1, 10), (5, 10), (20, 10)]);
+
+	assert_eq!(cash.compose(Balance(47)), Some(Dispense::from([(1, 2), (5, 1), (20, 2)])));
+}
+
+#[test]
+fn sm_3_compose_backs_off_when_greedy_fails() {
+	let cash = CashInventory::from([(20, 2), (30, 1)]);
+
+	assert_eq!(cash.compose(Balance(40)), Some(Dispense::from([(20, 2)])));
+}
+
+#[test]
+fn sm_3_compose_impossible_amount() {
+	let cash = CashInventory::from([(20, 2), (50, 1)]);
+
+	assert_eq!(cash.compose(Balance(30)), None);
+	assert_eq!(cash.compose(Balance(100)), None);
+	assert_eq!(cash.compose(Balance(90)), Some(Dispense::from([(20, 2), (50, 1)])));
+}
+
+#[test]
+fn sm_3_cash_inventory_total() {
+	assert_eq!(CashInventory::from([(5, 3), (20, 1)]).total(), Some(Balance(35)));
+	assert_eq!(CashInventory::from([(u64::MAX, 2)]).total(), None);
+}
+
+#[test]
+fn sm_3_withdraw_dispenses_notes() {
+	let start = Atm {
+		config: AtmConfig::default(),
+		records: AtmRecords::default(),
+		cash_inside: CashInventory::from([(2, 2), (3, 1)]),
+		expected_pin_hash: Auth::Authenticated,
+		keystroke_register: vec![Key::Four],
+	};
+	let end = Atm::next_state(&start, &Action::PressKey(Key::Enter));
+	let expected = Atm {
+		config: AtmConfig::default(),
+		records: AtmRecords { retained_cards: 0, withdrawals: 1 },
+		cash_inside: CashInventory::from([(3, 1)]),
+		expected_pin_hash: Auth::Waiting,
+		keystroke_register: Vec::new(),
+	};
+
+	assert_eq!(end, expected);
+}
+
+#[test]
+fn sm_3_withdraw_amount_not_composable() {
+	let start = Atm {
+		config: AtmConfig::default(),
+		records: AtmRecords::default(),
+		cash_inside: CashInventory::from([(20, 2)]),
+		expected_pin_hash: Auth::Authenticated,
+		keystroke_register: vec![Key::Three],
+	};
+	let end = Atm::next_state(&start, &Action::PressKey(Key::Enter));
+	let expected = Atm {
+		config: AtmConfig::default(),
+		records: AtmRecords::default(),
+		cash_inside: CashInventory::from([(20, 2)]),
+		expected_pin_hash: Auth::Waiting,
+		keystroke_register: Vec::new(),
+	};
+
+	assert_eq!(end, expected);
+}
+
+#[test]
+fn sm_3_withdraw_is_counted() {
+	let start = Atm {
+		config: AtmConfig::default(),
+		records: AtmRecords::default(),
+		cash_inside: CashInventory::from([(1, 10)]),
+		expected_pin_hash: Auth::Authenticated,
+		keystroke_register: vec![Key::Four],
+	};
+	let end = Atm::next_state(&start, &Action::PressKey(Key::Enter));
+
+	assert_eq!(end.records, AtmRecords { retained_cards: 0, withdrawals: 1 });
+}
+
+#[test]
+fn sm_3_abandon_retains_card() {
+	let start = Atm {
+		config: AtmConfig::default(),
+		records: AtmRecords::default(),
+		cash_inside: CashInventory::from([(1, 10)]),
+		expected_pin_hash: Auth::Authenticated,
+		keystroke_register: vec![Key::Four],
+	};
+	let end = Atm::next_state(&start, &Action::Abandon);
+	let expected = Atm {
+		config: AtmConfig::default(),
+		records: AtmRecords { retained_cards: 1, withdrawals: 0 },
+		cash_inside: CashInventory::from([(1, 10)]),
+		expected_pin_hash: Auth::Waiting,
+		keystroke_register: Vec::new(),
+	};
+
+	assert_eq!(end, expected);
+}
+
+#[test]
+fn sm_3_operator_login() {
+	let start = Atm {
+		config: AtmConfig::default(),
+		records: AtmRecords::default(),
+		cash_inside: CashInventory::from([(1, 10)]),
+		expected_pin_hash: Auth::Waiting,
+		keystroke_register: Vec::new(),
+	};
+	let mut end = Atm::next_state(&start, &Action::SwipeOperatorCard);
+	assert_eq!(end.audit(), None);
+	for key in [Key::One, Key::Two, Key::Three, Key::Four, Key::Enter] {
+		end = Atm::next_state(&end, &Action::PressKey(key));
+	}
+
+	assert_eq!(end.expected_pin_hash, Auth::Operator);
+	assert_eq!(end.audit(), Some(&AtmRecords::default()));
+}
+
+#[test]
+fn sm_3_operator_wrong_pin() {
+	let start = Atm {
+		config: AtmConfig::default(),
+		records: AtmRecords::default(),
+		cash_inside: CashInventory::from([(1, 10)]),
+		expected_pin_hash: Auth::OperatorAuthenticating,
+		keystroke_register: vec![Key::Four],
+	};
+	let end = Atm::next_state(&start, &Action::PressKey(Key::Enter));
+
+	assert_eq!(end.expected_pin_hash, Auth::Waiting);
+	assert_eq!(end.audit(), None);
+}
+
+#[test]
+fn sm_3_operator_refills_and_retrieves_cards() {
+	let start = Atm {
+		config: AtmConfig::default(),
+		records: AtmRecords { retained_cards: 3, withdrawals: 7 },
+		cash_inside: CashInventory::from([(1, 10)]),
+		expected_pin_hash: Auth::Operator,
+		keystroke_register: Vec::new(),
+	};
+	let refilled = Atm::next_state(&start, &Action::Refill(CashInventory::from([(1, 5), (20, 2)])));
+	let end = Atm::next_state(&refilled, &Action::RetrieveCards);
+	let expected = Atm {
+		config: AtmConfig::default(),
+		records: AtmRecords { retained_cards: 0, withdrawals: 7 },
+		cash_inside: CashInventory::from([(1, 15), (20, 2)]),
+		expected_pin_hash: Auth::Operator,
+		keystroke_register: Vec::new(),
+	};
+
+	assert_eq!(end, expected);
+	assert_eq!(Atm::next_state(&end, &Action::EndOperatorSession).audit(), None);
+}
+
+#[test]
+fn sm_3_customer_cannot_refill() {
+	let start = Atm {
+		config: AtmConfig::default(),
+		records: AtmRecords { retained_cards: 3, withdrawals: 0 },
+		cash_inside: CashInventory::from([(1, 10)]),
+		expected_pin_hash: Auth::Authenticated,
+		keystroke_register: Vec::new(),
+	};
+	let refilled = Atm::next_state(&start, &Action::Refill(CashInventory::from([(1, 5)])));
+	let end = Atm::next_state(&refilled, &Action::RetrieveCards);
+
+	assert_eq!(end, start);
+	assert_eq!(end.audit(), None);
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn sm_3_atm_json_roundtrip() {
+	let atm = Atm {
+		config: AtmConfig::default(),
+		records: AtmRecords { retained_cards: 1, withdrawals: 2 },
+		cash_inside: CashInventory::from([(5, 3), (20, 1)]),
+		expected_pin_hash: Auth::Authenticating(1234),
+		keystroke_register: vec![Key::One, Key::Four],
+	};
+	let json = serde_json::to_string(&atm).unwrap();
+	assert!(json.contains(r#""keystroke_register":["One","Four"]"#));
+	assert_eq!(serde_json::from_str::<Atm>(&json).unwrap(), atm);
+}
@@ -0,0 +1,356 @@
+//! The state machines we have written so far model individual devices that are typically used
+//! by a single user at a time. State machines can also model multi user systems. Blockchains
+//! strive to provide reliable public infrastructure. And the public is very much multiple users.
+//!
+//! In this module and the next we explore two common techniques at modeling multi-user state
+//! machines. In this module we explore accounts, and in the next we explore UTXOs.
+//!
+//! In this module we design a state machine that tracks the currency balances of several users.
+//! Each user is associated with an account balance and users are able to send money to other users.
+
+use super::{arithmetic::Balance, hints::Hints, StateMachine, User};
+use std::collections::HashMap;
+
+/// This state machine models a multi-user currency system. It tracks the balance of each
+/// user and allows users to send funds to one another.
+pub struct AccountedCurrency;
+
+/// The main balances mapping.
+///
+/// Each entry maps a user id to their corresponding balance.
+/// There exists an existential deposit of at least 1. That is
+/// to say that an account gets removed from the map entirely
+/// when its balance falls back to 0.
+type Balances = HashMap<User, Balance>;
+
+/// The state transitions that users can make in an accounted currency system
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum AccountingTransaction {
+	/// Create some new money for the given minter in the given amount
+	/// If the new balance would overflow, nothing is minted.
+	Mint { minter: User, amount: Balance },
+	/// Destroy some money from the given account in the given amount
+	/// If the burn amount exceeds the account balance, burn the entire
+	/// amount and remove the account from storage
+	Burn { burner: User, amount: Balance },
+	/// Send some tokens from one account to another
+	/// If the receiver's balance would overflow, nothing is transferred.
+	Transfer { sender: User, receiver: User, amount: Balance },
+}
+
+/// We model this system as a state machine with three possible transitions
+impl StateMachine for AccountedCurrency {
+	type State = Balances;
+	type Transition = AccountingTransaction;
+
+	fn next_state(starting_state: &Balances, t: &AccountingTransaction) -> Balances {
+		let mut new_state = starting_state.clone();
+		match t {
+			AccountingTransaction::Burn { burner, amount } => {
+				if let Some(balance) = starting_state.get(burner) {
+					match balance.checked_sub(*amount) {
+						Some(remaining) if !remaining.is_zero() => {
+							new_state.insert(*burner, remaining);
+						},
+						_ => {
+							new_state.remove(burner);
+						},
+					}
+				}
+			},
+			AccountingTransaction::Mint { minter, amount } =>
+				if !amount.is_zero() {
+					let balance = starting_state.get(minter).copied().unwrap_or_default();
+					if let Some(new_balance) = balance.checked_add(*amount) {
+						new_state.insert(*minter, new_balance);
+					}
+				},
+			AccountingTransaction::Transfer { sender, receiver, amount } => {
+				if sender == receiver {
+					return new_state;
+				}
+				let Some(sender_balance) = starting_state.get(sender) else {
+					return new_state;
+				};
+				let receiver_balance = starting_state.get(receiver).copied().unwrap_or_default();
+
+				if let (Some(sender_remaining), Some(receiver_new)) =
+					(sender_balance.checked_sub(*amount), receiver_balance.checked_add(*amount))
+				{
+					// Delete balance if fully transferred out
+					if sender_remaining.is_zero() {
+						new_state.remove(sender);
+					} else {
+						new_state.insert(*sender, sender_remaining);
+					}
+					new_state.insert(*receiver, receiver_new);
+				}
+			},
+		}
+		new_state
+	}
+}
+
+const EXISTENTIAL_DEPOSIT_HINTS: Hints = &[
+	"Nobody may be left in the map with a balance of zero.",
+	"Minting nothing must not create an account, and burning down to zero must remove one.",
+	"After working out the new balance, remove the account instead of inserting it when it is zero.",
+];
+
+#[test]
+fn sm_4_mint_creates_account() {
+	let start = HashMap::new();
+	let end = AccountedCurrency::next_state(
+		&start,
+		&AccountingTransaction::Mint { minter: User::Alice, amount: Balance(100) },
+	);
+	let expected = HashMap::from([(User::Alice, Balance(100))]);
+
+	assert_eq!(end, expected);
+}
+
+#[test]
+fn sm_4_mint_creates_second_account() {
+	let start = HashMap::from([(User::Alice, Balance(100))]);
+	let end = AccountedCurrency::next_state(
+		&start,
+		&AccountingTransaction::Mint { minter: User::Bob, amount: Balance(50) },
+	);
+	let expected = HashMap::from([(User::Alice, Balance(100)), (User::Bob, Balance(50))]);
+
+	assert_eq!(end, expected);
+}
+
+#[test]
+fn sm_4_mint_increases_balance() {
+	let start = HashMap::from([(User::Alice, Balance(100))]);
+	let end = AccountedCurrency::next_state(
+		&start,
+		&AccountingTransaction::Mint { minter: User::Alice, amount: Balance(50) },
+	);
+	let expected = HashMap::from([(User::Alice, Balance(150))]);
+
+	assert_eq!(end, expected);
+}
+
+#[test]
+fn sm_4_empty_mint() {
+	let start = HashMap::new();
+	let end = AccountedCurrency::next_state(
+		&start,
+		&AccountingTransaction::Mint { minter: User::Alice, amount: Balance(0) },
+	);
+	let expected = HashMap::new();
+
+	hint_assert_eq!(end, expected, EXISTENTIAL_DEPOSIT_HINTS);
+}
+
+#[test]
+fn sm_4_simple_burn() {
+	let start = HashMap::from([(User::Alice, Balance(100))]);
+	let end = AccountedCurrency::next_state(
+		&start,
+		&AccountingTransaction::Burn { burner: User::Alice, amount: Balance(50) },
+	);
+	let expected = HashMap::from([(User::Alice, Balance(50))]);
+
+	assert_eq!(end, expected);
+}
+
+#[test]
+fn sm_4_burn_no_existential_deposit_left() {
+	let start = HashMap::from([(User::Alice, Balance(100)), (User::Bob, Balance(50))]);
+	let end = AccountedCurrency::next_state(
+		&start,
+		&AccountingTransaction::Burn { burner: User::Bob, amount: Balance(50) },
+	);
+	let expected = HashMap::from([(User::Alice, Balance(100))]);
+
+	hint_assert_eq!(end, expected, EXISTENTIAL_DEPOSIT_HINTS);
+}
+
+#[test]
+fn sm_4_non_registered_burner() {
+	let start = HashMap::from([(User::Alice, Balance(100))]);
+	let end = AccountedCurrency::next_state(
+		&start,
+		&AccountingTransaction::Burn { burner: User::Bob, amount: Balance(50) },
+	);
+	let expected = HashMap::from([(User::Alice, Balance(100))]);
+
+	assert_eq!(end, expected);
+}
+
+#[test]
+fn sm_4_burn_more_than_balance() {
+	let start = HashMap::from([(User::Alice, Balance(100)), (User::Bob, Balance(50))]);
+	let end2 = AccountedCurrency::next_state(
+		&start,
+		&AccountingTransaction::Burn { burner: User::Bob, amount: Balance(100) },
+	);
+	let expected2 = HashMap::from([(User::Alice, Balance(100))]);
+
+	hint_assert_eq!(end2, expected2, EXISTENTIAL_DEPOSIT_HINTS);
+}
+
+#[test]
+fn sm_4_empty_burn() {
+	let start = HashMap::from([(User::Alice, Balance(100))]);
+	let end = AccountedCurrency::next_state(
+		&start,
+		&AccountingTransaction::Burn { burner: User::Alice, amount: Balance(0) },
+	);
+	let expected = HashMap::from([(User::Alice, Balance(100))]);
+
+	assert_eq!(end, expected);
+}
+
+#[test]
+fn sm_4_burner_does_not_exist() {
+	let start = HashMap::from([(User::Alice, Balance(100))]);
+	let end = AccountedCurrency::next_state(
+		&start,
+		&AccountingTransaction::Burn { burner: User::Bob, amount: Balance(50) },
+	);
+	let expected = HashMap::from([(User::Alice, Balance(100))]);
+
+	assert_eq!(end, expected);
+}
+
+#[test]
+fn sm_4_simple_transfer() {
+	let start = HashMap::from([(User::Alice, Balance(100)), (User::Bob, Balance(50))]);
+	let end = AccountedCurrency::next_state(
+		&start,
+		&AccountingTransaction::Transfer {
+			sender: User::Alice,
+			receiver: User::Bob,
+			amount: Balance(10),
+		},
+	);
+	let expected = HashMap::from([(User::Alice, Balance(90)), (User::Bob, Balance(60))]);
+
+	assert_eq!(end, expected);
+
+	let start = HashMap::from([(User::Alice, Balance(90)), (User::Bob, Balance(60))]);
+	let end1 = AccountedCurrency::next_state(
+		&start,
+		&AccountingTransaction::Transfer {
+			sender: User::Bob,
+			receiver: User::Alice,
+			amount: Balance(50),
+		},
+	);
+	let expected1 = HashMap::from([(User::Alice, Balance(140)), (User::Bob, Balance(10))]);
+
+	assert_eq!(end1, expected1);
+}
+
+#[test]
+fn sm_4_send_to_same_user() {
+	let start = HashMap::from([(User::Alice, Balance(100)), (User::Bob, Balance(50))]);
+	let end = AccountedCurrency::next_state(
+		&start,
+		&AccountingTransaction::Transfer {
+			sender: User::Bob,
+			receiver: User::Bob,
+			amount: Balance(10),
+		},
+	);
+	let expected = HashMap::from([(User::Alice, Balance(100)), (User::Bob, Balance(50))]);
+
+	assert_eq!(end, expected);
+}
+
+#[test]
+fn sm_4_insufficient_balance_transfer() {
+	let start = HashMap::from([(User::Alice, Balance(100)), (User::Bob, Balance(50))]);
+	let end = AccountedCurrency::next_state(
+		&start,
+		&AccountingTransaction::Transfer {
+			sender: User::Bob,
+			receiver: User::Alice,
+			amount: Balance(60),
+		},
+	);
+	let expected = HashMap::from([(User::Alice, Balance(100)), (User::Bob, Balance(50))]);
+
+	assert_eq!(end, expected);
+}
+
+#[test]
+fn sm_4_sender_not_registered() {
+	let start = HashMap::from([(User::Alice, Balance(100)), (User::Bob, Balance(50))]);
+	let end = AccountedCurrency::next_state(
+		&start,
+		&AccountingTransaction::Transfer {
+			sender: User::Charlie,
+			receiver: User::Alice,
+			amount: Balance(50),
+		},
+	);
+	let expected = HashMap::from([(User::Alice, Balance(100)), (User::Bob, Balance(50))]);
+
+	assert_eq!(end, expected);
+}
+
+#[test]
+fn sm_4_receiver_not_registered() {
+	let start = HashMap::from([(User::Alice, Balance(100)), (User::Bob, Balance(50))]);
+	let end = AccountedCurrency::next_state(
+		&start,
+		&AccountingTransaction::Transfer {
+			sender: User::Alice,
+			receiver: User::Charlie,
+			amount: Balance(50),
+		},
+	);
+	let expected = HashMap::from([
+		(User::Alice, Balance(50)),
+		(User::Bob, Balance(50)),
+		(User::Charlie, Balance(50)),
+	]);
+
+	assert_eq!(end, expected);
+}
+
+#[test]
+fn sm_4_sender_to_empty_balance() {
+	let start = HashMap::from([(User::Alice, Balance(100)), (User::Bob, Balance(50))]);
+	let end = AccountedCurrency::next_state(
+		&start,
+		&AccountingTransaction::Transfer {
+			sender: User::Bob,
+			receiver: User::Alice,
+			amount: Balance(50),
+		},
+	);
+	let expected = HashMap::from([(User::Alice, Balance(150))]);
+
+	assert_eq!(end, expected);
+}
+
+#[test]
+fn sm_4_transfer() {
+	let start = HashMap::from([(User::Alice, Balance(100)), (User::Bob, Balance(50))]);
+	let end = AccountedCurrency::next_state(
+		&start,
+		&AccountingTransaction::Transfer {
+			sender: User::Bob,
+			receiver: User::Charlie,
+			amount: Balance(50),
+		},
+	);
+	let expected = HashMap::from([(User::Alice, Balance(100)), (User::Charlie, Balance(50))]);
+
+	assert_eq!(end, expected);
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn sm_4_balances_json_roundtrip() {
+	let balances = HashMap::from([(User::Alice, Balance(100))]);
+	let json = serde_json::to_string(&balances).unwrap();
+	assert_eq!(json, r#"{"Alice":100}"#);
+	assert_eq!(serde_json::from_str::<Balances>(&json).unwrap(), balances);
+}
@@ -3,7 +3,7 @@
 //! cash bills. Each bill has an amount and an owner, and can be spent in its entirety.
 //! When a state transition spends bills, new bills are created in lesser or equal amount.
 
-use super::{StateMachine, User};
+use super::{arithmetic::Balance, StateMachine, User};
 use std::collections::{HashMap, HashSet};
 
 /// This state machine models a multi-user currency system. It tracks a set of bills in
@@ -14,6 +14,7 @@ pub struct DigitalCashSystem;
 /// it and an amount that it is worth. It also has serial number to ensure that each bill
 /// is unique.
 #[derive(Clone, Debug, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Bill {
 	owner: User,
 	amount: u64,
@@ -23,6 +24,7 @@ pub struct Bill {
 /// The State of a digital cash system. Primarily just the set of currently circulating bills.,
 /// but also a counter for the next serial number.
 #[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct State {
 	/// The set of currently circulating bills
 	bills: HashSet<Bill>,
@@ -99,10 +101,15 @@ impl StateMachine for DigitalCashSystem {
 				new_state.increment_serial();
 			},
 			CashTransaction::Transfer { spends, receives } => {
-				let spent_total: u64 = spends.iter().map(|bill| bill.amount).sum();
-				if let Some(received_total) = receives
-					.iter()
-					.fold(Some(0u64), |acc, bill| acc.and_then(|sum| sum.checked_add(bill.amount)))
+				let total = |bills: &Vec<Bill>| {
+					bills
+						.iter()
+						.try_fold(Balance::zero(), |sum, bill| {
+							sum.checked_add(Balance(bill.amount))
+						})
+						.map(|sum| sum.0)
+				};
+				if let (Some(spent_total), Some(received_total)) = (total(spends), total(receives))
 				{
 					let serials_exist =
 						receives.iter().any(|bill| starting_state.bills.contains(bill));
@@ -415,3 +422,15 @@ fn sm_5_spending_from_charlie_to_all() {
 	expected.set_serial(62);
 	assert_eq!(end, expected);
 }
+
+#[cfg(feature = "serde")]
+#[test]
+fn sm_5_state_json_roundtrip() {
+	let mut state = State::from([
+		Bill { owner: User::Alice, amount: 20, serial: 0 },
+		Bill { owner: User::Bob, amount: 5, serial: 1 },
+	]);
+	state.set_serial(2);
+	let json = serde_json::to_string(&state).unwrap();
+	assert_eq!(serde_json::from_str::<State>(&json).unwrap(), state);
+}
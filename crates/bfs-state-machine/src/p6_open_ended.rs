@@ -56,8 +56,8 @@ impl StateMachine for State {
 
 		match transition {
 			Transition::DecideOnNextCourseOfAction => {
-				if initial_state.personal_identity == Identity::RationalUtilityMaximizer
-					&& initial_state.track == Track::Developer
+				if initial_state.personal_identity == Identity::RationalUtilityMaximizer &&
+					initial_state.track == Track::Developer
 				{
 					output_state.activity =
 						Activity::Code { assignment: Assignment::BlockchainFromScratch };
@@ -66,17 +66,14 @@ impl StateMachine for State {
 				}
 			},
 			Transition::Code => match get_day_of_week() {
-				DayOfWeek::Wednesday => {
+				DayOfWeek::Wednesday =>
 					output_state.activity =
-						Activity::Code { assignment: Assignment::BlockchainFromScratch }
-				},
-				DayOfWeek::Thursday | DayOfWeek::Friday => {
-					output_state.activity = Activity::Code { assignment: Assignment::AssignmentTwo }
-				},
-				_ => {
+						Activity::Code { assignment: Assignment::BlockchainFromScratch },
+				DayOfWeek::Thursday | DayOfWeek::Friday =>
+					output_state.activity = Activity::Code { assignment: Assignment::AssignmentTwo },
+				_ =>
 					output_state.activity =
-						Activity::DayDream { topic: String::from("Next Big Idea") }
-				},
+						Activity::DayDream { topic: String::from("Next Big Idea") },
 			},
 			Transition::DayDream => {
 				output_state.activity = Activity::DayDream {
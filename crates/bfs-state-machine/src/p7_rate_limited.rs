@@ -0,0 +1,120 @@
+//! Every state machine so far has been written from scratch. But many policies, such as "not too
+//! many transitions at once", don't care what the underlying machine does at all. Rather than
+//! adding such a policy to each machine individually, we can write it once as a wrapper that turns
+//! any state machine into a new state machine with the policy layered on top.
+//!
+//! Here we limit how quickly transitions may be applied with a token bucket. The bucket holds up to
+//! `CAPACITY` tokens. Each transition of the inner machine spends one token, and each tick of the
+//! clock adds `REFILL` tokens back. When the bucket is empty, transitions are rejected until it has
+//! been refilled.
+
+use super::StateMachine;
+use std::marker::PhantomData;
+
+/// Wraps the state machine `SM` so that it may only make transitions as quickly as the token bucket
+/// allows.
+pub struct RateLimited<SM, const CAPACITY: u64, const REFILL: u64>(PhantomData<SM>);
+
+/// The state of a rate limited machine is the state of the inner machine along with the number of
+/// tokens left in the bucket.
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct Limited<S> {
+	/// The state of the wrapped machine
+	pub inner: S,
+	/// How many more transitions may be made before the next tick
+	pub tokens: u64,
+}
+
+/// A transition of a rate limited machine is either a transition of the inner machine, or the
+/// passage of time.
+pub enum Throttled<T> {
+	/// Apply this transition to the inner machine, if there is a token left to pay for it.
+	Inner(T),
+	/// Time passes, and the bucket is refilled.
+	Tick,
+}
+
+impl<SM: StateMachine, const CAPACITY: u64, const REFILL: u64> StateMachine
+	for RateLimited<SM, CAPACITY, REFILL>
+where
+	SM::State: Clone,
+{
+	type State = Limited<SM::State>;
+	type Transition = Throttled<SM::Transition>;
+
+	fn next_state(starting_state: &Self::State, t: &Self::Transition) -> Self::State {
+		match t {
+			Throttled::Inner(inner_transition) => match starting_state.tokens.checked_sub(1) {
+				Some(tokens) => Limited {
+					inner: SM::next_state(&starting_state.inner, inner_transition),
+					tokens,
+				},
+				None => starting_state.clone(),
+			},
+			Throttled::Tick => Limited {
+				inner: starting_state.inner.clone(),
+				tokens: starting_state.tokens.saturating_add(REFILL).min(CAPACITY),
+			},
+		}
+	}
+
+	fn human_name() -> String {
+		format!("Rate limited {}", SM::human_name())
+	}
+}
+
+#[cfg(test)]
+use super::p1_switches::LightSwitch;
+
+/// A light switch that may be flipped at most twice in a row, and once more each tick.
+#[cfg(test)]
+type SlowSwitch = RateLimited<LightSwitch, 2, 1>;
+
+#[test]
+fn sm_7_transition_spends_token() {
+	let start = Limited { inner: false, tokens: 2 };
+	let end = SlowSwitch::next_state(&start, &Throttled::Inner(()));
+	let expected = Limited { inner: true, tokens: 1 };
+	assert_eq!(end, expected);
+}
+
+#[test]
+fn sm_7_transition_rejected_when_empty() {
+	let start = Limited { inner: true, tokens: 0 };
+	let end = SlowSwitch::next_state(&start, &Throttled::Inner(()));
+	assert_eq!(end, start);
+}
+
+#[test]
+fn sm_7_tick_refills_bucket() {
+	let start = Limited { inner: true, tokens: 0 };
+	let end = SlowSwitch::next_state(&start, &Throttled::Tick);
+	let expected = Limited { inner: true, tokens: 1 };
+	assert_eq!(end, expected);
+}
+
+#[test]
+fn sm_7_tick_does_not_exceed_capacity() {
+	let start = Limited { inner: false, tokens: 2 };
+	let end = SlowSwitch::next_state(&start, &Throttled::Tick);
+	assert_eq!(end, start);
+}
+
+#[test]
+fn sm_7_burst_then_wait() {
+	let mut state = Limited { inner: false, tokens: 2 };
+	for _ in 0..3 {
+		state = SlowSwitch::next_state(&state, &Throttled::Inner(()));
+	}
+	// Only the first two flips went through.
+	assert_eq!(state, Limited { inner: false, tokens: 0 });
+
+	state = SlowSwitch::next_state(&state, &Throttled::Tick);
+	state = SlowSwitch::next_state(&state, &Throttled::Inner(()));
+	assert_eq!(state, Limited { inner: true, tokens: 0 });
+}
+
+#[test]
+fn sm_7_human_name_mentions_inner_machine() {
+	assert_eq!(SlowSwitch::human_name(), "Rate limited Unnamed state machine");
+}
@@ -0,0 +1,205 @@
+//! The accounted currency from part 4 has a glaring problem: anyone may submit any transaction. So
+//! Bob can transfer Alice's money to himself, and anyone at all can mint new money.
+//!
+//! Real blockchains solve this by attaching an _origin_ to every transaction. The origin records
+//! who is making the transaction, and each transaction checks that its origin is allowed to make
+//! it. In this lesson we write that check once as a wrapper, like the rate limiter in the previous
+//! lesson, so that it can be layered onto any state machine.
+
+use super::{
+	p4_accounted_currency::{AccountedCurrency, AccountingTransaction},
+	StateMachine, User,
+};
+use std::marker::PhantomData;
+
+/// Who is making a transition.
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub enum Origin {
+	/// The system itself, or whoever governs it. Typically allowed to do anything.
+	Root,
+	/// An ordinary user who has signed the transition.
+	Signed(User),
+	/// Nobody in particular. The transition is unsigned.
+	None,
+	/// A motion that a majority of the council voted for. See the council lesson.
+	Council,
+}
+
+/// Decides which origins may make which transitions of the state machine `SM`.
+pub trait OriginPolicy<SM: StateMachine> {
+	/// Whether the given origin may make the given transition.
+	fn allowed(origin: &Origin, transition: &SM::Transition) -> bool;
+}
+
+/// Wraps the state machine `SM` so that every transition carries an origin, and transitions whose
+/// origin is not allowed by the `Policy` are rejected. Rejected transitions leave the state
+/// unchanged.
+pub struct Authorized<SM, Policy>(PhantomData<(SM, Policy)>);
+
+/// A transition along with the origin that is making it.
+pub struct Call<T> {
+	/// Who is making the transition
+	pub origin: Origin,
+	/// The transition to make
+	pub transition: T,
+}
+
+impl<SM, Policy> StateMachine for Authorized<SM, Policy>
+where
+	SM: StateMachine,
+	SM::State: Clone,
+	Policy: OriginPolicy<SM>,
+{
+	type State = SM::State;
+	type Transition = Call<SM::Transition>;
+
+	fn next_state(starting_state: &Self::State, t: &Self::Transition) -> Self::State {
+		if Policy::allowed(&t.origin, &t.transition) {
+			SM::next_state(starting_state, &t.transition)
+		} else {
+			starting_state.clone()
+		}
+	}
+
+	fn human_name() -> String {
+		format!("Authorized {}", SM::human_name())
+	}
+}
+
+/// The usual rules for a currency.
+///
+/// - Only root or the council may mint new money.
+/// - Users may burn their own money, and root may burn anyone's.
+/// - Users may only transfer their own money. Not even root can take someone's money.
+pub struct CurrencyPolicy;
+
+impl OriginPolicy<AccountedCurrency> for CurrencyPolicy {
+	fn allowed(origin: &Origin, transition: &AccountingTransaction) -> bool {
+		match (origin, transition) {
+			(Origin::Root | Origin::Council, AccountingTransaction::Mint { .. }) => true,
+			(Origin::Root, AccountingTransaction::Burn { .. }) => true,
+			(Origin::Signed(who), AccountingTransaction::Burn { burner, .. }) => who == burner,
+			(Origin::Signed(who), AccountingTransaction::Transfer { sender, .. }) => who == sender,
+			_ => false,
+		}
+	}
+}
+
+/// The accounted currency from part 4, with the currency policy enforced.
+pub type AuthorizedCurrency = Authorized<AccountedCurrency, CurrencyPolicy>;
+
+#[cfg(test)]
+use super::arithmetic::Balance;
+#[cfg(test)]
+use std::collections::HashMap;
+
+#[test]
+fn sm_8_root_can_mint() {
+	let start = HashMap::new();
+	let end = AuthorizedCurrency::next_state(
+		&start,
+		&Call {
+			origin: Origin::Root,
+			transition: AccountingTransaction::Mint { minter: User::Alice, amount: Balance(100) },
+		},
+	);
+	let expected = HashMap::from([(User::Alice, Balance(100))]);
+	assert_eq!(end, expected);
+}
+
+#[test]
+fn sm_8_users_cannot_mint() {
+	let start = HashMap::new();
+	let end = AuthorizedCurrency::next_state(
+		&start,
+		&Call {
+			origin: Origin::Signed(User::Alice),
+			transition: AccountingTransaction::Mint { minter: User::Alice, amount: Balance(100) },
+		},
+	);
+	assert_eq!(end, start);
+}
+
+#[test]
+fn sm_8_unsigned_does_nothing() {
+	let start = HashMap::from([(User::Alice, Balance(100))]);
+	let end = AuthorizedCurrency::next_state(
+		&start,
+		&Call {
+			origin: Origin::None,
+			transition: AccountingTransaction::Burn { burner: User::Alice, amount: Balance(10) },
+		},
+	);
+	assert_eq!(end, start);
+}
+
+#[test]
+fn sm_8_users_transfer_their_own_money() {
+	let start = HashMap::from([(User::Alice, Balance(100))]);
+	let end = AuthorizedCurrency::next_state(
+		&start,
+		&Call {
+			origin: Origin::Signed(User::Alice),
+			transition: AccountingTransaction::Transfer {
+				sender: User::Alice,
+				receiver: User::Bob,
+				amount: Balance(10),
+			},
+		},
+	);
+	let expected = HashMap::from([(User::Alice, Balance(90)), (User::Bob, Balance(10))]);
+	assert_eq!(end, expected);
+}
+
+#[test]
+fn sm_8_users_cannot_transfer_others_money() {
+	let start = HashMap::from([(User::Alice, Balance(100))]);
+	let end = AuthorizedCurrency::next_state(
+		&start,
+		&Call {
+			origin: Origin::Signed(User::Bob),
+			transition: AccountingTransaction::Transfer {
+				sender: User::Alice,
+				receiver: User::Bob,
+				amount: Balance(10),
+			},
+		},
+	);
+	assert_eq!(end, start);
+}
+
+#[test]
+fn sm_8_root_cannot_transfer() {
+	let start = HashMap::from([(User::Alice, Balance(100))]);
+	let end = AuthorizedCurrency::next_state(
+		&start,
+		&Call {
+			origin: Origin::Root,
+			transition: AccountingTransaction::Transfer {
+				sender: User::Alice,
+				receiver: User::Bob,
+				amount: Balance(10),
+			},
+		},
+	);
+	assert_eq!(end, start);
+}
+
+#[test]
+fn sm_8_burn_own_or_as_root() {
+	let start = HashMap::from([(User::Alice, Balance(100)), (User::Bob, Balance(50))]);
+	let burn_bob = AccountingTransaction::Burn { burner: User::Bob, amount: Balance(50) };
+
+	let by_alice = AuthorizedCurrency::next_state(
+		&start,
+		&Call { origin: Origin::Signed(User::Alice), transition: burn_bob },
+	);
+	assert_eq!(by_alice, start);
+
+	let burn_bob = AccountingTransaction::Burn { burner: User::Bob, amount: Balance(50) };
+	let by_root = AuthorizedCurrency::next_state(
+		&start,
+		&Call { origin: Origin::Root, transition: burn_bob },
+	);
+	assert_eq!(by_root, HashMap::from([(User::Alice, Balance(100))]));
+}
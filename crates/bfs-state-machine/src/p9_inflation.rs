@@ -0,0 +1,273 @@
+//! Proof of Stake networks pay the people who stake their tokens by minting new tokens every era.
+//! That makes the total issuance of the token grow over time, which is to say the token inflates.
+//!
+//! How much to pay stakers is a balancing act. Stakers secure the network, so we want a healthy
+//! fraction of the tokens to be staked. But tokens that are staked can't be used for anything else,
+//! so we don't want _all_ of them staked either. The usual approach is to choose an ideal staking
+//! rate and pay stakers the most when the actual staking rate is right at the ideal. Whatever the
+//! stakers are not paid out of the era's maximum issuance goes to the treasury instead, so the
+//! total inflation stays predictable regardless of how many tokens are staked.
+//!
+//! All the ratios here are `Perbill`s, so the calculations are exact and reproducible on every
+//! machine.
+
+use super::{
+	arithmetic::{Balance, Perbill},
+	StateMachine,
+};
+
+/// The parameters of the inflation curve.
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub struct InflationConfig {
+	/// The fraction of the total issuance that is minted each era.
+	pub max_inflation_per_era: Perbill,
+	/// The staking rate at which stakers receive the whole era issuance.
+	pub ideal_staking_rate: Perbill,
+	/// The fraction of the era issuance that stakers receive when everything is staked. Between
+	/// the ideal rate and full staking, the stakers' share falls linearly down to this.
+	pub min_payout_ratio: Perbill,
+}
+
+impl InflationConfig {
+	/// The fraction of the era issuance that is paid to stakers at the given staking rate.
+	///
+	/// Below the ideal staking rate, the share rises linearly from nothing to everything. Above the
+	/// ideal rate it falls linearly from everything to the minimum payout ratio.
+	pub fn payout_ratio(&self, staking_rate: Perbill) -> Perbill {
+		let ideal = self.ideal_staking_rate.deconstruct() as u64;
+		let rate = staking_rate.deconstruct() as u64;
+		if rate <= ideal {
+			return Perbill::from_rational(rate, ideal);
+		}
+		let excess = Perbill::from_rational(rate - ideal, Perbill::ACCURACY as u64 - ideal);
+		let drop = excess.saturating_mul(self.min_payout_ratio.left_from_one());
+		Perbill::one().saturating_sub(drop)
+	}
+}
+
+/// This state machine tracks the token supply of a staking network across eras.
+pub struct StakingEconomy;
+
+/// The supply of tokens, and where they are.
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct Economy {
+	/// The inflation curve
+	pub config: InflationConfig,
+	/// Every token in existence, including those that are staked and those in the treasury
+	pub total_issuance: Balance,
+	/// The tokens that are currently staked
+	pub staked: Balance,
+	/// The tokens owned by the treasury
+	pub treasury: Balance,
+}
+
+impl Economy {
+	/// The fraction of all tokens that are staked.
+	pub fn staking_rate(&self) -> Perbill {
+		Perbill::from_rational(self.staked.0, self.total_issuance.0)
+	}
+}
+
+/// Things that change the supply of tokens.
+pub enum EconomyTransition {
+	/// Some free tokens are staked. Nothing happens if there aren't enough free tokens.
+	Stake(Balance),
+	/// Some staked tokens are unstaked. Nothing happens if there aren't that many staked.
+	Unstake(Balance),
+	/// The era ends. The era issuance is minted, and is split between the stakers, whose rewards
+	/// are staked automatically, and the treasury.
+	EndEra,
+}
+
+/// How the issuance for a single era was split up.
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub struct EraIssuance {
+	/// The new tokens paid to stakers
+	pub stakers: Balance,
+	/// The new tokens paid to the treasury
+	pub treasury: Balance,
+}
+
+/// Work out how the next era's issuance will be split up in the given economy.
+pub fn era_issuance(economy: &Economy) -> EraIssuance {
+	let config = &economy.config;
+	let max_issuance = config.max_inflation_per_era.mul_floor(economy.total_issuance.0);
+	let stakers = config.payout_ratio(economy.staking_rate()).mul_floor(max_issuance);
+	EraIssuance { stakers: Balance(stakers), treasury: Balance(max_issuance - stakers) }
+}
+
+impl StateMachine for StakingEconomy {
+	type State = Economy;
+	type Transition = EconomyTransition;
+
+	fn next_state(starting_state: &Economy, t: &EconomyTransition) -> Economy {
+		let mut new_state = starting_state.clone();
+		match t {
+			EconomyTransition::Stake(amount) => {
+				let staked = starting_state.staked.checked_add(*amount);
+				let free = starting_state
+					.total_issuance
+					.checked_sub(starting_state.staked)
+					.and_then(|free| free.checked_sub(starting_state.treasury));
+				if let (Some(staked), Some(free)) = (staked, free) {
+					if *amount <= free {
+						new_state.staked = staked;
+					}
+				}
+			},
+			EconomyTransition::Unstake(amount) => {
+				if let Some(staked) = starting_state.staked.checked_sub(*amount) {
+					new_state.staked = staked;
+				}
+			},
+			EconomyTransition::EndEra => {
+				let issuance = era_issuance(starting_state);
+				let minted = issuance.stakers.checked_add(issuance.treasury);
+				let after = (
+					minted.and_then(|minted| starting_state.total_issuance.checked_add(minted)),
+					starting_state.staked.checked_add(issuance.stakers),
+					starting_state.treasury.checked_add(issuance.treasury),
+				);
+				// If the supply would overflow, nothing is minted at all.
+				if let (Some(total_issuance), Some(staked), Some(treasury)) = after {
+					new_state.total_issuance = total_issuance;
+					new_state.staked = staked;
+					new_state.treasury = treasury;
+				}
+			},
+		}
+		new_state
+	}
+
+	fn human_name() -> String {
+		"Staking economy".into()
+	}
+}
+
+/// A summary of one simulated era.
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct EraReport {
+	/// Which era this is, starting from zero
+	pub era: usize,
+	/// The staking rate during the era
+	pub staking_rate: Perbill,
+	/// How the era's issuance was split up
+	pub issuance: EraIssuance,
+	/// The total issuance once the era ended
+	pub total_issuance: Balance,
+}
+
+/// Run the economy for the given number of eras without any staking or unstaking, and report on
+/// each era.
+pub fn simulate_eras(start: &Economy, eras: usize) -> Vec<EraReport> {
+	let mut economy = start.clone();
+	let mut reports = Vec::with_capacity(eras);
+	for era in 0..eras {
+		let staking_rate = economy.staking_rate();
+		let issuance = era_issuance(&economy);
+		economy = StakingEconomy::next_state(&economy, &EconomyTransition::EndEra);
+		reports.push(EraReport {
+			era,
+			staking_rate,
+			issuance,
+			total_issuance: economy.total_issuance,
+		});
+	}
+	reports
+}
+
+#[cfg(test)]
+fn test_config() -> InflationConfig {
+	InflationConfig {
+		max_inflation_per_era: Perbill::from_percent(10),
+		ideal_staking_rate: Perbill::from_percent(50),
+		min_payout_ratio: Perbill::from_percent(20),
+	}
+}
+
+#[cfg(test)]
+fn test_economy(staked: u64) -> Economy {
+	Economy {
+		config: test_config(),
+		total_issuance: Balance(1_000),
+		staked: Balance(staked),
+		treasury: Balance::zero(),
+	}
+}
+
+#[test]
+fn sm_9_payout_ratio_curve() {
+	let config = test_config();
+
+	assert_eq!(config.payout_ratio(Perbill::zero()), Perbill::zero());
+	assert_eq!(config.payout_ratio(Perbill::from_percent(25)), Perbill::from_percent(50));
+	assert_eq!(config.payout_ratio(Perbill::from_percent(50)), Perbill::one());
+	assert_eq!(config.payout_ratio(Perbill::from_percent(75)), Perbill::from_percent(60));
+	assert_eq!(config.payout_ratio(Perbill::one()), Perbill::from_percent(20));
+}
+
+#[test]
+fn sm_9_ideal_staking_pays_stakers_everything() {
+	let end = StakingEconomy::next_state(&test_economy(500), &EconomyTransition::EndEra);
+
+	assert_eq!(end.total_issuance, Balance(1_100));
+	assert_eq!(end.staked, Balance(600));
+	assert_eq!(end.treasury, Balance::zero());
+}
+
+#[test]
+fn sm_9_remainder_goes_to_treasury() {
+	let end = StakingEconomy::next_state(&test_economy(250), &EconomyTransition::EndEra);
+
+	assert_eq!(end.total_issuance, Balance(1_100));
+	assert_eq!(end.staked, Balance(300));
+	assert_eq!(end.treasury, Balance(50));
+}
+
+#[test]
+fn sm_9_over_staking_pays_less() {
+	let end = StakingEconomy::next_state(&test_economy(1_000), &EconomyTransition::EndEra);
+
+	assert_eq!(end.total_issuance, Balance(1_100));
+	assert_eq!(end.staked, Balance(1_020));
+	assert_eq!(end.treasury, Balance(80));
+}
+
+#[test]
+fn sm_9_stake_only_free_tokens() {
+	let start = Economy { treasury: Balance(100), ..test_economy(800) };
+
+	let end = StakingEconomy::next_state(&start, &EconomyTransition::Stake(Balance(101)));
+	assert_eq!(end, start);
+
+	let end = StakingEconomy::next_state(&start, &EconomyTransition::Stake(Balance(100)));
+	assert_eq!(end.staked, Balance(900));
+}
+
+#[test]
+fn sm_9_unstake() {
+	let start = test_economy(300);
+
+	let end = StakingEconomy::next_state(&start, &EconomyTransition::Unstake(Balance(301)));
+	assert_eq!(end, start);
+
+	let end = StakingEconomy::next_state(&start, &EconomyTransition::Unstake(Balance(100)));
+	assert_eq!(end.staked, Balance(200));
+}
+
+#[test]
+fn sm_9_simulated_eras_report() {
+	let reports = simulate_eras(&test_economy(500), 3);
+
+	assert_eq!(reports.len(), 3);
+	assert_eq!(reports[0].issuance, EraIssuance { stakers: Balance(100), treasury: Balance(0) });
+	assert_eq!(reports[0].total_issuance, Balance(1_100));
+	// Stakers compound their rewards, so the staking rate creeps above the ideal.
+	assert!(reports[1].staking_rate > Perbill::from_percent(50));
+	assert!(reports[2].issuance.treasury > Balance::zero());
+	for report in &reports {
+		let max_issuance = report.issuance.stakers.checked_add(report.issuance.treasury).unwrap();
+		let previous_total = report.total_issuance.checked_sub(max_issuance).unwrap();
+		assert_eq!(max_issuance, Balance(previous_total.0 / 10));
+	}
+}
@@ -0,0 +1,113 @@
+//! Sources of the current time.
+//!
+//! Anything that depends on time, such as slot based consensus or header timestamps, should take a
+//! [`Clock`] rather than reading the system time directly. In production that clock is the
+//! [`SystemClock`]. In tests it is a [`FixedClock`] or a [`SimulatedClock`], so that every test
+//! sees exactly the same times on every run.
+
+use std::{
+	cell::Cell,
+	time::{SystemTime, UNIX_EPOCH},
+};
+
+/// A source of the current time.
+pub trait Clock {
+	/// The current time in milliseconds since the unix epoch.
+	fn now(&self) -> u64;
+
+	/// The slot that the current time falls in, when time is divided into slots of the given
+	/// duration in milliseconds.
+	fn current_slot(&self, slot_duration: u64) -> u64 {
+		self.now() / slot_duration.max(1)
+	}
+}
+
+impl<C: Clock + ?Sized> Clock for &C {
+	fn now(&self) -> u64 {
+		(**self).now()
+	}
+}
+
+/// The real time, as reported by the operating system.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+	fn now(&self) -> u64 {
+		// A system clock set before 1970 is treated as reading exactly the epoch.
+		let since_epoch = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default();
+		u64::try_from(since_epoch.as_millis()).unwrap_or(u64::MAX)
+	}
+}
+
+/// A clock that is stopped at a single moment.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct FixedClock(pub u64);
+
+impl Clock for FixedClock {
+	fn now(&self) -> u64 {
+		self.0
+	}
+}
+
+/// A clock that only moves when it is told to. Several parts of a simulation can share one
+/// simulated clock by reference, and they will all see it move together.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SimulatedClock {
+	now: Cell<u64>,
+}
+
+impl SimulatedClock {
+	/// Create a clock showing the given time.
+	pub fn new(now: u64) -> Self {
+		SimulatedClock { now: Cell::new(now) }
+	}
+
+	/// Move the clock forward by the given number of milliseconds.
+	pub fn advance(&self, millis: u64) {
+		self.now.set(self.now.get().saturating_add(millis));
+	}
+
+	/// Set the clock to the given time. Simulated time may even go backwards.
+	pub fn set(&self, now: u64) {
+		self.now.set(now);
+	}
+}
+
+impl Clock for SimulatedClock {
+	fn now(&self) -> u64 {
+		self.now.get()
+	}
+}
+
+#[test]
+fn time_fixed_clock_never_moves() {
+	let clock = FixedClock(6_500);
+
+	assert_eq!(clock.now(), 6_500);
+	assert_eq!(clock.now(), 6_500);
+	assert_eq!(clock.current_slot(3_000), 2);
+}
+
+#[test]
+fn time_simulated_clock_is_shared() {
+	let clock = SimulatedClock::new(1_000);
+	let observer = &clock;
+
+	clock.advance(2_000);
+	assert_eq!(observer.now(), 3_000);
+	assert_eq!(observer.current_slot(1_000), 3);
+
+	clock.set(0);
+	assert_eq!(observer.now(), 0);
+}
+
+#[test]
+fn time_zero_slot_duration_does_not_panic() {
+	assert_eq!(FixedClock(10).current_slot(0), 10);
+}
+
+#[test]
+fn time_system_clock_is_after_2020() {
+	assert!(SystemClock.now() > 1_577_836_800_000);
+}
@@ -0,0 +1,44 @@
+//! The long-term stable API of the course.
+//!
+//! Lesson code is refactored freely from one version of the course to the next. The types exported
+//! here are the exception. They follow semver, so external tools should import from this module
+//! rather than from the individual chapters. The items exported here are listed in
+//! `tests/public_api.txt`, and the `public_api` integration test fails whenever the two disagree.
+
+pub use bfs_blockchain::{Block, Hash, Header};
+pub use bfs_state_machine::StateMachine;
+
+use bfs_blockchain::p4_batched_extrinsics::Header as LessonHeader;
+
+/// Judge which of several chains is the best one.
+///
+/// This is the fork choice trait from the blockchain chapter, but generic over the header type. It
+/// works on this module's [`Header`] unless told otherwise, so external rules can implement it
+/// without naming any lesson types. The rules from the fork choice lesson implement it for the
+/// headers that lesson was written for.
+pub trait ForkChoice<H = Header> {
+	/// Whether the first chain is better than the second. The chains are assumed to be valid.
+	fn first_chain_is_better(&self, chain_1: &[H], chain_2: &[H]) -> bool;
+
+	/// The best of the candidate chains. Comparing two chains is enough to compare any number of
+	/// them, so this has a provided implementation.
+	fn best_chain<'a>(&self, candidate_chains: &[&'a [H]]) -> &'a [H] {
+		let mut best_chain = candidate_chains[0];
+		for candidate in &candidate_chains[1..] {
+			if self.first_chain_is_better(candidate, best_chain) {
+				best_chain = candidate;
+			}
+		}
+		best_chain
+	}
+}
+
+impl<R: bfs_blockchain::ForkChoice> ForkChoice<LessonHeader> for R {
+	fn first_chain_is_better(&self, chain_1: &[LessonHeader], chain_2: &[LessonHeader]) -> bool {
+		bfs_blockchain::ForkChoice::first_chain_is_better(self, chain_1, chain_2)
+	}
+
+	fn best_chain<'a>(&self, candidate_chains: &[&'a [LessonHeader]]) -> &'a [LessonHeader] {
+		bfs_blockchain::ForkChoice::best_chain(self, candidate_chains)
+	}
+}
@@ -1,18 +1,18 @@
 //! Learn the fundamentals of blockchain by building it from scratch.
+//!
+//! Each chapter of the course lives in its own crate so that later chapters can build on the
+//! public APIs of earlier ones. This crate re-exports every chapter under its original path.
+//!
+//! The chapters change as the lessons evolve. Tools built on top of this crate should depend only
+//! on the [`api`] module, which follows semver.
 
-use std::{
-	collections::hash_map::DefaultHasher,
-	hash::{Hash, Hasher},
-};
+pub mod api;
 
-mod c1_state_machine;
-mod c2_blockchain;
-mod c3_consensus;
-mod c4_framework;
-
-// Simple helper to do some hashing.
-fn hash<T: Hash>(t: &T) -> u64 {
-	let mut s = DefaultHasher::new();
-	t.hash(&mut s);
-	s.finish()
-}
+#[doc(hidden)]
+pub use bfs_blockchain as c2_blockchain;
+#[doc(hidden)]
+pub use bfs_consensus as c3_consensus;
+#[doc(hidden)]
+pub use bfs_node as c4_framework;
+#[doc(hidden)]
+pub use bfs_state_machine as c1_state_machine;
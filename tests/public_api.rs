@@ -0,0 +1,88 @@
+//! Guards the stable public API.
+//!
+//! The items that `blockchain_from_scratch::api` exports are checked against the list in
+//! `public_api.txt`, so adding or removing one fails until the list is updated along with the
+//! version. Every item is also used here the way an external tool would use it. If this file stops
+//! compiling, the change that broke it is a breaking change and requires a major version bump.
+
+use blockchain_from_scratch::api::{Block, ForkChoice, Hash, Header, StateMachine};
+
+/// An external state machine implementing the stable trait.
+struct Counter;
+
+impl StateMachine for Counter {
+	type State = u64;
+	type Transition = u64;
+
+	fn next_state(starting_state: &u64, t: &u64) -> u64 {
+		starting_state + t
+	}
+}
+
+/// An external fork choice rule implementing the stable trait.
+struct FirstCandidate;
+
+impl ForkChoice for FirstCandidate {
+	fn first_chain_is_better(&self, _: &[Header], _: &[Header]) -> bool {
+		false
+	}
+}
+
+/// How the items that the `api` module declares itself begin.
+const ITEM_KEYWORDS: [&str; 5] = ["pub trait ", "pub struct ", "pub enum ", "pub type ", "pub fn "];
+
+/// The names of the items that the `api` module exports, read from its source.
+fn exported_items() -> Vec<String> {
+	let source = include_str!("../src/api.rs");
+	let mut items = Vec::new();
+	for line in source.lines() {
+		if let Some(path) = line.strip_prefix("pub use ") {
+			let path = path.trim_end_matches(';');
+			let names = match path.split_once('{') {
+				Some((_, names)) => names.trim_end_matches('}'),
+				None => path.rsplit("::").next().unwrap_or(path),
+			};
+			items.extend(names.split(',').map(|name| name.trim().to_string()));
+		} else if let Some(item) =
+			ITEM_KEYWORDS.iter().find_map(|keyword| line.strip_prefix(keyword))
+		{
+			let name = item.split(|c: char| !c.is_alphanumeric() && c != '_').next().unwrap_or("");
+			items.push(name.to_string());
+		}
+	}
+	items.retain(|name| !name.is_empty());
+	items.sort();
+	items
+}
+
+#[test]
+fn public_api_matches_snapshot() {
+	let snapshot: Vec<&str> = include_str!("public_api.txt").lines().collect();
+	assert_eq!(exported_items(), snapshot, "the stable API changed; update public_api.txt");
+}
+
+#[test]
+fn public_api_state_machine() {
+	assert_eq!(Counter::next_state(&1, &2), 3);
+	assert_eq!(Counter::human_name(), "Unnamed state machine");
+}
+
+#[test]
+fn public_api_hash_is_u64() {
+	let hash: Hash = u64::MAX;
+	assert_eq!(hash, u64::MAX);
+}
+
+#[test]
+fn public_api_block_and_header_are_nameable() {
+	fn assert_traits<T: Clone + std::fmt::Debug + Eq + std::hash::Hash>() {}
+
+	assert_traits::<Block>();
+	assert_traits::<Header>();
+}
+
+#[test]
+fn public_api_fork_choice_best_chain() {
+	assert!(!FirstCandidate.first_chain_is_better(&[], &[]));
+	assert!(FirstCandidate.best_chain(&[&[], &[]]).is_empty());
+}